@@ -0,0 +1,86 @@
+// times the OS keyboard scan against the core's own system VIA and keyboard
+//
+// with CH22_OS_ROM pointing at a 16K OS image the real OS is run, idling at the
+// prompt with a key held so every 100Hz interrupt interrogates the keyboard
+//
+// without one a stand in is run, modelled on the MOS 1.20 scan: a free running T1
+// raises the 100Hz IRQ and the handler interrogates every key code in turn by writing
+// it to port A and reading it back (STX &FE4F / LDX &FE4F, as OSBYTE &79 does)
+//
+// run with `cargo bench --bench io_dispatch`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use ch22_core::{BbcKey, Core};
+
+const FIELDS: u32 = 500;
+const RUNS: u32 = 5;
+
+fn modelled_os_rom() -> Vec<u8> {
+    let mut os_rom = vec![0; 0x4000];
+
+    let program = [
+        0xa9, 0x03, 0x8d, 0x40, 0xfe, // LDA #3: STA &FE40 (IC32 keyboard enable low)
+        0xa9, 0xff, 0x8d, 0x42, 0xfe, // LDA #&FF: STA &FE42 (DDRB)
+        0xa9, 0x7f, 0x8d, 0x43, 0xfe, // LDA #&7F: STA &FE43 (DDRA)
+        0xa9, 0x40, 0x8d, 0x4b, 0xfe, // LDA #&40: STA &FE4B (T1 free run)
+        0xa9, 0x0e, 0x8d, 0x44, 0xfe, // LDA #&0E: STA &FE44
+        0xa9, 0x27, 0x8d, 0x45, 0xfe, // LDA #&27: STA &FE45 (10ms)
+        0xa9, 0xc0, 0x8d, 0x4e, 0xfe, // LDA #&C0: STA &FE4E (T1 IRQ on)
+        0x58, // CLI
+        0x4c, 0x24, 0xc0, // JMP &C024
+    ];
+    let irq_handler = [
+        0xad, 0x44, 0xfe, // LDA &FE44 (clear T1)
+        0xa2, 0x79, // LDX #&79
+        0x8e, 0x4f, 0xfe, // STX &FE4F
+        0xae, 0x4f, 0xfe, // LDX &FE4F
+        0x8a, // TXA
+        0x29, 0x7f, // AND #&7F
+        0xaa, // TAX
+        0xca, // DEX
+        0x10, 0xf3, // BPL &C105
+        0x40, // RTI
+    ];
+
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x100..0x100 + irq_handler.len()].copy_from_slice(&irq_handler);
+    os_rom[0x3ffc..].copy_from_slice(&[0x00, 0xc0, 0x00, 0xc1]); // reset &C000, irq &C100
+
+    os_rom
+}
+
+fn main() {
+    let (os_rom, source) = match std::env::var("CH22_OS_ROM") {
+        Ok(path) => (
+            std::fs::read(&path).expect("OS ROM should be readable"),
+            path,
+        ),
+        Err(_) => (modelled_os_rom(), "modelled scan".to_string()),
+    };
+
+    let mut timings = Vec::new();
+
+    for _ in 0..RUNS {
+        let mut core = Box::new(Core::default());
+        core.setup();
+        core.load_rom(16, &os_rom);
+        core.reset();
+
+        // give the OS time to start before holding down a key
+        core.run_fields(50);
+        core.key_down(BbcKey { row: 4, column: 1 }); // A
+
+        let start = Instant::now();
+        black_box(core.run_fields(FIELDS));
+        timings.push(start.elapsed());
+    }
+
+    let best = timings.iter().min().unwrap();
+
+    println!(
+        "keyboard scan ({source}): {FIELDS} fields in {best:?} (best of {RUNS}), {:.1} fields/s",
+        FIELDS as f64 / best.as_secs_f64()
+    );
+}
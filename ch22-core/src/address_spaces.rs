@@ -2,6 +2,6 @@ mod io_space;
 mod ram;
 mod rom;
 
-pub use io_space::IOSpace;
-pub use ram::Ram;
+pub use io_space::{FloatingBus, IOAccess, IOAccessFilter, IOAccessKind, IOSpace, UnmappedAccess};
+pub use ram::{PowerOnRam, Ram};
 pub use rom::Rom;
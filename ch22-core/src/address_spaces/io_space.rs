@@ -1,91 +1,340 @@
+mod access_log;
 #[cfg(test)]
 mod tests;
+mod unmapped_access;
+
+pub use access_log::{IOAccess, IOAccessFilter, IOAccessKind};
+pub use unmapped_access::{FloatingBus, UnmappedAccess};
 
 use crate::cpu::InterruptType;
-use crate::devices::{DeviceSpeed, IODevice, IODeviceID, IODeviceList};
+use crate::devices::{
+    DeviceDescription, DeviceSpeed, IOAddressRange, IODevice, IODeviceID, IODeviceList, ResetType,
+};
 use crate::system::Clock;
 use crate::word::Word;
+use access_log::IOAccessLog;
+use unmapped_access::UnmappedAccessLog;
 
 #[derive(Default)]
 pub struct IOSpace {
     devices: IODeviceList,
-    phase_2_data: Option<(Word, u8)>,
+    phase_2_data: Option<(Word, u8, u64)>,
+    peeking: bool,
+    floating_bus: FloatingBus,
+    bus_value: u8,
+    instruction_pc: Word,
+    unmapped_log: Option<UnmappedAccessLog>,
+    access_log: Option<IOAccessLog>,
+    // kept while logging is off, so it applies whenever logging is enabled
+    access_filter: IOAccessFilter,
 }
 
 impl IOSpace {
     pub fn add_device(
         &mut self,
-        addresses: &[u16],
+        address_range: IOAddressRange,
         device: Box<dyn IODevice>,
         interrupt_type: Option<InterruptType>,
         speed: DeviceSpeed,
     ) -> IODeviceID {
         self.devices
-            .add_device(addresses, device, interrupt_type, speed)
+            .add_device(address_range, device, interrupt_type, speed)
     }
 
     pub fn get_interrupt(&mut self, interrupt_type: InterruptType, clock: &Clock) -> bool {
         self.devices
-            .get_by_interrupt_type(interrupt_type)
-            .any(|device| device.get_interrupt(clock.get_cycles()))
+            .any_by_interrupt_type(interrupt_type, |device| {
+                device.get_interrupt(clock.get_cycles())
+            })
+    }
+
+    pub fn remove_device(&mut self, device_id: IODeviceID) -> Option<Box<dyn IODevice>> {
+        self.devices.remove_device(device_id)
+    }
+
+    pub fn replace_device(
+        &mut self,
+        device_id: IODeviceID,
+        device: Box<dyn IODevice>,
+    ) -> Option<Box<dyn IODevice>> {
+        self.devices.replace_device(device_id, device)
+    }
+
+    // false if the handle is stale
+    pub fn set_interrupt(&mut self, device_id: IODeviceID, iterrupt: bool) -> bool {
+        let Some(device) = self.devices.get_by_id(device_id) else {
+            return false;
+        };
+
+        device.set_interrupt(iterrupt);
+
+        true
     }
 
-    pub fn set_interrupt(&mut self, device_id: IODeviceID, iterrupt: bool) {
-        self.devices.get_by_id(device_id).set_interrupt(iterrupt);
+    // false if the handle is stale
+    pub fn set_reset_on_break(&mut self, device_id: IODeviceID, reset_on_break: bool) -> bool {
+        self.devices.set_reset_on_break(device_id, reset_on_break)
+    }
+
+    pub fn reset(&mut self, reset_type: ResetType) {
+        self.phase_2_data = None;
+
+        self.devices.reset(reset_type);
+    }
+
+    pub fn on_field_complete(&mut self, cycles: u64) {
+        self.devices.for_each(|device| {
+            device.on_field_complete(cycles);
+        });
+    }
+
+    // None if the handle is stale or the device has no state to save
+    pub fn save_device_state(&self, device_id: IODeviceID) -> Option<Vec<u8>> {
+        self.devices.get(device_id)?.save_state()
+    }
+
+    pub fn load_device_state(&mut self, device_id: IODeviceID, state: &[u8]) -> Result<(), String> {
+        let Some(device) = self.devices.get_by_id(device_id) else {
+            return Err("Device has been removed".to_string());
+        };
+
+        device.load_state(state)
+    }
+
+    // devices which can describe themselves
+    pub fn describe_devices(&self) -> Vec<(IODeviceID, DeviceDescription)> {
+        self.devices.describe()
+    }
+
+    // while peeking reads are answered by IODevice::peek and writes are dropped,
+    // letting an address map be walked without side effects or clock changes
+    pub fn set_peeking(&mut self, peeking: bool) {
+        self.peeking = peeking;
+    }
+
+    pub fn peek(&mut self, address: Word) -> u8 {
+        self.devices
+            .get_by_address(address)
+            .and_then(|(device, register)| device.peek(address, register))
+            .unwrap_or_else(|| self.floating_value(address))
+    }
+
+    pub fn set_floating_bus(&mut self, floating_bus: FloatingBus) {
+        self.floating_bus = floating_bus;
+    }
+
+    // the value last driven onto the data bus by the cpu or a device
+    pub fn set_bus_value(&mut self, value: u8) {
+        self.bus_value = value;
+    }
+
+    // address of the instruction being executed, recorded against logged accesses
+    pub fn set_instruction_pc(&mut self, pc: Word) {
+        self.instruction_pc = pc;
+    }
+
+    // enabling clears any previously logged accesses
+    pub fn set_unmapped_logging(&mut self, enabled: bool) {
+        self.unmapped_log = enabled.then(UnmappedAccessLog::default);
+    }
+
+    pub fn take_unmapped_accesses(&mut self) -> Vec<UnmappedAccess> {
+        self.unmapped_log
+            .as_mut()
+            .map(UnmappedAccessLog::take)
+            .unwrap_or_default()
+    }
+
+    // keeps the latest capacity device accesses, 0 disables logging
+    // enabling clears any previously logged accesses
+    pub fn set_access_logging(&mut self, capacity: usize) {
+        self.access_log =
+            (capacity > 0).then(|| IOAccessLog::new(capacity, self.access_filter.clone()));
+    }
+
+    pub fn set_access_filter(&mut self, filter: IOAccessFilter) {
+        if let Some(log) = &mut self.access_log {
+            log.set_filter(filter.clone());
+        }
+
+        self.access_filter = filter;
+    }
+
+    pub fn take_accesses(&mut self) -> Vec<IOAccess> {
+        self.access_log
+            .as_mut()
+            .map(IOAccessLog::take)
+            .unwrap_or_default()
+    }
+
+    fn floating_value(&self, address: Word) -> u8 {
+        match self.floating_bus {
+            FloatingBus::Fixed(value) => value,
+            FloatingBus::LastBusValue => self.bus_value,
+            FloatingBus::OpenBus => address.1,
+        }
+    }
+
+    fn log_unmapped(&mut self, address: Word, value: u8, is_write: bool, clock: &Clock) {
+        if let Some(log) = &mut self.unmapped_log {
+            log.push(UnmappedAccess {
+                address: address.into(),
+                value,
+                is_write,
+                pc: self.instruction_pc.into(),
+                cycles: clock.get_cycles(),
+            });
+        }
+    }
+
+    fn log_access(
+        &mut self,
+        kind: IOAccessKind,
+        address: Word,
+        value: u8,
+        cycles: u64,
+        stretch_cycles: u64,
+    ) {
+        let Some(log) = &mut self.access_log else {
+            return;
+        };
+
+        let Some(device_id) = self.devices.get_id_by_address(address) else {
+            return;
+        };
+
+        log.push(IOAccess {
+            kind,
+            address: address.into(),
+            value,
+            cycles,
+            pc: self.instruction_pc.into(),
+            device_id,
+            stretch_cycles: stretch_cycles as u8,
+        });
     }
 
     pub fn read(&mut self, address: Word, clock: &mut Clock) -> u8 {
-        let Some((device, config)) = self.devices.get_with_config_by_address(address) else {
-            return 0xff;
+        if self.peeking {
+            return self.peek(address);
+        }
+
+        self.phase_2();
+
+        let Some((device, register, config)) = self.devices.get_with_config_by_address(address)
+        else {
+            let value = self.floating_value(address);
+
+            self.log_unmapped(address, value, false, clock);
+
+            return value;
         };
 
-        access(|cycles| device.read(address, cycles), &config.speed, clock)
+        let start_cycles = clock.get_cycles();
+
+        let (value, cycles) = access(
+            |cycles| (device.read(address, register, cycles), cycles),
+            BusAccess::Read,
+            &config.speed,
+            clock,
+        );
+
+        if self.access_log.is_some() {
+            let stretch_cycles = clock.get_cycles() - start_cycles;
+
+            self.log_access(IOAccessKind::Read, address, value, cycles, stretch_cycles);
+        }
+
+        value
     }
 
     pub fn write(&mut self, address: Word, value: u8, clock: &mut Clock) {
-        let Some((device, config)) = self.devices.get_with_config_by_address(address) else {
+        if self.peeking {
+            return;
+        }
+
+        self.phase_2();
+
+        let Some((device, register, config)) = self.devices.get_with_config_by_address(address)
+        else {
+            self.log_unmapped(address, value, true, clock);
+
             return;
         };
 
-        let needs_phase_2 = access(
-            |cycles| device.write(address, value, cycles),
+        let start_cycles = clock.get_cycles();
+
+        let (needs_phase_2, cycles) = access(
+            |cycles| (device.write(address, register, value, cycles), cycles),
+            BusAccess::Write,
             &config.speed,
             clock,
         );
 
+        if self.access_log.is_some() {
+            let stretch_cycles = clock.get_cycles() - start_cycles;
+
+            self.log_access(IOAccessKind::Write, address, value, cycles, stretch_cycles);
+        }
+
         if needs_phase_2 {
-            self.phase_2_data = Some((address, value));
+            self.phase_2_data = Some((address, value, cycles));
         }
     }
 
-    pub fn phase_2(&mut self, clock: &Clock) {
-        if let Some((address, value)) = self.phase_2_data {
-            if let Some(device) = self.devices.get_by_address(address) {
-                device.phase_2(address, value, clock.get_cycles());
+    // passes on a write needing phase 2 with the cycle it was latched in, delivered before
+    // anything else reaches the devices: the next io access, interrupt poll or device sync
+    pub fn phase_2(&mut self) {
+        if let Some((address, value, cycles)) = self.phase_2_data {
+            if let Some((device, register)) = self.devices.get_by_address(address) {
+                device.phase_2(address, register, value, cycles);
             }
 
+            self.log_access(IOAccessKind::Phase2, address, value, cycles, 0);
+
             self.phase_2_data = None;
         }
     }
 
-    pub fn on_vsync_change(&mut self, vsync: bool) {
+    pub fn on_vsync_change(&mut self, vsync: bool, cycles: u64) {
         self.devices.for_each(|device| {
-            device.on_vsync_change(vsync);
+            device.on_vsync_change(vsync, cycles);
         });
     }
 }
 
-pub fn access<F: FnOnce(u64) -> T, T>(access_fn: F, speed: &DeviceSpeed, clock: &mut Clock) -> T {
+pub enum BusAccess {
+    Read,
+    Write,
+}
+
+// 1MHz cycles start on even 2MHz cycles. A 1MHz access stretches the cpu cycle to
+// the end of the next whole 1MHz cycle, 1 extra 2MHz cycle when in phase and 2 when
+// not. Reads sample as 1MHz phi2 starts, the last 2MHz cycle of the stretched cycle,
+// and writes are latched as it ends, the cycle the cpu resumes in.
+pub fn access<F: FnOnce(u64) -> T, T>(
+    access_fn: F,
+    bus_access: BusAccess,
+    speed: &DeviceSpeed,
+    clock: &mut Clock,
+) -> T {
     match speed {
         DeviceSpeed::OneMhz => {
             clock.one_mhz_sync();
+            clock.tick();
+            clock.sync_devices();
 
-            let value = access_fn(clock.get_cycles());
+            let cycles = clock.get_cycles();
 
-            clock.inc();
+            match bus_access {
+                BusAccess::Read => access_fn(cycles),
+                BusAccess::Write => access_fn(cycles + 1),
+            }
+        }
+        DeviceSpeed::TwoMhz => {
+            clock.sync_devices();
 
-            value
+            access_fn(clock.get_cycles())
         }
-        DeviceSpeed::TwoMhz => access_fn(clock.get_cycles()),
     }
 }
@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::devices::IODeviceID;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IOAccessKind {
+    Read,
+    Write,
+    Phase2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IOAccess {
+    pub kind: IOAccessKind,
+    pub address: u16,
+    pub value: u8,
+    // when the device sampled the access
+    pub cycles: u64,
+    pub pc: u16,
+    pub device_id: IODeviceID,
+    // extra 2MHz cycles added for a 1MHz device
+    pub stretch_cycles: u8,
+}
+
+impl fmt::Display for IOAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            IOAccessKind::Read => "R",
+            IOAccessKind::Write => "W",
+            IOAccessKind::Phase2 => "P",
+        };
+
+        write!(
+            f,
+            "{kind} &{:04X} &{:02X} PC=&{:04X} cycles={} device={} stretch={}",
+            self.address,
+            self.value,
+            self.pc,
+            self.cycles,
+            self.device_id.to_bits(),
+            self.stretch_cycles
+        )
+    }
+}
+
+// accesses have to match both filters to be logged
+#[derive(Clone, Default)]
+pub struct IOAccessFilter {
+    pub device_id: Option<IODeviceID>,
+    pub addresses: Option<RangeInclusive<u16>>,
+}
+
+impl IOAccessFilter {
+    fn matches(&self, access: &IOAccess) -> bool {
+        self.device_id
+            .is_none_or(|device_id| device_id == access.device_id)
+            && self
+                .addresses
+                .as_ref()
+                .is_none_or(|addresses| addresses.contains(&access.address))
+    }
+}
+
+// most recent accesses passing the filter, oldest dropped once full
+pub struct IOAccessLog {
+    accesses: VecDeque<IOAccess>,
+    capacity: usize,
+    filter: IOAccessFilter,
+}
+
+impl IOAccessLog {
+    pub fn new(capacity: usize, filter: IOAccessFilter) -> Self {
+        IOAccessLog {
+            accesses: VecDeque::with_capacity(capacity),
+            capacity,
+            filter,
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: IOAccessFilter) {
+        self.filter = filter;
+    }
+
+    pub fn push(&mut self, access: IOAccess) {
+        if !self.filter.matches(&access) {
+            return;
+        }
+
+        if self.accesses.len() == self.capacity {
+            self.accesses.pop_front();
+        }
+
+        self.accesses.push_back(access);
+    }
+
+    pub fn take(&mut self) -> Vec<IOAccess> {
+        self.accesses.drain(..).collect()
+    }
+}
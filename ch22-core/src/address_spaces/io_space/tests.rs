@@ -1,9 +1,7 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
 use super::*;
 use crate::cpu::InterruptType;
-use crate::devices::{TimerDeviceList, io_device_mock::*};
+use crate::devices::{IOAddressRange, Scheduler, io_device_mock::*};
+use crate::shared::Shared;
 use crate::system::Clock;
 
 use DeviceSpeed::*;
@@ -15,9 +13,9 @@ const TEST_VALUE: u8 = 4;
 #[test]
 fn it_reads_from_a_two_mhz_device_without_adjusting_cycles() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, TwoMhz, false, None);
 
@@ -34,9 +32,9 @@ fn it_reads_from_a_two_mhz_device_without_adjusting_cycles() {
 #[test]
 fn it_writes_to_a_two_mhz_device_without_adjusting_cycles() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, TwoMhz, false, None);
 
@@ -50,11 +48,11 @@ fn it_writes_to_a_two_mhz_device_without_adjusting_cycles() {
 }
 
 #[test]
-fn it_reads_from_a_one_mhz_device_with_an_additional_cycle_afterwards() {
+fn it_reads_from_a_one_mhz_device_in_one_mhz_phi2_with_an_additional_cycle() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, None);
 
@@ -64,16 +62,16 @@ fn it_reads_from_a_one_mhz_device_with_an_additional_cycle_afterwards() {
     assert_eq!(read_value, TEST_VALUE);
     assert_eq!(
         *test_device_accesses.borrow().memory,
-        [MemoryAccess::Read(TEST_ADDRESS, 1000)]
+        [MemoryAccess::Read(TEST_ADDRESS, 1001)]
     );
 }
 
 #[test]
 fn it_reads_from_a_one_mhz_device_syncing_to_even_cycles_beforehand() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1001u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, None);
 
@@ -83,16 +81,16 @@ fn it_reads_from_a_one_mhz_device_syncing_to_even_cycles_beforehand() {
     assert_eq!(read_value, TEST_VALUE);
     assert_eq!(
         *test_device_accesses.borrow().memory,
-        [MemoryAccess::Read(TEST_ADDRESS, 1002)]
+        [MemoryAccess::Read(TEST_ADDRESS, 1003)]
     );
 }
 
 #[test]
-fn it_writes_to_a_one_mhz_device_with_an_additional_cycle_afterwards() {
+fn it_writes_to_a_one_mhz_device_as_one_mhz_phi2_ends_with_an_additional_cycle() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, None);
 
@@ -101,16 +99,16 @@ fn it_writes_to_a_one_mhz_device_with_an_additional_cycle_afterwards() {
     assert_eq!(cycles, 1001);
     assert_eq!(
         *test_device_accesses.borrow().memory,
-        [MemoryAccess::Write(TEST_ADDRESS, 12, 1000)]
+        [MemoryAccess::Write(TEST_ADDRESS, 12, 1002)]
     );
 }
 
 #[test]
 fn it_writes_to_a_one_mhz_device_syncing_to_even_cycles_beforehand() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1001u64;
-    let mut clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
 
     let test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, None);
 
@@ -119,16 +117,16 @@ fn it_writes_to_a_one_mhz_device_syncing_to_even_cycles_beforehand() {
     assert_eq!(cycles, 1003);
     assert_eq!(
         *test_device_accesses.borrow().memory,
-        [MemoryAccess::Write(TEST_ADDRESS, 12, 1002)]
+        [MemoryAccess::Write(TEST_ADDRESS, 12, 1004)]
     );
 }
 
 #[test]
 fn it_only_reads_the_irq_interrupt_for_irq_devices() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let clock = Clock::new(&mut cycles, &mut scheduler);
 
     let irq_test_device_accesses = setup_test_device(&mut io_space, OneMhz, true, Some(IRQ));
     let nmi_test_device_accesses = setup_test_device(&mut io_space, OneMhz, true, Some(NMI));
@@ -142,9 +140,9 @@ fn it_only_reads_the_irq_interrupt_for_irq_devices() {
 #[test]
 fn it_only_reads_the_nmi_interrupt_for_nmi_devices() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let clock = Clock::new(&mut cycles, &mut scheduler);
 
     let irq_test_device_accesses = setup_test_device(&mut io_space, OneMhz, true, Some(IRQ));
     let nmi_test_device_accesses = setup_test_device(&mut io_space, OneMhz, true, Some(NMI));
@@ -158,9 +156,9 @@ fn it_only_reads_the_nmi_interrupt_for_nmi_devices() {
 #[test]
 fn it_keeps_reading_interrupts_from_devices_until_interrupt_found() {
     let mut io_space = IOSpace::default();
-    let mut timer_device_list = TimerDeviceList::default();
+    let mut scheduler = Scheduler::default();
     let mut cycles = 1000u64;
-    let clock = Clock::new(&mut cycles, &mut timer_device_list);
+    let clock = Clock::new(&mut cycles, &mut scheduler);
 
     let first_test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, Some(NMI));
     let second_test_device_accesses = setup_test_device(&mut io_space, OneMhz, true, Some(NMI));
@@ -173,19 +171,300 @@ fn it_keeps_reading_interrupts_from_devices_until_interrupt_found() {
     assert_eq!(*third_test_device_accesses.borrow().interrupt, []);
 }
 
+#[test]
+fn it_peeks_a_one_mhz_device_without_accessing_it_or_adjusting_cycles() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1001u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    let test_device_accesses = setup_test_device(&mut io_space, OneMhz, false, None);
+
+    io_space.set_peeking(true);
+
+    let read_value = io_space.read(TEST_ADDRESS.into(), &mut clock);
+    io_space.write(TEST_ADDRESS.into(), 12, &mut clock);
+
+    assert_eq!(cycles, 1001);
+    assert_eq!(read_value, TEST_VALUE);
+    assert_eq!(io_space.peek(0x4321.into()), 0xff);
+    assert!(test_device_accesses.borrow().memory.is_empty());
+}
+
+#[test]
+fn it_unmaps_removed_devices_and_rejects_their_stale_handles() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    let device_id = io_space.add_device(
+        IOAddressRange::new(TEST_ADDRESS, 1),
+        Box::new(IODeviceMock::new(&[(TEST_ADDRESS, TEST_VALUE)], true)),
+        Some(IRQ),
+        TwoMhz,
+    );
+
+    assert!(io_space.remove_device(device_id).is_some());
+
+    let replacement_accesses = setup_test_device(&mut io_space, TwoMhz, false, None);
+
+    assert!(!io_space.set_interrupt(device_id, true));
+    assert!(io_space.remove_device(device_id).is_none());
+    assert!(!io_space.get_interrupt(IRQ, &clock));
+    assert_eq!(io_space.read(TEST_ADDRESS.into(), &mut clock), TEST_VALUE);
+    assert_eq!(replacement_accesses.borrow().memory.len(), 1);
+}
+
+#[test]
+fn it_replaces_a_device_at_the_same_addresses() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    let device_id = io_space.add_device(
+        IOAddressRange::new(TEST_ADDRESS, 1),
+        Box::new(IODeviceMock::new(&[(TEST_ADDRESS, 1)], false)),
+        None,
+        TwoMhz,
+    );
+
+    let replaced = io_space.replace_device(
+        device_id,
+        Box::new(IODeviceMock::new(&[(TEST_ADDRESS, 2)], false)),
+    );
+
+    assert!(replaced.is_some());
+    assert_eq!(io_space.read(TEST_ADDRESS.into(), &mut clock), 2);
+}
+
+#[test]
+fn it_reads_unmapped_addresses_from_the_floating_bus() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    assert_eq!(io_space.read(0xfc10.into(), &mut clock), 0xff);
+
+    io_space.set_floating_bus(FloatingBus::Fixed(0x00));
+    assert_eq!(io_space.read(0xfc10.into(), &mut clock), 0x00);
+
+    io_space.set_floating_bus(FloatingBus::LastBusValue);
+    io_space.set_bus_value(0x42);
+    assert_eq!(io_space.read(0xfc10.into(), &mut clock), 0x42);
+
+    io_space.set_floating_bus(FloatingBus::OpenBus);
+    assert_eq!(io_space.read(0xfd10.into(), &mut clock), 0xfd);
+}
+
+#[test]
+fn it_logs_unmapped_accesses_only_while_enabled() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    setup_test_device(&mut io_space, TwoMhz, false, None);
+
+    io_space.read(0xfc10.into(), &mut clock);
+    assert_eq!(io_space.take_unmapped_accesses(), []);
+
+    io_space.set_unmapped_logging(true);
+    io_space.set_instruction_pc(0x8023.into());
+    io_space.read(TEST_ADDRESS.into(), &mut clock);
+    io_space.read(0xfc10.into(), &mut clock);
+    io_space.write(0xfe90.into(), 0x12, &mut clock);
+
+    let accesses = io_space.take_unmapped_accesses();
+
+    assert_eq!(
+        accesses,
+        [
+            UnmappedAccess {
+                address: 0xfc10,
+                value: 0xff,
+                is_write: false,
+                pc: 0x8023,
+                cycles: 1000,
+            },
+            UnmappedAccess {
+                address: 0xfe90,
+                value: 0x12,
+                is_write: true,
+                pc: 0x8023,
+                cycles: 1000,
+            },
+        ]
+    );
+    assert_eq!(accesses[1].to_string(), "W &FE90 &12 PC=&8023 cycles=1000");
+    assert_eq!(io_space.take_unmapped_accesses(), []);
+}
+
+#[test]
+fn it_logs_device_accesses_with_their_stretch_cycles() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1001u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    setup_test_device(&mut io_space, OneMhz, false, None);
+    let device_id = io_space.add_device(
+        IOAddressRange::new(0xfe40, 1),
+        Box::new(Phase2Device),
+        None,
+        TwoMhz,
+    );
+
+    io_space.set_access_logging(16);
+    io_space.set_instruction_pc(0x8023.into());
+    io_space.read(TEST_ADDRESS.into(), &mut clock);
+    io_space.write(0xfe40.into(), 0x12, &mut clock);
+    io_space.phase_2();
+
+    let accesses = io_space.take_accesses();
+
+    assert_eq!(accesses.len(), 3);
+    assert_eq!(
+        (
+            accesses[0].kind,
+            accesses[0].cycles,
+            accesses[0].stretch_cycles
+        ),
+        (IOAccessKind::Read, 1003, 2)
+    );
+    assert_eq!(
+        accesses[1],
+        IOAccess {
+            kind: IOAccessKind::Write,
+            address: 0xfe40,
+            value: 0x12,
+            cycles: 1003,
+            pc: 0x8023,
+            device_id,
+            stretch_cycles: 0,
+        }
+    );
+    assert_eq!(accesses[2].kind, IOAccessKind::Phase2);
+    assert_eq!(
+        accesses[1].to_string(),
+        format!(
+            "W &FE40 &12 PC=&8023 cycles=1003 device={} stretch=0",
+            device_id.to_bits()
+        )
+    );
+}
+
+#[test]
+fn it_only_logs_accesses_passing_the_filter_dropping_the_oldest() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    setup_test_device(&mut io_space, TwoMhz, false, None);
+    let device_id = io_space.add_device(
+        IOAddressRange::new(0xfe40, 4),
+        Box::new(Phase2Device),
+        None,
+        TwoMhz,
+    );
+
+    io_space.set_access_logging(2);
+    io_space.set_access_filter(IOAccessFilter {
+        device_id: Some(device_id),
+        addresses: Some(0xfe41..=0xfe43),
+    });
+
+    io_space.read(TEST_ADDRESS.into(), &mut clock);
+    for address in 0xfe40..0xfe44 {
+        io_space.write(address.into(), 0, &mut clock);
+    }
+
+    let addresses: Vec<u16> = io_space
+        .take_accesses()
+        .iter()
+        .map(|access| access.address)
+        .collect();
+
+    assert_eq!(addresses, [0xfe42, 0xfe43]);
+
+    io_space.set_access_logging(0);
+    io_space.write(0xfe41.into(), 0, &mut clock);
+
+    assert_eq!(io_space.take_accesses(), []);
+}
+
+#[test]
+fn it_keeps_the_filter_while_logging_is_off() {
+    let mut io_space = IOSpace::default();
+    let mut scheduler = Scheduler::default();
+    let mut cycles = 1000u64;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    io_space.add_device(
+        IOAddressRange::new(0xfe40, 4),
+        Box::new(Phase2Device),
+        None,
+        TwoMhz,
+    );
+
+    io_space.set_access_filter(IOAccessFilter {
+        device_id: None,
+        addresses: Some(0xfe42..=0xfe42),
+    });
+    io_space.set_access_logging(4);
+
+    for _ in 0..2 {
+        for address in 0xfe40..0xfe44 {
+            io_space.read(address.into(), &mut clock);
+        }
+
+        let addresses: Vec<u16> = io_space
+            .take_accesses()
+            .iter()
+            .map(|access| access.address)
+            .collect();
+
+        assert_eq!(addresses, [0xfe42]);
+
+        // re-enabling starts a fresh log with the same filter
+        io_space.set_access_logging(0);
+        io_space.set_access_logging(4);
+    }
+}
+
+struct Phase2Device;
+
+impl IODevice for Phase2Device {
+    fn read(&mut self, _address: Word, _register: u16, _cycles: u64) -> u8 {
+        0
+    }
+
+    fn write(&mut self, _address: Word, _register: u16, _value: u8, _cycles: u64) -> bool {
+        true
+    }
+}
+
 fn setup_test_device(
     io_space: &mut IOSpace,
     speed: DeviceSpeed,
     interrupt_on: bool,
     interrupt_type: Option<InterruptType>,
-) -> Rc<RefCell<IODeviceAccesses>> {
+) -> Shared<IODeviceAccesses> {
     let test_device = Box::new(IODeviceMock::new(
         &[(TEST_ADDRESS, TEST_VALUE)],
         interrupt_on,
     ));
     let test_device_accesses = test_device.get_accesses();
 
-    io_space.add_device(&[TEST_ADDRESS], test_device, interrupt_type, speed);
+    io_space.add_device(
+        IOAddressRange::new(TEST_ADDRESS, 1),
+        test_device,
+        interrupt_type,
+        speed,
+    );
 
     test_device_accesses
 }
@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+// value read from an address no device is mapped to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatingBus {
+    Fixed(u8),
+    // whatever was last read or written by the cpu
+    LastBusValue,
+    // the high byte of the address, the last byte the cpu fetched for absolute addressing
+    OpenBus,
+}
+
+impl Default for FloatingBus {
+    fn default() -> Self {
+        FloatingBus::Fixed(0xff)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UnmappedAccess {
+    pub address: u16,
+    pub value: u8,
+    pub is_write: bool,
+    pub pc: u16,
+    pub cycles: u64,
+}
+
+impl fmt::Display for UnmappedAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = if self.is_write { "W" } else { "R" };
+
+        write!(
+            f,
+            "{access} &{:04X} &{:02X} PC=&{:04X} cycles={}",
+            self.address, self.value, self.pc, self.cycles
+        )
+    }
+}
+
+// most recent accesses, oldest dropped once full
+pub struct UnmappedAccessLog {
+    accesses: VecDeque<UnmappedAccess>,
+}
+
+impl Default for UnmappedAccessLog {
+    fn default() -> Self {
+        UnmappedAccessLog {
+            accesses: VecDeque::with_capacity(UNMAPPED_ACCESS_LOG_LEN),
+        }
+    }
+}
+
+impl UnmappedAccessLog {
+    pub fn push(&mut self, access: UnmappedAccess) {
+        if self.accesses.len() == UNMAPPED_ACCESS_LOG_LEN {
+            self.accesses.pop_front();
+        }
+
+        self.accesses.push_back(access);
+    }
+
+    pub fn take(&mut self) -> Vec<UnmappedAccess> {
+        self.accesses.drain(..).collect()
+    }
+}
+
+const UNMAPPED_ACCESS_LOG_LEN: usize = 1024;
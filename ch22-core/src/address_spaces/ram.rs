@@ -2,20 +2,33 @@ use std::ops::Range;
 
 use crate::word::Word;
 
-const RAM_SIZE: usize = 0x8000;
+const DEFAULT_RAM_SIZE: usize = 0x8000;
+
+// dram contents at power on, randomised from a seed so runs are repeatable
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerOnRam {
+    Cleared,
+    Randomised(u64),
+}
 
 pub struct Ram {
-    ram: [u8; RAM_SIZE],
+    ram: Box<[u8]>,
 }
 
 impl Default for Ram {
     fn default() -> Ram {
-        Ram { ram: [0; RAM_SIZE] }
+        Ram::new(DEFAULT_RAM_SIZE)
     }
 }
 
 impl Ram {
-    pub fn read(&mut self, address: Word) -> u8 {
+    pub fn new(size: usize) -> Ram {
+        Ram {
+            ram: vec![0; size].into_boxed_slice(),
+        }
+    }
+
+    pub fn read(&self, address: Word) -> u8 {
         self.ram[Into::<usize>::into(address)]
     }
 
@@ -23,7 +36,59 @@ impl Ram {
         self.ram[Into::<usize>::into(address)] = value;
     }
 
+    // None beyond the end, for debuggers which can ask for any address
+    pub fn get(&self, address: u16) -> Option<u8> {
+        self.ram.get(address as usize).copied()
+    }
+
+    pub fn get_mut(&mut self, address: u16) -> Option<&mut u8> {
+        self.ram.get_mut(address as usize)
+    }
+
+    pub fn power_on(&mut self, contents: PowerOnRam) {
+        match contents {
+            PowerOnRam::Cleared => self.ram.fill(0),
+            PowerOnRam::Randomised(seed) => {
+                // xorshift64, which needs a non-zero state
+                let mut state = seed.max(1);
+
+                for value in self.ram.iter_mut() {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+
+                    *value = (state >> 32) as u8;
+                }
+            }
+        }
+    }
+
     pub fn slice(&self, range: Range<u16>) -> &[u8] {
         &self.ram[range.start as usize..range.end as usize]
     }
+
+    // screen memory as the video circuitry sees it, with ram smaller than the 32k it
+    // addresses mirrored through that range, and a fetch running off the end cut short
+    pub fn screen_slice(&self, range: Range<u16>) -> &[u8] {
+        if self.ram.is_empty() {
+            return &[];
+        }
+
+        let start = range.start as usize % self.ram.len();
+        let end = (start + range.len()).min(self.ram.len());
+
+        &self.ram[start..end]
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.ram
+    }
+
+    pub fn len(&self) -> usize {
+        self.ram.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ram.is_empty()
+    }
 }
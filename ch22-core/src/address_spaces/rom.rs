@@ -1,20 +1,26 @@
 use crate::word::Word;
 
-const ROM_SIZE: usize = 0x4000;
+const DEFAULT_ROM_SIZE: usize = 0x4000;
 
 pub struct Rom {
-    rom: [u8; ROM_SIZE],
+    rom: Box<[u8]>,
 }
 
 impl Default for Rom {
     fn default() -> Self {
-        Rom { rom: [0; ROM_SIZE] }
+        Rom::new(DEFAULT_ROM_SIZE)
     }
 }
 
 impl Rom {
+    pub fn new(size: usize) -> Self {
+        Rom {
+            rom: vec![0; size].into_boxed_slice(),
+        }
+    }
+
     pub fn load(&mut self, data: &[u8]) {
-        if data.len() != ROM_SIZE {
+        if data.len() != self.rom.len() {
             panic!();
         }
 
@@ -24,4 +30,17 @@ impl Rom {
     pub fn read(&self, address: Word) -> u8 {
         self.rom[Into::<usize>::into(address)]
     }
+
+    pub fn write(&mut self, address: Word, value: u8) {
+        self.rom[Into::<usize>::into(address)] = value;
+    }
+
+    // None beyond the end, for debuggers which can ask for any address
+    pub fn get(&self, address: u16) -> Option<u8> {
+        self.rom.get(address as usize).copied()
+    }
+
+    pub fn get_mut(&mut self, address: u16) -> Option<&mut u8> {
+        self.rom.get_mut(address as usize)
+    }
 }
@@ -0,0 +1,7 @@
+mod cheat_list;
+mod cheat_search;
+mod value_type;
+
+pub use cheat_list::{Cheat, CheatList, FreezeMode};
+pub use cheat_search::{CheatSearch, Comparison};
+pub use value_type::ValueType;
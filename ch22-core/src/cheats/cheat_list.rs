@@ -0,0 +1,208 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::value_type::{MAX_WIDTH, ValueType};
+use crate::address_spaces::Ram;
+use crate::word::Word;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FreezeMode {
+    // re-applied at the end of every field
+    Field,
+    // re-applied whenever the cpu writes to it (and at the end of every field)
+    Write,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cheat {
+    pub address: u16,
+    pub value_type: ValueType,
+    pub value: u32,
+    pub mode: FreezeMode,
+    pub name: String,
+}
+
+impl Cheat {
+    fn apply(&self, ram: &mut Ram) {
+        let address = self.address as usize;
+        let width = self.value_type.width();
+
+        if address + width > ram.len() {
+            return;
+        }
+
+        let mut bytes = [0; MAX_WIDTH];
+
+        self.value_type.encode(self.value, &mut bytes);
+
+        for (offset, value) in bytes[..width].iter().enumerate() {
+            ram.write(Word::from((address + offset) as u16), *value);
+        }
+    }
+
+    fn covers(&self, address: u16) -> bool {
+        (self.address..)
+            .take(self.value_type.width())
+            .any(|cheat_address| cheat_address == address)
+    }
+}
+
+// frozen ram values, saved as text with one cheat per line:
+// <hex address> <value type> <decimal value> <field|write> <name>
+// blank lines and lines starting with # are ignored
+#[derive(Default)]
+pub struct CheatList {
+    cheats: Vec<Cheat>,
+    // the addresses covered by write cheats, so most writes are passed over without a lookup
+    write_addresses: Range<usize>,
+}
+
+impl CheatList {
+    pub fn add(&mut self, cheat: Cheat) {
+        self.cheats.push(cheat);
+        self.update_write_addresses();
+    }
+
+    pub fn remove(&mut self, index: usize) -> Cheat {
+        let cheat = self.cheats.remove(index);
+        self.update_write_addresses();
+
+        cheat
+    }
+
+    pub fn clear(&mut self) {
+        self.cheats.clear();
+        self.update_write_addresses();
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    pub fn extend(&mut self, cheat_list: CheatList) {
+        self.cheats.extend(cheat_list.cheats);
+        self.update_write_addresses();
+    }
+
+    pub fn on_field_complete(&self, ram: &mut Ram) {
+        for cheat in &self.cheats {
+            cheat.apply(ram);
+        }
+    }
+
+    // address is the cpu address written, which is assumed to map directly to ram
+    pub fn on_write(&self, address: Word, ram: &mut Ram) {
+        let address: u16 = address.into();
+
+        if !self.write_addresses.contains(&(address as usize)) || address as usize >= ram.len() {
+            return;
+        }
+
+        for cheat in &self.cheats {
+            if cheat.mode == FreezeMode::Write && cheat.covers(address) {
+                cheat.apply(ram);
+            }
+        }
+    }
+
+    fn update_write_addresses(&mut self) {
+        let write_cheats = self
+            .cheats
+            .iter()
+            .filter(|cheat| cheat.mode == FreezeMode::Write);
+
+        let start = write_cheats
+            .clone()
+            .map(|cheat| cheat.address as usize)
+            .min();
+        let end = write_cheats
+            .map(|cheat| cheat.address as usize + cheat.value_type.width())
+            .max();
+
+        self.write_addresses = start.unwrap_or(0)..end.unwrap_or(0);
+    }
+}
+
+impl FromStr for CheatList {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut cheat_list = CheatList::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let cheat =
+                parse_cheat(line).map_err(|error| format!("line {}: {error}", index + 1))?;
+
+            cheat_list.add(cheat);
+        }
+
+        Ok(cheat_list)
+    }
+}
+
+fn parse_cheat(line: &str) -> Result<Cheat, String> {
+    let mut parts = line.splitn(5, char::is_whitespace);
+
+    let mut next_part = |field: &str| {
+        parts
+            .next()
+            .filter(|part| !part.is_empty())
+            .ok_or(format!("missing {field}"))
+    };
+
+    let address = next_part("address")?;
+    let address = u16::from_str_radix(address.trim_start_matches("0x"), 16)
+        .map_err(|_| format!("invalid address {address}"))?;
+
+    let value_type: ValueType = next_part("value type")?.parse()?;
+
+    let value = next_part("value")?;
+    let value = value
+        .parse()
+        .map_err(|_| format!("invalid value {value}"))?;
+
+    let mode = match next_part("mode")? {
+        "field" => FreezeMode::Field,
+        "write" => FreezeMode::Write,
+        mode => return Err(format!("invalid mode {mode}")),
+    };
+
+    let name = parts.next().unwrap_or_default().trim().to_string();
+
+    Ok(Cheat {
+        address,
+        value_type,
+        value,
+        mode,
+        name,
+    })
+}
+
+impl fmt::Display for CheatList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for cheat in &self.cheats {
+            let mode = match cheat.mode {
+                FreezeMode::Field => "field",
+                FreezeMode::Write => "write",
+            };
+
+            writeln!(
+                f,
+                "{:04x} {} {} {mode} {}",
+                cheat.address, cheat.value_type, cheat.value, cheat.name
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+const CHEATS_TEXT: &str = "\
+# lives and score
+0e1f u8 3 field Lives
+0070 bcd24be 12345 write Player score
+";
+
+#[test]
+fn it_round_trips_the_text_format() {
+    let cheat_list: CheatList = CHEATS_TEXT.parse().unwrap();
+
+    assert_eq!(
+        cheat_list.cheats()[1],
+        Cheat {
+            address: 0x70,
+            value_type: ValueType::new(3, true, true).unwrap(),
+            value: 12345,
+            mode: FreezeMode::Write,
+            name: "Player score".to_string(),
+        }
+    );
+
+    let saved = cheat_list.to_string();
+
+    assert_eq!(
+        saved,
+        "0e1f u8 3 field Lives\n0070 bcd24be 12345 write Player score\n"
+    );
+    assert_eq!(
+        saved.parse::<CheatList>().unwrap().cheats(),
+        cheat_list.cheats()
+    );
+}
+
+#[test]
+fn it_reports_the_line_of_invalid_cheats() {
+    let error = "0e1f u8 3 field\n0070 u12 1 field".parse::<CheatList>();
+
+    assert_eq!(
+        error.err(),
+        Some("line 2: unsupported value width 12".to_string())
+    );
+}
+
+#[test]
+fn it_reapplies_write_cheats_when_their_bytes_are_written() {
+    let cheat_list: CheatList = CHEATS_TEXT.parse().unwrap();
+    let mut ram = Ram::new(0x1000);
+
+    ram.write(Word(0x71, 0), 0x99);
+    ram.write(Word(0x1f, 0x0e), 0x01);
+    cheat_list.on_write(Word(0x71, 0), &mut ram);
+
+    assert_eq!(ram.slice(0x70..0x73), [0x01, 0x23, 0x45]);
+    assert_eq!(ram.read(Word(0x1f, 0x0e)), 0x01);
+
+    cheat_list.on_field_complete(&mut ram);
+
+    assert_eq!(ram.read(Word(0x1f, 0x0e)), 0x03);
+}
+
+#[test]
+fn it_only_looks_up_writes_to_bytes_under_write_cheats() {
+    let mut cheat_list: CheatList = CHEATS_TEXT.parse().unwrap();
+    let mut ram = Ram::new(0x1000);
+
+    assert_eq!(cheat_list.write_addresses, 0x70..0x73);
+
+    cheat_list.on_write(Word(0x73, 0), &mut ram);
+    cheat_list.on_write(Word(0x00, 0xfe), &mut ram);
+
+    assert_eq!(ram.slice(0x70..0x73), [0x00, 0x00, 0x00]);
+
+    cheat_list.remove(1);
+
+    assert!(cheat_list.write_addresses.is_empty());
+}
+
+#[test]
+fn it_rejects_value_widths_a_u32_cannot_hold() {
+    assert!(ValueType::new(0, false, false).is_err());
+    assert!(ValueType::new(5, true, false).is_err());
+
+    let value_type = ValueType::new(4, true, false).unwrap();
+
+    assert_eq!(value_type.width(), 4);
+    assert!(value_type.bcd());
+    assert!(!value_type.big_endian());
+}
@@ -0,0 +1,88 @@
+use super::value_type::ValueType;
+use crate::address_spaces::Ram;
+
+#[cfg(test)]
+mod tests;
+
+// comparison of each candidate's current value with the previous snapshot
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Changed,
+    Decreased,
+    Increased,
+}
+
+// narrows down the ram addresses holding a value by comparing snapshots,
+// typically taken between fields
+pub struct CheatSearch {
+    value_type: ValueType,
+    snapshot: Box<[u8]>,
+    candidates: Vec<u16>,
+}
+
+impl CheatSearch {
+    pub fn new(ram: &Ram, value_type: ValueType) -> Self {
+        let snapshot: Box<[u8]> = ram.as_slice().into();
+
+        let candidates = (0..(snapshot.len() + 1).saturating_sub(value_type.width()))
+            .filter(|&address| value_type.decode(&snapshot[address..]).is_some())
+            .map(|address| address as u16)
+            .collect();
+
+        CheatSearch {
+            value_type,
+            snapshot,
+            candidates,
+        }
+    }
+
+    // keeps candidates matching the comparison and takes a new snapshot,
+    // returning the number of candidates left
+    pub fn filter(&mut self, ram: &Ram, comparison: Comparison) -> usize {
+        let value_type = self.value_type;
+        let current = ram.as_slice();
+        let previous = &self.snapshot;
+
+        self.candidates.retain(|&address| {
+            let address = address as usize;
+
+            let (Some(previous), Some(current)) = (
+                value_type.decode(&previous[address..]),
+                value_type.decode(&current[address..]),
+            ) else {
+                return false;
+            };
+
+            match comparison {
+                Comparison::Equal => current == previous,
+                Comparison::Changed => current != previous,
+                Comparison::Decreased => current < previous,
+                Comparison::Increased => current > previous,
+            }
+        });
+
+        self.snapshot.copy_from_slice(current);
+
+        self.candidates.len()
+    }
+
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    // candidate value as of the last snapshot
+    pub fn value(&self, address: u16) -> Option<u32> {
+        let address = address as usize;
+
+        if address + self.value_type.width() > self.snapshot.len() {
+            return None;
+        }
+
+        self.value_type.decode(&self.snapshot[address..])
+    }
+}
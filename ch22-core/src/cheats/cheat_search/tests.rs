@@ -0,0 +1,51 @@
+use super::*;
+use crate::word::Word;
+
+#[test]
+fn it_narrows_candidates_by_comparing_snapshots() {
+    let mut ram = Ram::new(0x100);
+    ram.write(Word(0x10, 0), 3);
+    ram.write(Word(0x20, 0), 3);
+
+    let mut search = CheatSearch::new(&ram, ValueType::default());
+
+    assert_eq!(search.candidates().len(), 0x100);
+
+    ram.write(Word(0x10, 0), 2);
+    ram.write(Word(0x20, 0), 4);
+    ram.write(Word(0x30, 0), 1);
+
+    assert_eq!(search.filter(&ram, Comparison::Changed), 3);
+    assert_eq!(search.filter(&ram, Comparison::Equal), 3);
+
+    ram.write(Word(0x10, 0), 1);
+    ram.write(Word(0x20, 0), 5);
+
+    assert_eq!(search.filter(&ram, Comparison::Decreased), 1);
+    assert_eq!(search.candidates(), [0x10]);
+    assert_eq!(search.value(0x10), Some(1));
+}
+
+#[test]
+fn it_compares_multi_byte_bcd_values() {
+    let value_type = ValueType::new(3, true, true).unwrap();
+
+    let mut ram = Ram::new(0x10);
+    ram.write(Word(0x04, 0), 0x9a); // not bcd, so excludes addresses 2 to 4
+
+    let mut search = CheatSearch::new(&ram, value_type);
+
+    assert_eq!(search.candidates(), [0, 1, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+
+    // score 000990 -> 001000, only the high byte increases as raw bytes
+    ram.write(Word(0x08, 0), 0x09);
+    ram.write(Word(0x09, 0), 0x90);
+    search.filter(&ram, Comparison::Increased);
+
+    ram.write(Word(0x08, 0), 0x10);
+    ram.write(Word(0x09, 0), 0x00);
+    search.filter(&ram, Comparison::Increased);
+
+    assert!(search.candidates().contains(&0x07));
+    assert_eq!(search.value(0x07), Some(1000));
+}
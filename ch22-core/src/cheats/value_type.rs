@@ -0,0 +1,138 @@
+use std::fmt;
+use std::str::FromStr;
+
+// how a value is laid out in ram, BBC games often keep scores in BCD
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueType {
+    width: usize, // 1 to MAX_WIDTH bytes
+    bcd: bool,
+    big_endian: bool,
+}
+
+impl Default for ValueType {
+    fn default() -> Self {
+        ValueType {
+            width: 1,
+            bcd: false,
+            big_endian: false,
+        }
+    }
+}
+
+impl ValueType {
+    pub fn new(width: usize, bcd: bool, big_endian: bool) -> Result<Self, String> {
+        if !(1..=MAX_WIDTH).contains(&width) {
+            return Err(format!("unsupported value width {width} bytes"));
+        }
+
+        Ok(ValueType {
+            width,
+            bcd,
+            big_endian,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn bcd(&self) -> bool {
+        self.bcd
+    }
+
+    pub fn big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    // None if the bytes aren't valid BCD
+    pub fn decode(&self, bytes: &[u8]) -> Option<u32> {
+        let mut value = 0u32;
+
+        for index in 0..self.width {
+            let byte = bytes[self.byte_index(index)];
+
+            value = if self.bcd {
+                let (high, low) = (byte >> 4, byte & 0x0f);
+
+                if high > 9 || low > 9 {
+                    return None;
+                }
+
+                value * 100 + (high * 10 + low) as u32
+            } else {
+                (value << 8) | byte as u32
+            };
+        }
+
+        Some(value)
+    }
+
+    pub fn encode(&self, value: u32, bytes: &mut [u8]) {
+        let mut value = value;
+
+        for index in (0..self.width).rev() {
+            bytes[self.byte_index(index)] = if self.bcd {
+                let digits = (value % 100) as u8;
+                value /= 100;
+
+                ((digits / 10) << 4) | (digits % 10)
+            } else {
+                let byte = value as u8;
+                value >>= 8;
+
+                byte
+            };
+        }
+    }
+
+    // index into the bytes of the nth most significant byte
+    fn byte_index(&self, significance: usize) -> usize {
+        if self.big_endian {
+            significance
+        } else {
+            self.width - 1 - significance
+        }
+    }
+}
+
+// u8, u16, u24, u32 or bcd8 .. bcd32 with a "be" suffix for big endian
+impl FromStr for ValueType {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (text, big_endian) = match text.strip_suffix("be") {
+            Some(text) => (text, true),
+            None => (text, false),
+        };
+
+        let (bits, bcd) = if let Some(bits) = text.strip_prefix("bcd") {
+            (bits, true)
+        } else if let Some(bits) = text.strip_prefix('u') {
+            (bits, false)
+        } else {
+            return Err(format!("unknown value type {text}"));
+        };
+
+        let width = match bits {
+            "8" => 1,
+            "16" => 2,
+            "24" => 3,
+            "32" => 4,
+            _ => return Err(format!("unsupported value width {bits}")),
+        };
+
+        ValueType::new(width, bcd, big_endian)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = if self.bcd { "bcd" } else { "u" };
+        let suffix = if self.big_endian { "be" } else { "" };
+
+        write!(f, "{prefix}{}{suffix}", self.width * 8)
+    }
+}
+
+// the most a u32 holds
+pub const MAX_WIDTH: usize = 4;
@@ -22,23 +22,47 @@ pub struct Cpu {
 }
 
 impl Cpu {
+    // the 7 cycle sequence of a BRK with its stack writes turned into reads,
+    // leaving A, X and Y untouched and the stack pointer 3 lower
     pub fn reset<IO: CpuIO>(&mut self, io: &mut IO) {
-        self.registers = Registers {
-            program_counter: Word(
-                io.read(RESET_VECTOR),
-                io.read(RESET_VECTOR.same_page_add(1)),
-            ),
-            stack_pointer: 0xff,
-            flags: ProcessorFlags {
-                interrupt_disable: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        io.phantom_read(self.registers.program_counter);
+        io.phantom_read(self.registers.program_counter);
+
+        for _ in 0..3 {
+            io.phantom_read(Word::stack_page(self.registers.stack_pointer));
+
+            self.registers.stack_pointer = self.registers.stack_pointer.wrapping_sub(1);
+        }
+
+        self.registers.flags.interrupt_disable = true;
+
+        self.registers.program_counter = Word(
+            io.read(RESET_VECTOR),
+            io.read(RESET_VECTOR.same_page_add(1)),
+        );
 
         self.interrupt_due_state = InterruptDueState::default();
     }
 
+    pub fn get_program_counter(&self) -> Word {
+        self.registers.program_counter
+    }
+
+    // enters a program with an empty stack and interrupts disabled, abandoning whatever
+    // was running (including an interrupt handler part way through)
+    pub fn start_program(&mut self, address: Word) {
+        self.registers.stack_pointer = 0xff;
+        self.registers.flags.interrupt_disable = true;
+        self.registers.flags.decimal_mode = false;
+        self.registers.program_counter = address;
+
+        self.interrupt_due_state.interrupt_due = None;
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
     pub fn handle_next_instruction<IO: CpuIO>(&mut self, io: &mut IO) {
         execute(
             io,
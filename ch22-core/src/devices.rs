@@ -1,21 +1,30 @@
+mod device_slots;
+mod io_address_range;
 mod io_device;
 mod io_device_list;
+#[cfg(target_arch = "wasm32")]
 mod js_io_device;
+#[cfg(target_arch = "wasm32")]
 mod js_timer_device;
 mod rom_select;
+mod scheduler;
 mod static_device;
 mod timer_device;
-mod timer_device_list;
 
-pub use io_device::IODevice;
+pub use device_slots::{DeviceHandle, DeviceSlots};
+pub use io_address_range::IOAddressRange;
+pub use io_device::{DeviceDescription, IODevice, ResetType};
 pub use io_device_list::{DeviceSpeed, IODeviceID, IODeviceList};
+#[cfg(target_arch = "wasm32")]
 pub use js_io_device::JsIODevice;
+#[cfg(target_arch = "wasm32")]
 pub use js_timer_device::JsTimerDevice;
 pub use rom_select::RomSelect;
+pub use scheduler::{CoreEvent, Scheduler, TimerDeviceID};
 pub use static_device::StaticDevice;
-pub use timer_device_list::{TimerDeviceID, TimerDeviceList};
+pub use timer_device::TimerDevice;
 
 #[cfg(test)]
 pub mod io_device_mock;
 #[cfg(test)]
-mod timer_device_mock;
+pub(crate) mod timer_device_mock;
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests;
+
+// a device's slot index along with the slot's generation when the device was added,
+// so a handle to a removed device can't address whatever replaced it in the slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceHandle {
+    index: u16,
+    generation: u16,
+}
+
+impl DeviceHandle {
+    // packed as generation << 16 | index for passing to JS
+    pub fn to_bits(self) -> u32 {
+        (self.generation as u32) << 16 | self.index as u32
+    }
+
+    pub fn from_bits(bits: u32) -> Self {
+        DeviceHandle {
+            index: bits as u16,
+            generation: (bits >> 16) as u16,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    generation: u16,
+}
+
+// generational storage for devices, removed devices leave a slot to be reused
+// a slot is retired rather than reused once its generation runs out, so a stale
+// handle can never match again
+pub struct DeviceSlots<T> {
+    slots: Vec<Slot<T>>,
+    free_indices: Vec<u16>,
+}
+
+impl<T> Default for DeviceSlots<T> {
+    fn default() -> Self {
+        DeviceSlots {
+            slots: Vec::new(),
+            free_indices: Vec::new(),
+        }
+    }
+}
+
+impl<T> DeviceSlots<T> {
+    pub fn insert(&mut self, value: T) -> DeviceHandle {
+        let index = match self.free_indices.pop() {
+            Some(index) => index,
+            None => {
+                let Ok(index) = u16::try_from(self.slots.len()) else {
+                    panic!("Too many devices");
+                };
+
+                self.slots.push(Slot {
+                    value: None,
+                    generation: 0,
+                });
+
+                index
+            }
+        };
+
+        let slot = &mut self.slots[index as usize];
+
+        slot.value = Some(value);
+
+        DeviceHandle {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    // None for a stale handle
+    pub fn remove(&mut self, handle: DeviceHandle) -> Option<T> {
+        let slot = self.get_slot_mut(handle)?;
+        let value = slot.value.take();
+
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+
+            self.free_indices.push(handle.index);
+        }
+
+        value
+    }
+
+    pub fn get(&self, handle: DeviceHandle) -> Option<&T> {
+        self.slots
+            .get(handle.index())
+            .filter(|slot| slot.generation == handle.generation)?
+            .value
+            .as_ref()
+    }
+
+    pub fn get_mut(&mut self, handle: DeviceHandle) -> Option<&mut T> {
+        self.get_slot_mut(handle)?.value.as_mut()
+    }
+
+    // for lookups from tables which are cleared when a device is removed
+    pub fn get_by_index_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.slots[index].value.as_mut()
+    }
+
+    // for lookups from tables, None if the slot is empty
+    pub fn handle_at(&self, index: usize) -> Option<DeviceHandle> {
+        let slot = self.slots.get(index)?;
+
+        slot.value.as_ref().map(|_| DeviceHandle {
+            index: index as u16,
+            generation: slot.generation,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (DeviceHandle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = DeviceHandle {
+                index: index as u16,
+                generation: slot.generation,
+            };
+
+            Some((handle, slot.value.as_ref()?))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots.iter_mut().filter_map(|slot| slot.value.as_mut())
+    }
+
+    fn get_slot_mut(&mut self, handle: DeviceHandle) -> Option<&mut Slot<T>> {
+        self.slots
+            .get_mut(handle.index())
+            .filter(|slot| slot.generation == handle.generation && slot.value.is_some())
+    }
+}
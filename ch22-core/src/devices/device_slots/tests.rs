@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn it_rejects_stale_handles_after_a_slot_is_reused() {
+    let mut slots = DeviceSlots::default();
+
+    let first = slots.insert("first");
+    let second = slots.insert("second");
+
+    assert_eq!(slots.remove(first), Some("first"));
+    assert_eq!(slots.remove(first), None);
+
+    let third = slots.insert("third");
+
+    assert_eq!(third.index(), first.index());
+    assert_eq!(slots.get(first), None);
+    assert_eq!(slots.get(third), Some(&"third"));
+    assert_eq!(slots.get(second), Some(&"second"));
+}
+
+#[test]
+fn it_round_trips_handles_through_bits() {
+    let mut slots = DeviceSlots::default();
+
+    let handle = slots.insert(0);
+    slots.remove(handle);
+    let handle = slots.insert(1);
+
+    assert_eq!(handle.to_bits(), 0x0001_0000);
+    assert_eq!(DeviceHandle::from_bits(handle.to_bits()), handle);
+}
+
+#[test]
+fn it_retires_a_slot_rather_than_wrapping_its_generation() {
+    let mut slots = DeviceSlots::default();
+
+    let mut handle = slots.insert(0);
+
+    for value in 1..=u16::MAX as u32 {
+        slots.remove(handle);
+        handle = slots.insert(value);
+    }
+
+    assert_eq!(handle.to_bits(), 0xffff_0000);
+
+    slots.remove(handle);
+    let next = slots.insert(0x10000);
+
+    assert_eq!(next.index(), 1);
+    assert_eq!(slots.get(DeviceHandle::from_bits(0)), None);
+    assert_eq!(slots.get(handle), None);
+}
+
+#[test]
+fn it_reuses_the_most_recently_freed_slot() {
+    let mut slots = DeviceSlots::default();
+
+    let handles: Vec<_> = (0..4).map(|value| slots.insert(value)).collect();
+
+    slots.remove(handles[1]);
+    slots.remove(handles[3]);
+
+    assert_eq!(slots.insert(4).index(), 3);
+    assert_eq!(slots.insert(5).index(), 1);
+    assert_eq!(slots.insert(6).index(), 4);
+}
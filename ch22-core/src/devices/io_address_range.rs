@@ -0,0 +1,62 @@
+use std::ops::Range;
+
+#[cfg(test)]
+mod tests;
+
+// where a device is decoded in io space, BBC hardware only partially decodes
+// addresses so a device's registers repeat across its range,
+// e.g. the system VIA's 16 registers across &FE40-&FE5F
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IOAddressRange {
+    pub base: u16,
+    pub size: u16,
+    // applied to the offset from base to give the register passed to the device
+    pub mirror_mask: u16,
+}
+
+impl IOAddressRange {
+    // a register at every address in the range, panics if it runs past &FFFF
+    pub fn new(base: u16, size: u16) -> Self {
+        IOAddressRange::mirrored(base, size, 0xffff)
+    }
+
+    // panics if the range runs past &FFFF, see try_mirrored
+    pub fn mirrored(base: u16, size: u16, mirror_mask: u16) -> Self {
+        IOAddressRange::try_mirrored(base, size, mirror_mask)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_mirrored(base: u16, size: u16, mirror_mask: u16) -> Result<Self, String> {
+        if base as u32 + size as u32 > 0x10000 {
+            return Err(format!(
+                "IO address range runs past &FFFF: {base:04x} size {size:04x}"
+            ));
+        }
+
+        Ok(IOAddressRange {
+            base,
+            size,
+            mirror_mask,
+        })
+    }
+
+    // stops at &FFFF rather than wrapping for a range built field by field
+    pub fn addresses(&self) -> impl Iterator<Item = (u16, u16)> {
+        (0..self.size)
+            .map_while(|offset| Some((self.base.checked_add(offset)?, offset & self.mirror_mask)))
+    }
+}
+
+// panics if the range is reversed
+impl From<Range<u16>> for IOAddressRange {
+    fn from(range: Range<u16>) -> Self {
+        let Some(size) = range.end.checked_sub(range.start) else {
+            panic!(
+                "IO address range is reversed: {:04x}..{:04x}",
+                range.start, range.end
+            );
+        };
+
+        IOAddressRange::new(range.start, size)
+    }
+}
@@ -0,0 +1,39 @@
+use super::*;
+
+#[test]
+fn it_decodes_ranges_up_to_the_top_of_memory() {
+    let address_range = IOAddressRange::try_mirrored(0xfffe, 2, 0xffff).unwrap();
+
+    assert_eq!(
+        address_range.addresses().collect::<Vec<_>>(),
+        [(0xfffe, 0), (0xffff, 1)]
+    );
+}
+
+#[test]
+fn it_rejects_ranges_running_past_the_top_of_memory() {
+    assert_eq!(
+        IOAddressRange::try_mirrored(0xfff0, 0x20, 0x0f),
+        Err("IO address range runs past &FFFF: fff0 size 0020".to_string())
+    );
+}
+
+#[test]
+fn it_stops_at_the_top_of_memory_for_ranges_built_field_by_field() {
+    let address_range = IOAddressRange {
+        base: 0xffff,
+        size: 2,
+        mirror_mask: 0xffff,
+    };
+
+    assert_eq!(address_range.addresses().collect::<Vec<_>>(), [(0xffff, 0)]);
+}
+
+#[test]
+#[should_panic(expected = "IO address range is reversed: fe10..fe00")]
+fn it_rejects_reversed_ranges() {
+    let _ = IOAddressRange::from(Range {
+        start: 0xfe10,
+        end: 0xfe00,
+    });
+}
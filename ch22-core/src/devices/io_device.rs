@@ -1,14 +1,60 @@
+use std::fmt;
+
+use crate::shared::MaybeSend;
 use crate::word::Word;
 
-pub trait IODevice {
-    fn read(&mut self, address: Word, cycles: u64) -> u8;
-    fn write(&mut self, _address: Word, _value: u8, _cycles: u64) -> bool {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResetType {
+    PowerOn,
+    // the BREAK key asserting RES, only reaching devices wired to it
+    Break,
+}
+
+// name and register values for debugger views
+pub struct DeviceDescription {
+    pub name: String,
+    pub registers: Vec<(String, u16)>,
+}
+
+impl fmt::Display for DeviceDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+
+        for (name, value) in &self.registers {
+            write!(f, " {name}=&{value:02X}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// register is the offset of the address within the device's IOAddressRange after mirroring
+pub trait IODevice: MaybeSend {
+    fn read(&mut self, address: Word, register: u16, cycles: u64) -> u8;
+    fn write(&mut self, _address: Word, _register: u16, _value: u8, _cycles: u64) -> bool {
         false
     }
-    fn on_vsync_change(&mut self, _vsync: bool) {}
-    fn phase_2(&mut self, _address: Word, _value: u8, _cycles: u64) {}
+    fn on_vsync_change(&mut self, _vsync: bool, _cycles: u64) {}
+    fn phase_2(&mut self, _address: Word, _register: u16, _value: u8, _cycles: u64) {}
     fn get_interrupt(&mut self, _cycles: u64) -> bool {
         false
     }
     fn set_interrupt(&mut self, _interrupt: bool) {}
+    // side effect free read for debuggers, None if the device can't report its registers
+    fn peek(&self, _address: Word, _register: u16) -> Option<u8> {
+        None
+    }
+    fn reset(&mut self, _reset_type: ResetType) {}
+    fn on_field_complete(&mut self, _cycles: u64) {}
+    // opaque bytes for snapshots, only loaded back into a device of the same type
+    // None (and an error loading) if the device has no state to save
+    fn save_state(&self) -> Option<Vec<u8>> {
+        None
+    }
+    fn load_state(&mut self, _state: &[u8]) -> Result<(), String> {
+        Err("Device doesn't support saving state".to_string())
+    }
+    fn describe(&self) -> Option<DeviceDescription> {
+        None
+    }
 }
@@ -1,101 +1,179 @@
 use std::collections::HashMap;
 
 use crate::cpu::InterruptType;
-use crate::devices::IODevice;
+use crate::devices::{
+    DeviceDescription, DeviceHandle, DeviceSlots, IOAddressRange, IODevice, ResetType,
+};
 use crate::word::Word;
 
-pub type IODeviceID = usize;
+pub type IODeviceID = DeviceHandle;
 
 pub enum DeviceSpeed {
     OneMhz,
     TwoMhz,
 }
 
+struct IODeviceEntry {
+    device: Box<dyn IODevice>,
+    config: IODeviceConfig,
+}
+
 #[derive(Default)]
 pub struct IODeviceList {
-    device_list: Vec<Box<dyn IODevice>>,
-    address_to_device_id: HashMap<Word, IODeviceID>,
-    config_list: Vec<IODeviceConfig>,
+    devices: DeviceSlots<IODeviceEntry>,
+    // the slot index and register each address decodes to
+    address_to_device_index: HashMap<Word, (usize, u16)>,
+    irq_device_indices: Vec<usize>,
+    nmi_device_indices: Vec<usize>,
 }
 
 impl IODeviceList {
     pub fn add_device(
         &mut self,
-        addresses: &[u16],
+        address_range: IOAddressRange,
         device: Box<dyn IODevice>,
         interrupt_type: Option<InterruptType>,
         speed: DeviceSpeed,
     ) -> IODeviceID {
-        self.device_list.push(device);
-
-        self.config_list.push(IODeviceConfig {
-            interrupt_type,
-            speed,
+        let device_id = self.devices.insert(IODeviceEntry {
+            device,
+            config: IODeviceConfig {
+                speed,
+                reset_on_break: true,
+            },
         });
 
-        // assumes devices will not be removed
-        let device_id = self.device_list.len() - 1;
+        let device_index = device_id.index();
 
-        for address in addresses {
-            self.address_to_device_id
-                .insert((*address).into(), device_id);
+        for (address, register) in address_range.addresses() {
+            self.address_to_device_index
+                .insert(address.into(), (device_index, register));
+        }
+
+        match interrupt_type {
+            Some(InterruptType::IRQ) => self.irq_device_indices.push(device_index),
+            Some(InterruptType::NMI) => self.nmi_device_indices.push(device_index),
+            None => (),
         }
 
         device_id
     }
 
-    pub fn get_by_id(&mut self, device_id: IODeviceID) -> &mut dyn IODevice {
-        self.device_list[device_id].as_mut()
+    // unmaps the device's addresses, None if the handle is stale
+    pub fn remove_device(&mut self, device_id: IODeviceID) -> Option<Box<dyn IODevice>> {
+        let entry = self.devices.remove(device_id)?;
+        let device_index = device_id.index();
+
+        self.address_to_device_index
+            .retain(|_, (index, _)| *index != device_index);
+
+        self.irq_device_indices
+            .retain(|index| *index != device_index);
+        self.nmi_device_indices
+            .retain(|index| *index != device_index);
+
+        Some(entry.device)
     }
 
-    pub fn get_with_config_by_id(
+    // swaps in a device at the same addresses, interrupt type and speed, keeping the handle
+    // returns the previous device, or None (dropping the new device) if the handle is stale
+    pub fn replace_device(
         &mut self,
         device_id: IODeviceID,
-    ) -> (&mut dyn IODevice, &IODeviceConfig) {
-        let device = self.device_list[device_id].as_mut();
-        let config = &self.config_list[device_id];
+        device: Box<dyn IODevice>,
+    ) -> Option<Box<dyn IODevice>> {
+        let entry = self.devices.get_mut(device_id)?;
 
-        (device, config)
+        Some(std::mem::replace(&mut entry.device, device))
     }
 
-    pub fn get_by_address(&mut self, address: Word) -> Option<&mut dyn IODevice> {
-        let device_id = self.address_to_device_id.get(&address)?;
+    // devices are wired to RES unless set otherwise, false if the handle is stale
+    pub fn set_reset_on_break(&mut self, device_id: IODeviceID, reset_on_break: bool) -> bool {
+        let Some(entry) = self.devices.get_mut(device_id) else {
+            return false;
+        };
 
-        Some(self.get_by_id(*device_id))
+        entry.config.reset_on_break = reset_on_break;
+
+        true
+    }
+
+    pub fn reset(&mut self, reset_type: ResetType) {
+        for entry in self.devices.iter_mut() {
+            if reset_type == ResetType::PowerOn || entry.config.reset_on_break {
+                entry.device.reset(reset_type);
+            }
+        }
+    }
+
+    pub fn describe(&self) -> Vec<(IODeviceID, DeviceDescription)> {
+        self.devices
+            .iter()
+            .filter_map(|(device_id, entry)| Some((device_id, entry.device.describe()?)))
+            .collect()
+    }
+
+    pub fn get(&self, device_id: IODeviceID) -> Option<&dyn IODevice> {
+        Some(self.devices.get(device_id)?.device.as_ref())
+    }
+
+    pub fn get_by_id(&mut self, device_id: IODeviceID) -> Option<&mut dyn IODevice> {
+        Some(self.devices.get_mut(device_id)?.device.as_mut())
+    }
+
+    pub fn get_by_address(&mut self, address: Word) -> Option<(&mut dyn IODevice, u16)> {
+        let (device, register, _) = self.get_with_config_by_address(address)?;
+
+        Some((device, register))
+    }
+
+    pub fn get_id_by_address(&self, address: Word) -> Option<IODeviceID> {
+        let (device_index, _) = self.lookup(address)?;
+
+        self.devices.handle_at(device_index)
     }
 
     pub fn get_with_config_by_address(
         &mut self,
         address: Word,
-    ) -> Option<(&mut dyn IODevice, &IODeviceConfig)> {
-        let device_id = self.address_to_device_id.get(&address)?;
+    ) -> Option<(&mut dyn IODevice, u16, &IODeviceConfig)> {
+        let (device_index, register) = self.lookup(address)?;
+        let entry = self.devices.get_by_index_mut(device_index)?;
 
-        Some(self.get_with_config_by_id(*device_id))
+        Some((entry.device.as_mut(), register, &entry.config))
     }
 
-    pub fn get_by_interrupt_type(
+    // true if the callback returns true for any device with the interrupt type,
+    // stopping at the first
+    pub fn any_by_interrupt_type<F: FnMut(&mut dyn IODevice) -> bool>(
         &mut self,
         interrupt_type: InterruptType,
-    ) -> impl Iterator<Item = &mut Box<dyn IODevice>> {
-        let config_list = &self.config_list;
-
-        self.device_list
-            .iter_mut()
-            .enumerate()
-            .filter(move |(device_id, _)| {
-                config_list[*device_id].interrupt_type == Some(interrupt_type)
-            })
-            .map(|(_, device)| device)
+        mut callback: F,
+    ) -> bool {
+        let device_indices = match interrupt_type {
+            InterruptType::IRQ => &self.irq_device_indices,
+            InterruptType::NMI => &self.nmi_device_indices,
+        };
+
+        device_indices.iter().any(|device_index| {
+            self.devices
+                .get_by_index_mut(*device_index)
+                .is_some_and(|entry| callback(entry.device.as_mut()))
+        })
+    }
+
+    fn lookup(&self, address: Word) -> Option<(usize, u16)> {
+        self.address_to_device_index.get(&address).copied()
     }
 
     pub fn for_each<F: FnMut(&mut Box<dyn IODevice>)>(&mut self, mut callback: F) {
-        for device in self.device_list.iter_mut() {
-            callback(device);
+        for entry in self.devices.iter_mut() {
+            callback(&mut entry.device);
         }
     }
 }
 
 pub struct IODeviceConfig {
-    pub interrupt_type: Option<InterruptType>,
     pub speed: DeviceSpeed,
+    pub reset_on_break: bool,
 }
@@ -1,14 +1,15 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::collections::HashMap;
 
+use crate::shared::Shared;
 use crate::word::Word;
 
-use super::io_device::IODevice;
+use super::io_device::{IODevice, ResetType};
 
 #[derive(Default)]
 pub struct IODeviceMock {
     memory: HashMap<u16, u8>,
     interrupt_on: bool,
-    accesses: Rc<RefCell<IODeviceAccesses>>,
+    accesses: Shared<IODeviceAccesses>,
 }
 
 impl IODeviceMock {
@@ -26,7 +27,7 @@ impl IODeviceMock {
         }
     }
 
-    pub fn get_accesses(&self) -> Rc<RefCell<IODeviceAccesses>> {
+    pub fn get_accesses(&self) -> Shared<IODeviceAccesses> {
         self.accesses.clone()
     }
 
@@ -36,7 +37,7 @@ impl IODeviceMock {
 }
 
 impl IODevice for IODeviceMock {
-    fn read(&mut self, address: Word, cycles: u64) -> u8 {
+    fn read(&mut self, address: Word, _register: u16, cycles: u64) -> u8 {
         let address: u16 = address.into();
 
         self.push_memory_access(MemoryAccess::Read(address, cycles));
@@ -47,7 +48,7 @@ impl IODevice for IODeviceMock {
             .unwrap_or_else(|| panic!("memory not set {address:x}"))
     }
 
-    fn write(&mut self, address: Word, value: u8, cycles: u64) -> bool {
+    fn write(&mut self, address: Word, _register: u16, value: u8, cycles: u64) -> bool {
         let address: u16 = address.into();
 
         self.memory.insert(address, value);
@@ -62,6 +63,18 @@ impl IODevice for IODeviceMock {
 
         self.interrupt_on
     }
+
+    fn peek(&self, address: Word, _register: u16) -> Option<u8> {
+        self.memory.get(&address.into()).copied()
+    }
+
+    fn reset(&mut self, reset_type: ResetType) {
+        self.accesses.borrow_mut().resets.push(reset_type);
+    }
+
+    fn on_field_complete(&mut self, cycles: u64) {
+        self.accesses.borrow_mut().field_completes.push(cycles);
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -74,4 +87,6 @@ pub enum MemoryAccess {
 pub struct IODeviceAccesses {
     pub memory: Vec<MemoryAccess>,
     pub interrupt: Vec<u64>,
+    pub resets: Vec<ResetType>,
+    pub field_completes: Vec<u64>,
 }
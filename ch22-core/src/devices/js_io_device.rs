@@ -1,20 +1,18 @@
-use std::{cell::Cell, rc::Rc};
-
 use js_sys::Function;
 use wasm_bindgen::JsValue;
 
 use crate::word::Word;
 
-use super::io_device::IODevice;
+use super::io_device::{IODevice, ResetType};
 
 pub struct JsIODevice {
-    read: Box<dyn Fn(u16, u64) -> u64>,
-    write: Box<dyn Fn(u16, u8, u64) -> u64>,
+    read: Box<dyn Fn(u16, u16, u64) -> u64>,
+    write: Box<dyn Fn(u16, u16, u8, u64) -> u64>,
     on_vsync_change: Option<Box<dyn Fn(bool) -> u64>>,
     handle_trigger: Box<dyn Fn(u64) -> u64>,
+    reset: Option<Box<dyn Fn(bool) -> u64>>,
     trigger: Option<u64>,
     interrupt: bool,
-    ic32_latch: Rc<Cell<u8>>,
     phase_2_write: bool,
 }
 
@@ -24,22 +22,28 @@ impl JsIODevice {
         js_write: Function,
         js_on_vsync_change: Option<Function>,
         js_handle_trigger: Function,
+        js_reset: Option<Function>,
         phase_2_write: bool,
-        ic32_latch: Rc<Cell<u8>>,
     ) -> Self {
-        let read = Box::new(move |address: u16, cycles: u64| {
+        let read = Box::new(move |address: u16, register: u16, cycles: u64| {
             js_read
-                .call2(&JsValue::NULL, &address.into(), &cycles.into())
+                .call3(
+                    &JsValue::NULL,
+                    &address.into(),
+                    &register.into(),
+                    &cycles.into(),
+                )
                 .expect("js_read error")
                 .try_into()
                 .expect("js_read error")
         });
 
-        let write = Box::new(move |address: u16, value: u8, cycles: u64| {
+        let write = Box::new(move |address: u16, register: u16, value: u8, cycles: u64| {
             js_write
-                .call3(
+                .call4(
                     &JsValue::NULL,
                     &address.into(),
+                    &register.into(),
                     &value.into(),
                     &cycles.into(),
                 )
@@ -66,28 +70,37 @@ impl JsIODevice {
                 .expect("js_handle_trigger error")
         });
 
+        let reset = js_reset.map(|js_reset| {
+            Box::new(move |power_on: bool| {
+                js_reset
+                    .call1(&JsValue::NULL, &power_on.into())
+                    .expect("js_reset error")
+                    .try_into()
+                    .expect("js_reset error")
+            }) as Box<dyn Fn(bool) -> u64>
+        });
+
         JsIODevice {
             read,
             write,
             on_vsync_change,
             handle_trigger,
+            reset,
             trigger: None,
             interrupt: false,
             phase_2_write,
-            ic32_latch,
         }
     }
 }
 
 impl IODevice for JsIODevice {
-    fn read(&mut self, address: Word, cycles: u64) -> u8 {
-        self.set_js_device_params((self.read)(address.into(), cycles))
-            .unwrap()
+    fn read(&mut self, address: Word, register: u16, cycles: u64) -> u8 {
+        self.set_js_device_params((self.read)(address.into(), register, cycles))
     }
 
-    fn write(&mut self, address: Word, value: u8, cycles: u64) -> bool {
+    fn write(&mut self, address: Word, register: u16, value: u8, cycles: u64) -> bool {
         if !self.phase_2_write {
-            self.set_js_device_params((self.write)(address.into(), value, cycles));
+            self.set_js_device_params((self.write)(address.into(), register, value, cycles));
 
             false
         } else {
@@ -95,9 +108,9 @@ impl IODevice for JsIODevice {
         }
     }
 
-    fn phase_2(&mut self, address: Word, value: u8, cycles: u64) {
+    fn phase_2(&mut self, address: Word, register: u16, value: u8, cycles: u64) {
         if self.phase_2_write {
-            self.set_js_device_params((self.write)(address.into(), value, cycles));
+            self.set_js_device_params((self.write)(address.into(), register, value, cycles));
         }
     }
 
@@ -111,11 +124,17 @@ impl IODevice for JsIODevice {
         self.interrupt = interrupt;
     }
 
-    fn on_vsync_change(&mut self, vsync: bool) {
+    fn on_vsync_change(&mut self, vsync: bool, _cycles: u64) {
         if let Some(on_vsync_change) = &self.on_vsync_change {
             self.set_js_device_params((on_vsync_change)(vsync));
         }
     }
+
+    fn reset(&mut self, reset_type: ResetType) {
+        if let Some(reset) = &self.reset {
+            self.set_js_device_params((reset)(reset_type == ResetType::PowerOn));
+        }
+    }
 }
 
 impl JsIODevice {
@@ -127,10 +146,9 @@ impl JsIODevice {
         }
     }
 
-    // Encoding format: [trig trig trig trig trig trig flags (value or ic32)]
-    // The last byte contains either a value or ic32 data, depending on the JS_IO_FLAG_VALUE_IS_IC32 flag.
+    // Encoding format: [trig trig trig trig trig trig flags value]
 
-    fn set_js_device_params(&mut self, params_and_value: u64) -> Option<u8> {
+    fn set_js_device_params(&mut self, params_and_value: u64) -> u8 {
         let [_, _, _, _, _, _, flags, value] = params_and_value.to_be_bytes();
 
         self.interrupt = flags & JS_IO_FLAG_INTERRUPT != 0;
@@ -141,16 +159,9 @@ impl JsIODevice {
             None
         };
 
-        if flags & JS_IO_FLAG_VALUE_IS_IC32 != 0 {
-            self.ic32_latch.set(value);
-
-            None
-        } else {
-            Some(value)
-        }
+        value
     }
 }
 
 const JS_IO_FLAG_HAS_TRIGGER: u8 = 0x01;
 const JS_IO_FLAG_INTERRUPT: u8 = 0x02;
-const JS_IO_FLAG_VALUE_IS_IC32: u8 = 0x04;
@@ -1,28 +1,55 @@
-use std::cell::Cell;
-use std::rc::Rc;
-
+use crate::shared::Latch;
 use crate::word::Word;
 
 use super::io_device::*;
 
 pub struct RomSelect {
-    active_rom: Rc<Cell<usize>>,
+    active_rom: Latch,
 }
 
 impl RomSelect {
-    pub fn new(active_rom: Rc<Cell<usize>>) -> Self {
+    pub fn new(active_rom: Latch) -> Self {
         RomSelect { active_rom }
     }
 }
 
 impl IODevice for RomSelect {
-    fn read(&mut self, _address: Word, _cycles: u64) -> u8 {
-        self.active_rom.get() as u8
+    fn read(&mut self, _address: Word, _register: u16, _cycles: u64) -> u8 {
+        self.active_rom.get()
     }
 
-    fn write(&mut self, _address: Word, value: u8, _cycles: u64) -> bool {
-        self.active_rom.set((value & 0x0f) as usize); // 4 bit latch
+    fn write(&mut self, _address: Word, _register: u16, value: u8, _cycles: u64) -> bool {
+        self.active_rom.set(value & 0x0f); // 4 bit latch
 
         false
     }
+
+    fn peek(&self, _address: Word, _register: u16) -> Option<u8> {
+        Some(self.active_rom.get())
+    }
+
+    fn reset(&mut self, _reset_type: ResetType) {
+        self.active_rom.set(0);
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        Some(vec![self.active_rom.get()])
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), String> {
+        let [active_rom] = state else {
+            return Err(format!("Invalid ROM select state length: {}", state.len()));
+        };
+
+        self.active_rom.set(active_rom & 0x0f);
+
+        Ok(())
+    }
+
+    fn describe(&self) -> Option<DeviceDescription> {
+        Some(DeviceDescription {
+            name: "ROM select".to_string(),
+            registers: vec![("ROMSEL".to_string(), self.active_rom.get() as u16)],
+        })
+    }
 }
@@ -0,0 +1,205 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::device_slots::{DeviceHandle, DeviceSlots};
+use super::timer_device::TimerDevice;
+
+pub type TimerDeviceID = DeviceHandle;
+
+#[cfg(test)]
+mod tests;
+
+// events handled by the core between runs, rather than by a timer device mid instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CoreEvent {
+    Scanline,
+}
+
+struct TimerDeviceEntry {
+    device: Box<dyn TimerDevice>,
+    trigger: Option<u64>,
+    period: Option<u64>,
+    // unique per schedule so superseded queue entries can be skipped
+    schedule_id: u64,
+}
+
+// timer device triggers and core events ordered by cycle in binary heaps
+// triggers at or before the current cycle fire on the next sync, so an overdue trigger is never lost
+#[derive(Default)]
+pub struct Scheduler {
+    devices: DeviceSlots<TimerDeviceEntry>,
+    device_queue: BinaryHeap<Reverse<(u64, usize, u64)>>,
+    core_queue: BinaryHeap<Reverse<(u64, CoreEvent)>>,
+    rescheduled: Vec<(usize, Option<u64>)>,
+    next_schedule_id: u64,
+}
+
+impl Scheduler {
+    pub fn add_device(&mut self, device: Box<dyn TimerDevice>) -> TimerDeviceID {
+        self.devices.insert(TimerDeviceEntry {
+            device,
+            trigger: None,
+            period: None,
+            schedule_id: 0,
+        })
+    }
+
+    // cancels any pending trigger, None if the handle is stale
+    pub fn remove_device(&mut self, device_id: TimerDeviceID) -> Option<Box<dyn TimerDevice>> {
+        Some(self.devices.remove(device_id)?.device)
+    }
+
+    // swaps in a device keeping the handle and any pending trigger
+    // returns the previous device, or None (dropping the new device) if the handle is stale
+    pub fn replace_device(
+        &mut self,
+        device_id: TimerDeviceID,
+        device: Box<dyn TimerDevice>,
+    ) -> Option<Box<dyn TimerDevice>> {
+        let entry = self.devices.get_mut(device_id)?;
+
+        Some(std::mem::replace(&mut entry.device, device))
+    }
+
+    // a trigger of None cancels any pending (or periodic) trigger
+    // false if the handle is stale
+    pub fn set_device_trigger(&mut self, device_id: TimerDeviceID, trigger: Option<u64>) -> bool {
+        let Some(entry) = self.devices.get_mut(device_id) else {
+            return false;
+        };
+
+        entry.period = None;
+
+        self.schedule_device(device_id.index(), trigger);
+
+        true
+    }
+
+    // the device is synced every period cycles from the first trigger,
+    // with the triggers returned from its sync ignored
+    // false if the handle is stale
+    pub fn set_device_period(
+        &mut self,
+        device_id: TimerDeviceID,
+        first_trigger: u64,
+        period: u64,
+    ) -> bool {
+        let Some(entry) = self.devices.get_mut(device_id) else {
+            return false;
+        };
+
+        entry.period = Some(period.max(1));
+
+        self.schedule_device(device_id.index(), Some(first_trigger));
+
+        true
+    }
+
+    pub fn get_device_trigger(&self, device_id: TimerDeviceID) -> Option<u64> {
+        self.devices.get(device_id)?.trigger
+    }
+
+    pub fn needs_sync(&mut self, cycles: u64) -> bool {
+        self.next_device_trigger()
+            .is_some_and(|next_trigger| next_trigger <= cycles)
+    }
+
+    pub fn sync(&mut self, cycles: u64) {
+        if !self.needs_sync(cycles) {
+            return;
+        }
+
+        while let Some(&Reverse((trigger, device_index, schedule_id))) = self.device_queue.peek() {
+            if trigger > cycles {
+                break;
+            }
+
+            self.device_queue.pop();
+
+            let Some(entry) = self.devices.get_by_index_mut(device_index) else {
+                continue;
+            };
+
+            if entry.schedule_id != schedule_id {
+                continue;
+            }
+
+            let next_trigger = entry.device.sync(cycles);
+
+            let next_trigger = match entry.period {
+                Some(period) => Some(trigger + period),
+                None => next_trigger,
+            };
+
+            // queued after the loop so a device can't be synced twice in one cycle
+            self.rescheduled.push((device_index, next_trigger));
+        }
+
+        for index in 0..self.rescheduled.len() {
+            let (device_index, next_trigger) = self.rescheduled[index];
+
+            self.schedule_device(device_index, next_trigger);
+        }
+
+        self.rescheduled.clear();
+    }
+
+    pub fn next_device_trigger(&mut self) -> Option<u64> {
+        while let Some(&Reverse((trigger, device_index, schedule_id))) = self.device_queue.peek() {
+            let is_current = self
+                .devices
+                .get_by_index_mut(device_index)
+                .is_some_and(|entry| entry.schedule_id == schedule_id);
+
+            if is_current {
+                return Some(trigger);
+            }
+
+            self.device_queue.pop();
+        }
+
+        None
+    }
+
+    pub fn schedule_core_event(&mut self, event: CoreEvent, cycles: u64) {
+        self.core_queue.push(Reverse((cycles, event)));
+    }
+
+    pub fn cancel_core_event(&mut self, event: CoreEvent) {
+        self.core_queue
+            .retain(|&Reverse((_, queued_event))| queued_event != event);
+    }
+
+    pub fn next_core_event(&self) -> Option<u64> {
+        self.core_queue.peek().map(|&Reverse((cycles, _))| cycles)
+    }
+
+    // removes and returns the earliest core event due by cycles
+    pub fn pop_core_event(&mut self, cycles: u64) -> Option<CoreEvent> {
+        let &Reverse((trigger, event)) = self.core_queue.peek()?;
+
+        if trigger > cycles {
+            return None;
+        }
+
+        self.core_queue.pop();
+
+        Some(event)
+    }
+
+    fn schedule_device(&mut self, device_index: usize, trigger: Option<u64>) {
+        let Some(entry) = self.devices.get_by_index_mut(device_index) else {
+            return;
+        };
+
+        self.next_schedule_id += 1;
+
+        entry.schedule_id = self.next_schedule_id;
+        entry.trigger = trigger;
+
+        if let Some(trigger) = trigger {
+            self.device_queue
+                .push(Reverse((trigger, device_index, entry.schedule_id)));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::devices::{
+    CoreEvent, Scheduler, TimerDeviceID,
+    timer_device_mock::{TimerDeviceAccessess, TimerDeviceMock},
+};
+use crate::shared::Shared;
+
+#[test]
+fn it_needs_sync_from_the_earliest_trigger_onwards() {
+    let mut scheduler = Scheduler::default();
+    let (device_1_id, _) = setup_test_device(&mut scheduler, None);
+    let (device_2_id, _) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_1_id, Some(8));
+    scheduler.set_device_trigger(device_2_id, Some(4));
+
+    assert!(!scheduler.needs_sync(3));
+    assert!(scheduler.needs_sync(4));
+    assert!(scheduler.needs_sync(8));
+}
+
+#[test]
+fn it_needs_sync_for_earliest_timings_returned_from_device_sync() {
+    let mut scheduler = Scheduler::default();
+    let (device_1_id, _) = setup_test_device(&mut scheduler, Some(8));
+    let (device_2_id, _) = setup_test_device(&mut scheduler, Some(12));
+
+    scheduler.set_device_trigger(device_1_id, Some(4));
+    scheduler.set_device_trigger(device_2_id, Some(4));
+
+    scheduler.sync(4);
+
+    assert!(!scheduler.needs_sync(4));
+    assert_eq!(scheduler.next_device_trigger(), Some(8));
+    assert_eq!(scheduler.get_device_trigger(device_2_id), Some(12));
+}
+
+#[test]
+fn it_only_syncs_devices_with_due_triggers() {
+    let mut scheduler = Scheduler::default();
+    let (device_1_id, device_1_accesses) = setup_test_device(&mut scheduler, None);
+    let (device_2_id, device_2_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_1_id, Some(4));
+    scheduler.set_device_trigger(device_2_id, Some(8));
+
+    scheduler.sync(4);
+
+    assert_eq!(device_1_accesses.borrow().syncs, [4]);
+    assert_eq!(device_2_accesses.borrow().syncs, []);
+}
+
+#[test]
+fn it_syncs_overdue_triggers_immediately() {
+    let mut scheduler = Scheduler::default();
+    let (device_id, device_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_id, Some(4));
+
+    scheduler.sync(10);
+
+    assert_eq!(device_accesses.borrow().syncs, [10]);
+}
+
+#[test]
+fn it_defers_triggers_returned_for_the_current_cycle_to_the_next_sync() {
+    let mut scheduler = Scheduler::default();
+    let (device_id, device_accesses) = setup_test_device(&mut scheduler, Some(4));
+
+    scheduler.set_device_trigger(device_id, Some(4));
+
+    scheduler.sync(4);
+    scheduler.sync(5);
+
+    assert_eq!(device_accesses.borrow().syncs, [4, 5]);
+}
+
+#[test]
+fn it_cancels_and_replaces_triggers() {
+    let mut scheduler = Scheduler::default();
+    let (device_id, device_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_id, Some(4));
+    scheduler.set_device_trigger(device_id, None);
+
+    scheduler.sync(4);
+
+    scheduler.set_device_trigger(device_id, Some(6));
+    scheduler.set_device_trigger(device_id, Some(8));
+
+    scheduler.sync(6);
+    scheduler.sync(8);
+
+    assert_eq!(device_accesses.borrow().syncs, [8]);
+    assert_eq!(scheduler.next_device_trigger(), None);
+}
+
+#[test]
+fn it_syncs_periodic_devices_without_drift() {
+    let mut scheduler = Scheduler::default();
+    let (device_id, device_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_period(device_id, 10, 10);
+
+    for cycles in [10, 21, 25, 30] {
+        scheduler.sync(cycles);
+    }
+
+    assert_eq!(device_accesses.borrow().syncs, [10, 21, 30]);
+    assert_eq!(scheduler.get_device_trigger(device_id), Some(40));
+}
+
+#[test]
+fn it_pops_due_core_events() {
+    let mut scheduler = Scheduler::default();
+
+    scheduler.schedule_core_event(CoreEvent::Scanline, 128);
+
+    assert_eq!(scheduler.next_core_event(), Some(128));
+    assert_eq!(scheduler.pop_core_event(127), None);
+    assert_eq!(scheduler.pop_core_event(130), Some(CoreEvent::Scanline));
+    assert_eq!(scheduler.next_core_event(), None);
+
+    scheduler.schedule_core_event(CoreEvent::Scanline, 256);
+    scheduler.cancel_core_event(CoreEvent::Scanline);
+
+    assert_eq!(scheduler.next_core_event(), None);
+}
+
+fn setup_test_device(
+    scheduler: &mut Scheduler,
+    sync_result: Option<u64>,
+) -> (TimerDeviceID, Shared<TimerDeviceAccessess>) {
+    let test_device = Box::new(TimerDeviceMock::new(sync_result));
+    let test_device_accesses = test_device.get_accesses();
+
+    let device_id = scheduler.add_device(test_device);
+
+    (device_id, test_device_accesses)
+}
+
+#[test]
+fn it_rejects_stale_handles_of_removed_devices() {
+    let mut scheduler = Scheduler::default();
+    let (device_1_id, device_1_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_1_id, Some(4));
+    scheduler.remove_device(device_1_id);
+
+    let (device_2_id, device_2_accesses) = setup_test_device(&mut scheduler, None);
+
+    assert!(!scheduler.set_device_trigger(device_1_id, Some(6)));
+    assert!(scheduler.remove_device(device_1_id).is_none());
+
+    scheduler.sync(10);
+
+    assert_eq!(device_1_accesses.borrow().syncs, []);
+    assert_eq!(device_2_accesses.borrow().syncs, []);
+    assert_eq!(scheduler.get_device_trigger(device_2_id), None);
+}
+
+#[test]
+fn it_keeps_the_trigger_of_a_replaced_device() {
+    let mut scheduler = Scheduler::default();
+    let (device_id, old_device_accesses) = setup_test_device(&mut scheduler, None);
+
+    scheduler.set_device_trigger(device_id, Some(4));
+
+    let new_device = Box::new(TimerDeviceMock::new(None));
+    let new_device_accesses = new_device.get_accesses();
+
+    assert!(scheduler.replace_device(device_id, new_device).is_some());
+
+    scheduler.sync(4);
+
+    assert_eq!(old_device_accesses.borrow().syncs, []);
+    assert_eq!(new_device_accesses.borrow().syncs, [4]);
+}
@@ -1,4 +1,4 @@
-use super::io_device::IODevice;
+use super::io_device::{DeviceDescription, IODevice};
 use crate::word::Word;
 
 pub struct StaticDevice {
@@ -7,14 +7,25 @@ pub struct StaticDevice {
 }
 
 impl IODevice for StaticDevice {
-    fn read(&mut self, _address: Word, _cycles: u64) -> u8 {
+    fn read(&mut self, _address: Word, _register: u16, _cycles: u64) -> u8 {
         self.read_value
     }
 
-    fn write(&mut self, _address: Word, _value: u8, _cycles: u64) -> bool {
+    fn write(&mut self, _address: Word, _register: u16, _value: u8, _cycles: u64) -> bool {
         if self.panic_on_write {
             panic!();
         }
         false
     }
+
+    fn peek(&self, _address: Word, _register: u16) -> Option<u8> {
+        Some(self.read_value)
+    }
+
+    fn describe(&self) -> Option<DeviceDescription> {
+        Some(DeviceDescription {
+            name: "Static".to_string(),
+            registers: vec![("value".to_string(), self.read_value as u16)],
+        })
+    }
 }
@@ -1,3 +1,5 @@
-pub trait TimerDevice {
+use crate::shared::MaybeSend;
+
+pub trait TimerDevice: MaybeSend {
     fn sync(&mut self, _cycles: u64) -> Option<u64>;
 }
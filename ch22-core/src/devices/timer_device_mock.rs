@@ -1,11 +1,10 @@
-use std::{cell::RefCell, rc::Rc};
-
 use super::timer_device::TimerDevice;
+use crate::shared::Shared;
 
 #[derive(Default)]
 pub struct TimerDeviceMock {
     sync_result: Option<u64>,
-    accesses: Rc<RefCell<TimerDeviceAccessess>>,
+    accesses: Shared<TimerDeviceAccessess>,
 }
 
 impl TimerDeviceMock {
@@ -16,7 +15,7 @@ impl TimerDeviceMock {
         }
     }
 
-    pub fn get_accesses(&self) -> Rc<RefCell<TimerDeviceAccessess>> {
+    pub fn get_accesses(&self) -> Shared<TimerDeviceAccessess> {
         self.accesses.clone()
     }
 }
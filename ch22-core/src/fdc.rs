@@ -0,0 +1,41 @@
+mod disc_image;
+mod fdc_io_device;
+mod fdc_timer_device;
+mod intel_8271;
+
+#[cfg(test)]
+mod tests;
+
+use crate::devices::IOAddressRange;
+use crate::shared::Shared;
+use fdc_io_device::FdcIODevice;
+use fdc_timer_device::FdcTimerDevice;
+
+pub use disc_image::DiscImage;
+pub use intel_8271::Intel8271;
+
+// 8271 registers at &FE80-&FE83 and its data register at &FE84, repeated across &FE80-&FE9F
+pub const FDC_ADDRESS_RANGE: IOAddressRange = IOAddressRange {
+    base: 0xfe80,
+    size: 0x20,
+    mirror_mask: 0x07,
+};
+
+#[derive(Clone, Default)]
+pub struct Fdc {
+    fdc: Shared<Intel8271>,
+}
+
+impl Fdc {
+    pub fn create_io_device(&self) -> FdcIODevice {
+        FdcIODevice::new(self.fdc.clone())
+    }
+
+    pub fn create_timer_device(&self) -> FdcTimerDevice {
+        FdcTimerDevice::new(self.fdc.clone())
+    }
+
+    pub fn chip(&self) -> &Shared<Intel8271> {
+        &self.fdc
+    }
+}
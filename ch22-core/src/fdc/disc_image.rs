@@ -0,0 +1,174 @@
+// a disc held in memory as sectors with their IDs, so formatting can lay tracks out freely
+// loaded from and saved back to .ssd/.dsd images (10 sectors of 256 bytes a track)
+pub struct DiscImage {
+    // tracks for each side
+    sides: Vec<Vec<Vec<Sector>>>,
+    write_protected: bool,
+    dirty: bool,
+}
+
+#[derive(Clone)]
+pub struct Sector {
+    pub id: SectorId,
+    pub data: Vec<u8>,
+    pub deleted: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SectorId {
+    pub track: u8,
+    pub head: u8,
+    pub sector: u8,
+    // 128 << size_code bytes
+    pub size_code: u8,
+}
+
+impl DiscImage {
+    // a double sided image interleaves the sides' tracks, side 0 first
+    pub fn new(data: &[u8], double_sided: bool, write_protected: bool) -> Result<Self, String> {
+        let side_count = if double_sided { 2 } else { 1 };
+
+        if data.is_empty() || data.len() > side_count * DRIVE_TRACKS * TRACK_BYTES {
+            return Err(format!("Invalid disc image size: {}", data.len()));
+        }
+
+        let mut sides = vec![vec![Vec::new(); DRIVE_TRACKS]; side_count];
+
+        for (index, track_data) in data.chunks(TRACK_BYTES).enumerate() {
+            let track = index / side_count;
+            let side = index % side_count;
+
+            sides[side][track] = (0..SECTORS_PER_TRACK)
+                .map(|sector| {
+                    let start = (sector * SECTOR_BYTES).min(track_data.len());
+                    let end = (start + SECTOR_BYTES).min(track_data.len());
+
+                    let mut data = track_data[start..end].to_vec();
+                    data.resize(SECTOR_BYTES, 0);
+
+                    Sector {
+                        id: SectorId {
+                            track: track as u8,
+                            head: 0,
+                            sector: sector as u8,
+                            size_code: 1,
+                        },
+                        data,
+                        deleted: false,
+                    }
+                })
+                .collect();
+        }
+
+        Ok(DiscImage {
+            sides,
+            write_protected,
+            dirty: false,
+        })
+    }
+
+    // back in the layout it was loaded from, up to the last formatted track
+    // sectors 0-9 are taken by their IDs, anything else on a track is dropped
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let track_count = (0..DRIVE_TRACKS)
+            .rev()
+            .find(|&track| self.sides.iter().any(|side| !side[track].is_empty()))
+            .map_or(0, |track| track + 1);
+
+        let mut data = Vec::with_capacity(track_count * self.sides.len() * TRACK_BYTES);
+
+        for track in 0..track_count {
+            for side in &self.sides {
+                for sector_number in 0..SECTORS_PER_TRACK as u8 {
+                    let mut sector_data = side[track]
+                        .iter()
+                        .find(|sector| sector.id.sector == sector_number)
+                        .map_or_else(Vec::new, |sector| sector.data.clone());
+
+                    sector_data.resize(SECTOR_BYTES, 0);
+                    data.extend(sector_data);
+                }
+            }
+        }
+
+        data
+    }
+
+    pub fn is_write_protected(&self) -> bool {
+        self.write_protected
+    }
+
+    // written to since it was loaded or last saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    // the sectors in the order they pass the head from the index hole,
+    // a missing side or track reading as unformatted
+    pub fn track(&self, side: usize, track: usize) -> &[Sector] {
+        self.sides
+            .get(side)
+            .and_then(|tracks| tracks.get(track))
+            .map_or(&[], |sectors| sectors)
+    }
+
+    // false (changing nothing) if there's no such sector
+    pub fn write_sector(
+        &mut self,
+        side: usize,
+        track: usize,
+        index: usize,
+        data: Vec<u8>,
+        deleted: bool,
+    ) -> bool {
+        let Some(sector) = self
+            .sides
+            .get_mut(side)
+            .and_then(|tracks| tracks.get_mut(track))
+            .and_then(|sectors| sectors.get_mut(index))
+        else {
+            return false;
+        };
+
+        sector.data = data;
+        sector.deleted = deleted;
+        self.dirty = true;
+
+        true
+    }
+
+    // false if the side or track doesn't exist
+    pub fn format_track(&mut self, side: usize, track: usize, ids: &[SectorId]) -> bool {
+        let Some(sectors) = self
+            .sides
+            .get_mut(side)
+            .and_then(|tracks| tracks.get_mut(track))
+        else {
+            return false;
+        };
+
+        *sectors = ids
+            .iter()
+            .map(|&id| Sector {
+                id,
+                data: vec![FORMAT_FILL; 128 << (id.size_code & 0x07)],
+                deleted: false,
+            })
+            .collect();
+        self.dirty = true;
+
+        true
+    }
+}
+
+pub const DRIVE_TRACKS: usize = 80;
+const SECTORS_PER_TRACK: usize = 10;
+const SECTOR_BYTES: usize = 256;
+const TRACK_BYTES: usize = SECTORS_PER_TRACK * SECTOR_BYTES;
+
+// the byte freshly formatted sectors are filled with
+const FORMAT_FILL: u8 = 0xe5;
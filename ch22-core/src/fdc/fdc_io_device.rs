@@ -0,0 +1,47 @@
+use super::intel_8271::Intel8271;
+use crate::devices::{DeviceDescription, IODevice, ResetType};
+use crate::shared::Shared;
+use crate::word::Word;
+
+pub struct FdcIODevice {
+    fdc: Shared<Intel8271>,
+}
+
+impl FdcIODevice {
+    pub fn new(fdc: Shared<Intel8271>) -> Self {
+        FdcIODevice { fdc }
+    }
+}
+
+impl IODevice for FdcIODevice {
+    fn read(&mut self, _address: Word, register: u16, cycles: u64) -> u8 {
+        self.fdc.borrow_mut().read(register as u8, cycles)
+    }
+
+    fn write(&mut self, _address: Word, register: u16, value: u8, cycles: u64) -> bool {
+        self.fdc.borrow_mut().write(register as u8, value, cycles);
+
+        false
+    }
+
+    fn get_interrupt(&mut self, cycles: u64) -> bool {
+        let mut fdc = self.fdc.borrow_mut();
+
+        fdc.catch_up(cycles);
+
+        fdc.interrupt()
+    }
+
+    fn peek(&self, _address: Word, register: u16) -> Option<u8> {
+        Some(self.fdc.borrow().peek(register as u8))
+    }
+
+    fn reset(&mut self, _reset_type: ResetType) {
+        self.fdc.borrow_mut().reset();
+    }
+
+    // state isn't saved, a command in progress would need the discs snapshotted with it
+    fn describe(&self) -> Option<DeviceDescription> {
+        Some(self.fdc.borrow().describe())
+    }
+}
@@ -0,0 +1,33 @@
+use super::intel_8271::Intel8271;
+use crate::devices::TimerDevice;
+use crate::shared::Shared;
+
+pub struct FdcTimerDevice {
+    fdc: Shared<Intel8271>,
+}
+
+impl FdcTimerDevice {
+    pub fn new(fdc: Shared<Intel8271>) -> Self {
+        FdcTimerDevice { fdc }
+    }
+}
+
+impl TimerDevice for FdcTimerDevice {
+    // commands are started by register writes the scheduler doesn't see,
+    // so an idle chip is still checked every so often
+    fn sync(&mut self, cycles: u64) -> Option<u64> {
+        let mut fdc = self.fdc.borrow_mut();
+
+        fdc.catch_up(cycles);
+
+        let next_sync = cycles + FDC_SYNC_INTERVAL;
+
+        Some(
+            fdc.next_wakeup()
+                .map_or(next_sync, |wakeup| wakeup.min(next_sync)),
+        )
+    }
+}
+
+// maximum number of cycles between wakeups
+pub const FDC_SYNC_INTERVAL: u64 = 2 * 0x10000;
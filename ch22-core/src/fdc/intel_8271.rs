@@ -0,0 +1,972 @@
+use super::disc_image::{DRIVE_TRACKS, DiscImage, Sector, SectorId};
+use crate::devices::DeviceDescription;
+
+// Intel 8271 in non-DMA mode, as wired on the BBC: its INT drives NMI, raised for each
+// byte of a transfer (with the data request) and as a command with a result completes
+// commands run as a sequence of timed events (head load, steps, settle, waiting for
+// sectors to come round, byte slots), caught up lazily whenever the chip is accessed
+pub struct Intel8271 {
+    drives: [Option<DiscImage>; 2],
+    head_tracks: [u8; 2],
+
+    status: u8,
+    result: u8,
+    data: u8,
+    command: u8,
+    parameters: Vec<u8>,
+
+    step_rate: u8,
+    settle_time: u8,
+    head_load_time: u8,
+    head_loaded: bool,
+
+    // special registers, tracks by drive select
+    bad_tracks: [[u8; 2]; 2],
+    current_tracks: [u8; 2],
+    mode: u8,
+    drive_output: u8,
+    scan_sector: u8,
+    scan_count: u16,
+
+    operation: Option<Operation>,
+    next_event: Option<u64>,
+}
+
+struct Operation {
+    kind: OperationKind,
+    phase: Phase,
+    drive: usize,
+    side: usize,
+    // the logical track and sector looked for, and how many sectors are left
+    track: u8,
+    sector: u8,
+    sectors_left: u8,
+    sector_size: usize,
+    sector_step: u8,
+    // index into the track of the sector being transferred
+    sector_index: usize,
+    search_deadline: u64,
+    buffer: Vec<u8>,
+    position: usize,
+    result_flags: u8,
+    // scans compare upto scan_length bytes of each sector
+    scan_length: usize,
+    scan_mismatch: bool,
+    // formats lay their sectors out from this index pulse
+    index_time: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OperationKind {
+    Seek,
+    Read { with_deleted: bool },
+    Verify,
+    Write { deleted: bool },
+    ReadId,
+    Format,
+    Scan { with_deleted: bool },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Phase {
+    HeadLoad,
+    Step { steps_left: u32, outward: bool },
+    Settle,
+    // waiting for the sector's ID to pass, the transfer starting with its data field
+    Search,
+    Transfer,
+    // the drive spins on to where the command gives up
+    Finish { result: u8 },
+}
+
+impl Default for Intel8271 {
+    fn default() -> Self {
+        Intel8271 {
+            drives: [None, None],
+            head_tracks: [0; 2],
+            status: 0,
+            result: 0,
+            data: 0,
+            command: 0,
+            parameters: Vec::new(),
+            step_rate: DEFAULT_STEP_RATE,
+            settle_time: DEFAULT_SETTLE_TIME,
+            head_load_time: 0,
+            head_loaded: false,
+            bad_tracks: [[NO_BAD_TRACK; 2]; 2],
+            current_tracks: [0; 2],
+            mode: 0,
+            drive_output: 0,
+            scan_sector: 0,
+            scan_count: 0,
+            operation: None,
+            next_event: None,
+        }
+    }
+}
+
+impl Intel8271 {
+    pub fn insert_disc(&mut self, drive: usize, disc: DiscImage) {
+        self.drives[drive] = Some(disc);
+    }
+
+    pub fn eject_disc(&mut self, drive: usize) -> Option<DiscImage> {
+        self.drives.get_mut(drive)?.take()
+    }
+
+    pub fn disc(&self, drive: usize) -> Option<&DiscImage> {
+        self.drives.get(drive)?.as_ref()
+    }
+
+    pub fn disc_mut(&mut self, drive: usize) -> Option<&mut DiscImage> {
+        self.drives.get_mut(drive)?.as_mut()
+    }
+
+    pub fn catch_up(&mut self, cycles: u64) {
+        while let Some(at) = self.next_event {
+            if at > cycles {
+                break;
+            }
+
+            self.next_event = None;
+            self.run_event(at);
+        }
+    }
+
+    pub fn interrupt(&self) -> bool {
+        self.status & STATUS_INTERRUPT != 0
+    }
+
+    pub fn next_wakeup(&self) -> Option<u64> {
+        self.next_event
+    }
+
+    pub fn read(&mut self, register: u8, cycles: u64) -> u8 {
+        self.catch_up(cycles);
+
+        match register {
+            REG_STATUS => self.status,
+            REG_RESULT => {
+                self.status &= !(STATUS_RESULT_FULL | STATUS_INTERRUPT);
+                self.result
+            }
+            REG_DATA.. => {
+                self.status &= !(STATUS_DATA_REQUEST | STATUS_INTERRUPT);
+                self.data
+            }
+            _ => 0xff,
+        }
+    }
+
+    pub fn write(&mut self, register: u8, value: u8, cycles: u64) {
+        self.catch_up(cycles);
+
+        match register {
+            REG_COMMAND => {
+                // ignored while a command is running
+                if self.status & STATUS_BUSY != 0 {
+                    return;
+                }
+
+                self.command = value;
+                self.parameters.clear();
+                self.status |= STATUS_BUSY;
+
+                if parameter_count(value) == 0 {
+                    self.execute(cycles);
+                }
+            }
+            REG_PARAMETER => {
+                if self.status & STATUS_BUSY == 0 || self.operation.is_some() {
+                    return;
+                }
+
+                self.parameters.push(value);
+
+                if self.parameters.len() == parameter_count(self.command) {
+                    self.execute(cycles);
+                }
+            }
+            REG_RESET if value & 0x01 != 0 => self.reset(),
+            REG_DATA.. => {
+                self.data = value;
+                self.status &= !(STATUS_DATA_REQUEST | STATUS_INTERRUPT);
+            }
+            _ => (),
+        }
+    }
+
+    pub fn peek(&self, register: u8) -> u8 {
+        match register {
+            REG_STATUS => self.status,
+            REG_RESULT => self.result,
+            REG_DATA.. => self.data,
+            _ => 0xff,
+        }
+    }
+
+    // abandons any command, the discs and heads are left where they are
+    pub fn reset(&mut self) {
+        *self = Intel8271 {
+            drives: std::mem::take(&mut self.drives),
+            head_tracks: self.head_tracks,
+            ..Intel8271::default()
+        };
+    }
+
+    pub fn describe(&self) -> DeviceDescription {
+        let registers = [
+            ("STATUS", self.status),
+            ("RESULT", self.result),
+            ("COMMAND", self.command),
+            ("DATA", self.data),
+            ("TRACK0", self.current_tracks[0]),
+            ("TRACK1", self.current_tracks[1]),
+        ];
+
+        DeviceDescription {
+            name: "8271 FDC".to_string(),
+            registers: registers
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value as u16))
+                .collect(),
+        }
+    }
+
+    fn execute(&mut self, at: u64) {
+        let parameters = std::mem::take(&mut self.parameters);
+        let parameter = |index: usize| parameters[index];
+
+        // single record commands transfer one 128 byte sector
+        let single = (1, 0);
+        let variable = || (parameter(2) & 0x1f, parameter(2) >> 5);
+
+        let (kind, (sectors, size_code)) = match self.command & 0x3f {
+            COMMAND_SCAN_DATA => (
+                OperationKind::Scan {
+                    with_deleted: false,
+                },
+                variable(),
+            ),
+            COMMAND_SCAN_DATA_AND_DELETED => {
+                (OperationKind::Scan { with_deleted: true }, variable())
+            }
+            COMMAND_WRITE_DATA_SINGLE => (OperationKind::Write { deleted: false }, single),
+            COMMAND_WRITE_DATA => (OperationKind::Write { deleted: false }, variable()),
+            COMMAND_WRITE_DELETED_DATA_SINGLE => (OperationKind::Write { deleted: true }, single),
+            COMMAND_WRITE_DELETED_DATA => (OperationKind::Write { deleted: true }, variable()),
+            COMMAND_READ_DATA_SINGLE => (
+                OperationKind::Read {
+                    with_deleted: false,
+                },
+                single,
+            ),
+            COMMAND_READ_DATA => (
+                OperationKind::Read {
+                    with_deleted: false,
+                },
+                variable(),
+            ),
+            COMMAND_READ_DATA_AND_DELETED_SINGLE => {
+                (OperationKind::Read { with_deleted: true }, single)
+            }
+            COMMAND_READ_DATA_AND_DELETED => {
+                (OperationKind::Read { with_deleted: true }, variable())
+            }
+            COMMAND_READ_ID => (OperationKind::ReadId, (parameter(2), 0)),
+            COMMAND_VERIFY_DATA_SINGLE => (OperationKind::Verify, single),
+            COMMAND_VERIFY_DATA => (OperationKind::Verify, variable()),
+            COMMAND_FORMAT => (OperationKind::Format, variable()),
+            COMMAND_SEEK => (OperationKind::Seek, (0, 0)),
+            COMMAND_READ_DRIVE_STATUS => {
+                self.select_drives(self.command);
+                self.complete_without_interrupt(Some(self.drive_input(at)));
+                return;
+            }
+            COMMAND_SPECIFY => {
+                self.specify(&parameters);
+                self.complete_without_interrupt(None);
+                return;
+            }
+            COMMAND_WRITE_SPECIAL_REGISTER => {
+                self.write_special_register(parameter(0), parameter(1));
+                self.complete_without_interrupt(None);
+                return;
+            }
+            COMMAND_READ_SPECIAL_REGISTER => {
+                let value = self.read_special_register(parameter(0), at);
+                self.complete_without_interrupt(Some(value));
+                return;
+            }
+            // the chip has no other commands
+            _ => {
+                self.complete_without_interrupt(None);
+                return;
+            }
+        };
+
+        let is_scan = matches!(kind, OperationKind::Scan { .. });
+
+        let operation = Operation {
+            kind,
+            phase: Phase::HeadLoad,
+            drive: 0,
+            side: (self.drive_output & DRIVE_OUTPUT_SIDE != 0) as usize,
+            track: parameter(0),
+            sector: parameters.get(1).copied().unwrap_or(0),
+            sectors_left: sectors,
+            sector_size: 128 << size_code,
+            sector_step: if is_scan { parameter(3) } else { 1 },
+            sector_index: 0,
+            search_deadline: 0,
+            buffer: Vec::new(),
+            position: 0,
+            result_flags: 0,
+            scan_length: if is_scan && parameter(4) != 0 {
+                parameter(4) as usize
+            } else {
+                128 << size_code
+            },
+            scan_mismatch: false,
+            index_time: 0,
+        };
+
+        self.begin(operation, at);
+    }
+
+    fn begin(&mut self, mut operation: Operation, at: u64) {
+        let Some(drive) = self.select_drives(self.command) else {
+            self.finish(RESULT_NOT_READY);
+            return;
+        };
+
+        operation.drive = drive;
+
+        let Some(disc) = &self.drives[drive] else {
+            self.finish(RESULT_NOT_READY);
+            return;
+        };
+
+        let is_writing = matches!(
+            operation.kind,
+            OperationKind::Write { .. } | OperationKind::Format
+        );
+
+        if is_writing && disc.is_write_protected() {
+            self.finish(RESULT_WRITE_PROTECTED);
+            return;
+        }
+
+        let next_event = if self.head_loaded {
+            self.start_seek(&mut operation, at)
+        } else {
+            self.head_loaded = true;
+
+            Some(at + self.head_load_time as u64 * HEAD_LOAD_UNIT_CYCLES)
+        };
+
+        self.continue_with(operation, next_event);
+    }
+
+    fn run_event(&mut self, at: u64) {
+        let Some(mut operation) = self.operation.take() else {
+            return;
+        };
+
+        let next_event = match operation.phase {
+            Phase::HeadLoad => self.start_seek(&mut operation, at),
+            Phase::Step { .. } => self.step(&mut operation, at),
+            Phase::Settle => self.seek_complete(&mut operation, at),
+            Phase::Search => self.start_transfer(&mut operation, at),
+            Phase::Transfer => self.transfer_byte(&mut operation, at),
+            Phase::Finish { result } => {
+                self.finish(result);
+                None
+            }
+        };
+
+        self.continue_with(operation, next_event);
+    }
+
+    // None from an event means the operation has finished
+    fn continue_with(&mut self, operation: Operation, next_event: Option<u64>) {
+        if next_event.is_some() {
+            self.operation = Some(operation);
+            self.next_event = next_event;
+        }
+    }
+
+    // seeking to track 0 steps out until the drive reports the head there
+    fn start_seek(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        let drive = operation.drive;
+
+        let (steps, outward) = if operation.track == 0 {
+            (self.head_tracks[drive] as u32, true)
+        } else {
+            let target = self.physical_track(drive, operation.track);
+            let current = self.current_tracks[drive];
+
+            (target.abs_diff(current) as u32, target < current)
+        };
+
+        if steps == 0 {
+            return self.seek_complete(operation, at);
+        }
+
+        operation.phase = Phase::Step {
+            steps_left: steps,
+            outward,
+        };
+
+        Some(at + self.step_rate as u64 * MS_CYCLES)
+    }
+
+    fn step(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        let Phase::Step {
+            steps_left,
+            outward,
+        } = operation.phase
+        else {
+            return None;
+        };
+
+        let drive = operation.drive;
+
+        if outward {
+            self.head_tracks[drive] = self.head_tracks[drive].saturating_sub(1);
+            self.current_tracks[drive] = self.current_tracks[drive].saturating_sub(1);
+        } else {
+            self.head_tracks[drive] = (self.head_tracks[drive] + 1).min(DRIVE_TRACKS as u8 - 1);
+            self.current_tracks[drive] = self.current_tracks[drive].saturating_add(1);
+        }
+
+        if steps_left > 1 {
+            operation.phase = Phase::Step {
+                steps_left: steps_left - 1,
+                outward,
+            };
+
+            return Some(at + self.step_rate as u64 * MS_CYCLES);
+        }
+
+        operation.phase = Phase::Settle;
+
+        Some(at + self.settle_time as u64 * MS_CYCLES)
+    }
+
+    fn seek_complete(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        if operation.track == 0 {
+            self.current_tracks[operation.drive] = 0;
+        }
+
+        match operation.kind {
+            OperationKind::Seek => {
+                self.finish(RESULT_OK);
+                None
+            }
+            // the sector IDs are requested in slots laid out from the next index pulse
+            OperationKind::Format => {
+                operation.index_time = next_angle_time(at, 0);
+                operation.phase = Phase::Transfer;
+
+                Some(operation.index_time)
+            }
+            _ => {
+                operation.search_deadline = at + SEARCH_REVOLUTIONS * REVOLUTION_CYCLES;
+
+                Some(self.search(operation, at))
+            }
+        }
+    }
+
+    // sectors pass the head spaced evenly around the track from the index pulse
+    fn search(&self, operation: &mut Operation, at: u64) -> u64 {
+        let sectors = self.track_under_head(operation);
+        let sector_count = sectors.len() as u64;
+
+        let found = sectors
+            .iter()
+            .enumerate()
+            .filter(|(_, sector)| {
+                operation.kind == OperationKind::ReadId
+                    || sector.id.track == operation.track && sector.id.sector == operation.sector
+            })
+            .map(|(index, _)| {
+                let angle = index as u64 * REVOLUTION_CYCLES / sector_count;
+
+                (
+                    next_angle_time(at, angle) + ID_FIELD_BYTES * BYTE_CYCLES,
+                    index,
+                )
+            })
+            .min();
+
+        match found {
+            Some((data_time, index)) if data_time <= operation.search_deadline => {
+                operation.phase = Phase::Search;
+                operation.sector_index = index;
+
+                data_time
+            }
+            _ => {
+                operation.phase = Phase::Finish {
+                    result: RESULT_SECTOR_NOT_FOUND,
+                };
+
+                operation.search_deadline
+            }
+        }
+    }
+
+    fn start_transfer(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        // the disc may have been ejected or swapped since the sector was found
+        let Some(sector) = self.track_under_head(operation).get(operation.sector_index) else {
+            let result = match self.drives[operation.drive] {
+                Some(_) => RESULT_SECTOR_NOT_FOUND,
+                None => RESULT_NOT_READY,
+            };
+
+            self.finish(result);
+            return None;
+        };
+        let (id, deleted) = (sector.id, sector.deleted);
+
+        let mut data = sector.data.clone();
+        data.resize(operation.sector_size, 0);
+
+        let is_reading = matches!(
+            operation.kind,
+            OperationKind::Read { .. } | OperationKind::Scan { .. } | OperationKind::Verify
+        );
+
+        if deleted && is_reading {
+            operation.result_flags |= RESULT_DELETED_DATA;
+
+            // commands not taking deleted data stop after the sector
+            if let OperationKind::Read {
+                with_deleted: false,
+            }
+            | OperationKind::Scan {
+                with_deleted: false,
+            }
+            | OperationKind::Verify = operation.kind
+            {
+                operation.sectors_left = 1;
+            }
+        }
+
+        operation.buffer = match operation.kind {
+            OperationKind::Read { .. } | OperationKind::Scan { .. } => data,
+            OperationKind::ReadId => vec![id.track, id.head, id.sector, id.size_code],
+            _ => Vec::new(),
+        };
+        operation.position = 0;
+        operation.scan_mismatch = false;
+        operation.phase = Phase::Transfer;
+
+        match operation.kind {
+            // nothing passes to the cpu, the data field is just read through
+            OperationKind::Verify => {
+                operation.position = operation.sector_size;
+
+                Some(at + operation.sector_size as u64 * BYTE_CYCLES)
+            }
+            _ => self.transfer_byte(operation, at),
+        }
+    }
+
+    // a byte slot: reads offer the next byte, writes take the byte given since the
+    // last request, either being late if the cpu hasn't answered the last request
+    fn transfer_byte(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        let is_late = self.status & STATUS_DATA_REQUEST != 0;
+
+        if operation.position > 0 && is_late && operation.kind != OperationKind::Verify {
+            self.finish(RESULT_LATE_DMA);
+            return None;
+        }
+
+        let length = match operation.kind {
+            OperationKind::Read { .. } | OperationKind::ReadId => operation.buffer.len(),
+            OperationKind::Scan { .. } => operation.scan_length.min(operation.sector_size),
+            OperationKind::Format => operation.sectors_left as usize * 4,
+            _ => operation.sector_size,
+        };
+
+        if operation.position > 0 {
+            let index = operation.position - 1;
+
+            match operation.kind {
+                OperationKind::Write { .. } | OperationKind::Format => {
+                    operation.buffer.push(self.data);
+                }
+                // &FF from the cpu matches anything
+                OperationKind::Scan { .. }
+                    if self.data != 0xff && self.data != operation.buffer[index] =>
+                {
+                    operation.scan_mismatch = true;
+                }
+                _ => (),
+            }
+        }
+
+        if operation.position == length {
+            return self.sector_complete(operation, at);
+        }
+
+        if let OperationKind::Read { .. } | OperationKind::ReadId = operation.kind {
+            self.data = operation.buffer[operation.position];
+        }
+
+        self.status |= STATUS_DATA_REQUEST | STATUS_INTERRUPT;
+        operation.position += 1;
+
+        Some(match operation.kind {
+            OperationKind::Format => {
+                let sector = (operation.position / 4) as u64;
+                let byte = (operation.position % 4) as u64;
+
+                operation.index_time
+                    + sector * REVOLUTION_CYCLES / operation.sectors_left as u64
+                    + byte * BYTE_CYCLES
+            }
+            _ => at + BYTE_CYCLES,
+        })
+    }
+
+    fn sector_complete(&mut self, operation: &mut Operation, at: u64) -> Option<u64> {
+        let (drive, side) = (operation.drive, operation.side);
+        let track = self.head_tracks[drive] as usize;
+
+        match operation.kind {
+            OperationKind::Write { deleted } => {
+                let data = std::mem::take(&mut operation.buffer);
+
+                if let Some(disc) = &mut self.drives[drive] {
+                    disc.write_sector(side, track, operation.sector_index, data, deleted);
+                }
+            }
+            OperationKind::Format => {
+                let ids: Vec<SectorId> = operation
+                    .buffer
+                    .chunks(4)
+                    .map(|id| SectorId {
+                        track: id[0],
+                        head: id[1],
+                        sector: id[2],
+                        size_code: id[3],
+                    })
+                    .collect();
+
+                if let Some(disc) = &mut self.drives[drive] {
+                    disc.format_track(side, track, &ids);
+                }
+
+                self.finish(RESULT_OK);
+                return None;
+            }
+            OperationKind::Scan { .. } if !operation.scan_mismatch => {
+                self.scan_sector = operation.sector;
+                self.scan_count = operation.sectors_left as u16 - 1;
+                self.finish(RESULT_SCAN_MET_EQUAL | operation.result_flags);
+                return None;
+            }
+            _ => (),
+        }
+
+        operation.sectors_left = operation.sectors_left.saturating_sub(1);
+
+        if operation.sectors_left == 0 {
+            let result = match operation.kind {
+                OperationKind::Scan { .. } => RESULT_SCAN_NOT_MET,
+                _ => RESULT_OK,
+            };
+
+            self.finish(result | operation.result_flags);
+            return None;
+        }
+
+        operation.sector = operation.sector.wrapping_add(operation.sector_step);
+        operation.search_deadline = at + SEARCH_REVOLUTIONS * REVOLUTION_CYCLES;
+
+        Some(self.search(operation, at))
+    }
+
+    fn finish(&mut self, result: u8) {
+        self.operation = None;
+        self.next_event = None;
+
+        self.result = result;
+        self.status = self.status & !(STATUS_BUSY | STATUS_DATA_REQUEST)
+            | STATUS_RESULT_FULL
+            | STATUS_INTERRUPT;
+    }
+
+    // specify, the special registers and drive status complete at once without an interrupt
+    fn complete_without_interrupt(&mut self, result: Option<u8>) {
+        self.status &= !STATUS_BUSY;
+
+        if let Some(result) = result {
+            self.result = result;
+            self.status |= STATUS_RESULT_FULL;
+        }
+    }
+
+    // the command's select bits drive the select lines, 0x40 for drive 0 and 0x80 for drive 1
+    // a change of drive unloads the head
+    fn select_drives(&mut self, command: u8) -> Option<usize> {
+        let selects = command & (DRIVE_OUTPUT_SELECT_0 | DRIVE_OUTPUT_SELECT_1);
+
+        if selects != self.drive_output & (DRIVE_OUTPUT_SELECT_0 | DRIVE_OUTPUT_SELECT_1) {
+            self.head_loaded = false;
+        }
+
+        self.drive_output =
+            self.drive_output & !(DRIVE_OUTPUT_SELECT_0 | DRIVE_OUTPUT_SELECT_1) | selects;
+
+        match selects {
+            0 => None,
+            DRIVE_OUTPUT_SELECT_1 => Some(1),
+            _ => Some(0),
+        }
+    }
+
+    fn selected_drive(&self) -> Option<usize> {
+        match self.drive_output & (DRIVE_OUTPUT_SELECT_0 | DRIVE_OUTPUT_SELECT_1) {
+            0 => None,
+            DRIVE_OUTPUT_SELECT_1 => Some(1),
+            _ => Some(0),
+        }
+    }
+
+    fn track_under_head(&self, operation: &Operation) -> &[Sector] {
+        let track = self.head_tracks[operation.drive] as usize;
+
+        self.drives[operation.drive]
+            .as_ref()
+            .map_or(&[], |disc| disc.track(operation.side, track))
+    }
+
+    // logical tracks skip over the drive's bad tracks
+    fn physical_track(&self, drive: usize, track: u8) -> u8 {
+        let mut bad_tracks = self.bad_tracks[drive];
+        bad_tracks.sort();
+
+        bad_tracks
+            .into_iter()
+            .filter(|&bad_track| bad_track != NO_BAD_TRACK)
+            .fold(track, |physical, bad_track| {
+                if bad_track <= physical {
+                    physical.saturating_add(1)
+                } else {
+                    physical
+                }
+            })
+    }
+
+    fn drive_input(&self, at: u64) -> u8 {
+        let mut input = 0;
+
+        if self.drives[0].is_some() {
+            input |= DRIVE_INPUT_READY_0;
+        }
+
+        if self.drives[1].is_some() {
+            input |= DRIVE_INPUT_READY_1;
+        }
+
+        if let Some(drive) = self.selected_drive() {
+            if self.head_tracks[drive] == 0 {
+                input |= DRIVE_INPUT_TRACK_0;
+            }
+
+            if let Some(disc) = &self.drives[drive] {
+                if disc.is_write_protected() {
+                    input |= DRIVE_INPUT_WRITE_PROTECT;
+                }
+
+                if at % REVOLUTION_CYCLES < INDEX_PULSE_CYCLES {
+                    input |= DRIVE_INPUT_INDEX;
+                }
+            }
+        }
+
+        input
+    }
+
+    // 0x0d sets the step rate, settle time (both in ms) and head load time (low nibble,
+    // in 8ms units), 0x10 and 0x18 the bad tracks and current track for each drive
+    fn specify(&mut self, parameters: &[u8]) {
+        match parameters[0] {
+            0x0d => {
+                self.step_rate = parameters[1];
+                self.settle_time = parameters[2];
+                self.head_load_time = parameters[3] & 0x0f;
+            }
+            0x10 | 0x18 => {
+                let drive = (parameters[0] >> 3 & 0x01) as usize;
+
+                self.bad_tracks[drive] = [parameters[1], parameters[2]];
+                self.current_tracks[drive] = parameters[3];
+            }
+            _ => (),
+        }
+    }
+
+    fn read_special_register(&self, register: u8, at: u64) -> u8 {
+        match register {
+            SPECIAL_SCAN_SECTOR => self.scan_sector,
+            SPECIAL_SCAN_COUNT_LOW => self.scan_count as u8,
+            SPECIAL_SCAN_COUNT_HIGH => (self.scan_count >> 8) as u8,
+            SPECIAL_BAD_TRACK_1_DRIVE_0 => self.bad_tracks[0][0],
+            SPECIAL_BAD_TRACK_2_DRIVE_0 => self.bad_tracks[0][1],
+            SPECIAL_CURRENT_TRACK_DRIVE_0 => self.current_tracks[0],
+            SPECIAL_BAD_TRACK_1_DRIVE_1 => self.bad_tracks[1][0],
+            SPECIAL_BAD_TRACK_2_DRIVE_1 => self.bad_tracks[1][1],
+            SPECIAL_CURRENT_TRACK_DRIVE_1 => self.current_tracks[1],
+            SPECIAL_MODE => self.mode,
+            SPECIAL_DRIVE_INPUT => self.drive_input(at),
+            SPECIAL_DRIVE_OUTPUT => self.drive_output,
+            _ => 0,
+        }
+    }
+
+    fn write_special_register(&mut self, register: u8, value: u8) {
+        match register {
+            SPECIAL_SCAN_SECTOR => self.scan_sector = value,
+            SPECIAL_SCAN_COUNT_LOW => self.scan_count = self.scan_count & 0xff00 | value as u16,
+            SPECIAL_SCAN_COUNT_HIGH => {
+                self.scan_count = self.scan_count & 0x00ff | (value as u16) << 8;
+            }
+            SPECIAL_BAD_TRACK_1_DRIVE_0 => self.bad_tracks[0][0] = value,
+            SPECIAL_BAD_TRACK_2_DRIVE_0 => self.bad_tracks[0][1] = value,
+            SPECIAL_CURRENT_TRACK_DRIVE_0 => self.current_tracks[0] = value,
+            SPECIAL_BAD_TRACK_1_DRIVE_1 => self.bad_tracks[1][0] = value,
+            SPECIAL_BAD_TRACK_2_DRIVE_1 => self.bad_tracks[1][1] = value,
+            SPECIAL_CURRENT_TRACK_DRIVE_1 => self.current_tracks[1] = value,
+            SPECIAL_MODE => self.mode = value,
+            SPECIAL_DRIVE_OUTPUT => {
+                if (value ^ self.drive_output) & (DRIVE_OUTPUT_SELECT_0 | DRIVE_OUTPUT_SELECT_1)
+                    != 0
+                {
+                    self.head_loaded = false;
+                }
+
+                self.drive_output = value;
+            }
+            _ => (),
+        }
+    }
+}
+
+fn parameter_count(command: u8) -> usize {
+    match command & 0x3f {
+        COMMAND_SCAN_DATA | COMMAND_SCAN_DATA_AND_DELETED | COMMAND_FORMAT => 5,
+        COMMAND_SPECIFY => 4,
+        COMMAND_WRITE_DATA
+        | COMMAND_WRITE_DELETED_DATA
+        | COMMAND_READ_DATA
+        | COMMAND_READ_DATA_AND_DELETED
+        | COMMAND_READ_ID
+        | COMMAND_VERIFY_DATA => 3,
+        COMMAND_WRITE_DATA_SINGLE
+        | COMMAND_WRITE_DELETED_DATA_SINGLE
+        | COMMAND_READ_DATA_SINGLE
+        | COMMAND_READ_DATA_AND_DELETED_SINGLE
+        | COMMAND_VERIFY_DATA_SINGLE
+        | COMMAND_WRITE_SPECIAL_REGISTER => 2,
+        COMMAND_SEEK | COMMAND_READ_SPECIAL_REGISTER => 1,
+        _ => 0,
+    }
+}
+
+// the first time from cycles that the disc is at angle (cycles past the index pulse)
+fn next_angle_time(cycles: u64, angle: u64) -> u64 {
+    let revolution_start = cycles - cycles % REVOLUTION_CYCLES;
+    let time = revolution_start + angle;
+
+    if time < cycles {
+        time + REVOLUTION_CYCLES
+    } else {
+        time
+    }
+}
+
+// 300rpm, single density FM at 125kbit/s (64us a byte)
+pub const REVOLUTION_CYCLES: u64 = 400_000;
+pub const BYTE_CYCLES: u64 = 128;
+const MS_CYCLES: u64 = 2000;
+const HEAD_LOAD_UNIT_CYCLES: u64 = 8 * MS_CYCLES;
+const INDEX_PULSE_CYCLES: u64 = 4 * MS_CYCLES;
+// from the start of a sector's ID field to its data (ID, CRC and gap 2)
+pub const ID_FIELD_BYTES: u64 = 18;
+// the index pulses seen before a missing sector is given up on
+const SEARCH_REVOLUTIONS: u64 = 2;
+
+const DEFAULT_STEP_RATE: u8 = 12;
+const DEFAULT_SETTLE_TIME: u8 = 20;
+const NO_BAD_TRACK: u8 = 0xff;
+
+pub const REG_STATUS: u8 = 0;
+pub const REG_COMMAND: u8 = 0;
+pub const REG_RESULT: u8 = 1;
+pub const REG_PARAMETER: u8 = 1;
+pub const REG_RESET: u8 = 2;
+pub const REG_DATA: u8 = 4;
+
+pub const STATUS_BUSY: u8 = 0x80;
+pub const STATUS_RESULT_FULL: u8 = 0x10;
+pub const STATUS_INTERRUPT: u8 = 0x08;
+pub const STATUS_DATA_REQUEST: u8 = 0x04;
+
+pub const COMMAND_SCAN_DATA: u8 = 0x00;
+pub const COMMAND_SCAN_DATA_AND_DELETED: u8 = 0x04;
+pub const COMMAND_WRITE_DATA_SINGLE: u8 = 0x0a;
+pub const COMMAND_WRITE_DATA: u8 = 0x0b;
+pub const COMMAND_WRITE_DELETED_DATA_SINGLE: u8 = 0x0e;
+pub const COMMAND_WRITE_DELETED_DATA: u8 = 0x0f;
+pub const COMMAND_READ_DATA_SINGLE: u8 = 0x12;
+pub const COMMAND_READ_DATA: u8 = 0x13;
+pub const COMMAND_READ_DATA_AND_DELETED_SINGLE: u8 = 0x16;
+pub const COMMAND_READ_DATA_AND_DELETED: u8 = 0x17;
+pub const COMMAND_READ_ID: u8 = 0x1b;
+pub const COMMAND_VERIFY_DATA_SINGLE: u8 = 0x1e;
+pub const COMMAND_VERIFY_DATA: u8 = 0x1f;
+pub const COMMAND_FORMAT: u8 = 0x23;
+pub const COMMAND_SEEK: u8 = 0x29;
+pub const COMMAND_READ_DRIVE_STATUS: u8 = 0x2c;
+pub const COMMAND_SPECIFY: u8 = 0x35;
+pub const COMMAND_WRITE_SPECIAL_REGISTER: u8 = 0x3a;
+pub const COMMAND_READ_SPECIAL_REGISTER: u8 = 0x3d;
+
+pub const RESULT_OK: u8 = 0x00;
+pub const RESULT_SCAN_MET_EQUAL: u8 = 0x00;
+pub const RESULT_SCAN_NOT_MET: u8 = 0x04;
+pub const RESULT_LATE_DMA: u8 = 0x0a;
+pub const RESULT_NOT_READY: u8 = 0x10;
+pub const RESULT_WRITE_PROTECTED: u8 = 0x12;
+pub const RESULT_SECTOR_NOT_FOUND: u8 = 0x18;
+pub const RESULT_DELETED_DATA: u8 = 0x20;
+
+const SPECIAL_SCAN_SECTOR: u8 = 0x06;
+const SPECIAL_BAD_TRACK_1_DRIVE_0: u8 = 0x10;
+const SPECIAL_BAD_TRACK_2_DRIVE_0: u8 = 0x11;
+const SPECIAL_CURRENT_TRACK_DRIVE_0: u8 = 0x12;
+const SPECIAL_SCAN_COUNT_LOW: u8 = 0x13;
+const SPECIAL_SCAN_COUNT_HIGH: u8 = 0x14;
+const SPECIAL_MODE: u8 = 0x17;
+const SPECIAL_BAD_TRACK_1_DRIVE_1: u8 = 0x18;
+const SPECIAL_BAD_TRACK_2_DRIVE_1: u8 = 0x19;
+const SPECIAL_CURRENT_TRACK_DRIVE_1: u8 = 0x1a;
+pub const SPECIAL_DRIVE_INPUT: u8 = 0x22;
+pub const SPECIAL_DRIVE_OUTPUT: u8 = 0x23;
+
+// the BBC wires the 8271's write fault reset output to the drives' side select
+pub const DRIVE_OUTPUT_SIDE: u8 = 0x20;
+const DRIVE_OUTPUT_SELECT_0: u8 = 0x40;
+const DRIVE_OUTPUT_SELECT_1: u8 = 0x80;
+
+const DRIVE_INPUT_TRACK_0: u8 = 0x02;
+const DRIVE_INPUT_READY_0: u8 = 0x04;
+const DRIVE_INPUT_WRITE_PROTECT: u8 = 0x08;
+const DRIVE_INPUT_INDEX: u8 = 0x10;
+const DRIVE_INPUT_READY_1: u8 = 0x40;
@@ -0,0 +1,353 @@
+use super::disc_image::SectorId;
+use super::intel_8271::*;
+use super::*;
+
+// each byte of a sector holds its track and sector number
+fn numbered_ssd(tracks: usize) -> Vec<u8> {
+    (0..tracks * 10)
+        .flat_map(|sector| [(sector / 10 * 0x10 + sector % 10) as u8; 256])
+        .collect()
+}
+
+fn fdc_with_disc(write_protected: bool) -> Intel8271 {
+    let mut fdc = Intel8271::default();
+
+    fdc.insert_disc(
+        0,
+        DiscImage::new(&numbered_ssd(40), false, write_protected).unwrap(),
+    );
+
+    fdc
+}
+
+// runs a command on drive 0, answering data requests as the nmi handler would (a little
+// after each is raised) by reading bytes or writing them from to_write
+// returns the result (0 for commands without one), the bytes read and the cycle it finished
+fn run_command(
+    fdc: &mut Intel8271,
+    cycles: u64,
+    command: u8,
+    parameters: &[u8],
+    to_write: &[u8],
+) -> (u8, Vec<u8>, u64) {
+    let mut cycles = cycles;
+    let mut to_write = to_write.iter();
+    let mut read = Vec::new();
+
+    fdc.write(REG_COMMAND, 0x40 | command, cycles);
+
+    for &parameter in parameters {
+        fdc.write(REG_PARAMETER, parameter, cycles);
+    }
+
+    loop {
+        let status = fdc.read(REG_STATUS, cycles);
+
+        if status & STATUS_RESULT_FULL != 0 {
+            return (fdc.read(REG_RESULT, cycles), read, cycles);
+        }
+
+        if status & STATUS_BUSY == 0 {
+            return (0, read, cycles);
+        }
+
+        if status & STATUS_DATA_REQUEST != 0 {
+            match to_write.next() {
+                Some(&value) => fdc.write(REG_DATA, value, cycles),
+                None => read.push(fdc.read(REG_DATA, cycles)),
+            }
+        }
+
+        cycles = fdc.next_wakeup().expect("command stalled") + 10;
+    }
+}
+
+#[test]
+fn it_reads_sectors_after_seeking_to_their_track() {
+    let mut fdc = fdc_with_disc(false);
+
+    // track 2 from sector 8, 2 sectors of 256 bytes
+    let (result, read, _) = run_command(&mut fdc, 0, COMMAND_READ_DATA, &[2, 8, 0x22], &[]);
+
+    assert_eq!(result, RESULT_OK);
+    assert_eq!(read.len(), 512);
+    assert!(read[..256].iter().all(|&value| value == 0x28));
+    assert!(read[256..].iter().all(|&value| value == 0x29));
+
+    let (_, _, _) = run_command(&mut fdc, 0, COMMAND_READ_SPECIAL_REGISTER, &[0x12], &[]);
+    assert_eq!(fdc.peek(REG_RESULT), 2);
+}
+
+#[test]
+fn it_steps_settles_and_waits_for_the_sector_to_come_round() {
+    let mut fdc = fdc_with_disc(false);
+
+    // 6ms steps, 20ms settle, no head load time
+    run_command(&mut fdc, 0, COMMAND_SPECIFY, &[0x0d, 6, 20, 0], &[]);
+
+    let (result, _, cycles) = run_command(&mut fdc, 0, COMMAND_SEEK, &[10], &[]);
+
+    assert_eq!(result, RESULT_OK);
+    assert_eq!(cycles, (10 * 6 + 20) * 2000 + 10);
+
+    // sector 5 of 10 comes round half a revolution from the index pulse
+    let start = REVOLUTION_CYCLES * 3;
+    let (result, _, cycles) = run_command(&mut fdc, start, COMMAND_READ_DATA_SINGLE, &[10, 5], &[]);
+
+    assert_eq!(result, RESULT_OK);
+    assert_eq!(
+        cycles,
+        start + REVOLUTION_CYCLES / 2 + (ID_FIELD_BYTES + 128) * BYTE_CYCLES + 10
+    );
+}
+
+#[test]
+fn it_gives_up_on_missing_sectors_after_two_revolutions() {
+    let mut fdc = fdc_with_disc(false);
+
+    let (result, read, cycles) = run_command(&mut fdc, 0, COMMAND_READ_DATA_SINGLE, &[0, 10], &[]);
+
+    assert_eq!(result, RESULT_SECTOR_NOT_FOUND);
+    assert!(read.is_empty());
+    assert_eq!(cycles, 2 * REVOLUTION_CYCLES + 10);
+}
+
+#[test]
+fn it_reports_late_data_when_requests_go_unanswered() {
+    let mut fdc = fdc_with_disc(false);
+
+    fdc.write(REG_COMMAND, 0x40 | COMMAND_READ_DATA_SINGLE, 0);
+    fdc.write(REG_PARAMETER, 0, 0);
+    fdc.write(REG_PARAMETER, 0, 0);
+
+    fdc.catch_up(REVOLUTION_CYCLES);
+
+    assert_eq!(fdc.read(REG_STATUS, REVOLUTION_CYCLES) & STATUS_BUSY, 0);
+    assert_eq!(fdc.read(REG_RESULT, REVOLUTION_CYCLES), RESULT_LATE_DMA);
+}
+
+#[test]
+fn it_writes_sectors_marking_the_disc_dirty() {
+    let mut fdc = fdc_with_disc(false);
+
+    let data: Vec<u8> = (0..=255).collect();
+    let (result, _, cycles) = run_command(&mut fdc, 0, COMMAND_WRITE_DATA, &[3, 4, 0x21], &data);
+
+    assert_eq!(result, RESULT_OK);
+    assert!(fdc.disc(0).unwrap().is_dirty());
+
+    let (_, read, _) = run_command(&mut fdc, cycles, COMMAND_READ_DATA, &[3, 4, 0x21], &[]);
+    assert_eq!(read, data);
+
+    let image = fdc.disc(0).unwrap().to_bytes();
+    assert_eq!(image.len(), 40 * 2560);
+    assert_eq!(image[(3 * 10 + 4) * 256..(3 * 10 + 5) * 256], data);
+}
+
+#[test]
+fn it_refuses_to_write_to_protected_or_missing_discs() {
+    let mut fdc = fdc_with_disc(true);
+
+    let (result, _, _) = run_command(&mut fdc, 0, COMMAND_WRITE_DATA_SINGLE, &[0, 0], &[0; 128]);
+    assert_eq!(result, RESULT_WRITE_PROTECTED);
+    assert!(!fdc.disc(0).unwrap().is_dirty());
+
+    fdc.eject_disc(0);
+
+    let (result, _, _) = run_command(&mut fdc, 0, COMMAND_READ_DATA_SINGLE, &[0, 0], &[]);
+    assert_eq!(result, RESULT_NOT_READY);
+}
+
+#[test]
+fn it_formats_tracks_with_the_ids_given() {
+    let mut fdc = fdc_with_disc(false);
+
+    // track 5 as 4 sectors of 512 bytes, numbered from 1
+    let ids: Vec<u8> = (1..=4).flat_map(|sector| [5, 0, sector, 2]).collect();
+    let (result, _, cycles) = run_command(
+        &mut fdc,
+        0,
+        COMMAND_FORMAT,
+        &[5, 0x15, 0x44, 0x00, 0x10],
+        &ids,
+    );
+
+    assert_eq!(result, RESULT_OK);
+
+    let (result, read, cycles) = run_command(&mut fdc, cycles, COMMAND_READ_ID, &[5, 0, 4], &[]);
+
+    assert_eq!(result, RESULT_OK);
+
+    let mut read_ids: Vec<&[u8]> = read.chunks(4).collect();
+    read_ids.sort();
+    assert_eq!(read_ids, ids.chunks(4).collect::<Vec<_>>());
+
+    let (result, read, _) = run_command(&mut fdc, cycles, COMMAND_READ_DATA, &[5, 3, 0x41], &[]);
+
+    assert_eq!(result, RESULT_OK);
+    assert_eq!(read, [0xe5; 512]);
+
+    assert_eq!(
+        fdc.disc(0).unwrap().track(0, 5)[0].id,
+        SectorId {
+            track: 5,
+            head: 0,
+            sector: 1,
+            size_code: 2,
+        }
+    );
+}
+
+#[test]
+fn it_reads_deleted_data_stopping_unless_asked_for() {
+    let mut fdc = fdc_with_disc(false);
+
+    let (result, _, cycles) = run_command(
+        &mut fdc,
+        0,
+        COMMAND_WRITE_DELETED_DATA,
+        &[1, 2, 0x21],
+        &[0x55; 256],
+    );
+    assert_eq!(result, RESULT_OK);
+
+    let (result, read, cycles) =
+        run_command(&mut fdc, cycles, COMMAND_READ_DATA, &[1, 1, 0x23], &[]);
+
+    assert_eq!(result, RESULT_DELETED_DATA);
+    assert_eq!(read.len(), 512);
+
+    let (result, read, _) = run_command(
+        &mut fdc,
+        cycles,
+        COMMAND_READ_DATA_AND_DELETED,
+        &[1, 1, 0x23],
+        &[],
+    );
+
+    assert_eq!(result, RESULT_DELETED_DATA);
+    assert_eq!(read.len(), 768);
+}
+
+#[test]
+fn it_scans_sectors_for_a_match() {
+    let mut fdc = fdc_with_disc(false);
+
+    // sectors 1, 3, 5 and 7 of track 0, comparing 4 bytes (&FF matching anything)
+    let keys: Vec<u8> = [[0x00, 0x00, 0x00, 0x00], [0x05, 0xff, 0x05, 0x05]]
+        .iter()
+        .flat_map(|key| std::iter::repeat_n(*key, 2).flatten())
+        .collect();
+    let (result, _, cycles) =
+        run_command(&mut fdc, 0, COMMAND_SCAN_DATA, &[0, 1, 0x24, 2, 4], &keys);
+
+    assert_eq!(result, RESULT_SCAN_MET_EQUAL);
+
+    let (_, _, cycles) = run_command(
+        &mut fdc,
+        cycles,
+        COMMAND_READ_SPECIAL_REGISTER,
+        &[0x06],
+        &[],
+    );
+    assert_eq!(fdc.peek(REG_RESULT), 5);
+
+    let (result, _, _) = run_command(
+        &mut fdc,
+        cycles,
+        COMMAND_SCAN_DATA,
+        &[0, 1, 0x22, 2, 4],
+        &[0; 8],
+    );
+    assert_eq!(result, RESULT_SCAN_NOT_MET);
+}
+
+#[test]
+fn it_reports_drive_status_and_selects_sides() {
+    let mut fdc = Intel8271::default();
+
+    let (_, _, _) = run_command(&mut fdc, 0, COMMAND_READ_DRIVE_STATUS, &[], &[]);
+    assert_eq!(fdc.peek(REG_RESULT) & 0x46, 0x02);
+
+    let mut dsd = numbered_ssd(2);
+    dsd[2560..5120].fill(0xaa);
+    fdc.insert_disc(0, DiscImage::new(&dsd, true, false).unwrap());
+
+    let (_, _, _) = run_command(&mut fdc, 0, COMMAND_READ_DRIVE_STATUS, &[], &[]);
+    assert_eq!(fdc.peek(REG_RESULT) & 0x46, 0x06);
+
+    // side 1 of track 0
+    run_command(
+        &mut fdc,
+        0,
+        COMMAND_WRITE_SPECIAL_REGISTER,
+        &[SPECIAL_DRIVE_OUTPUT, DRIVE_OUTPUT_SIDE],
+        &[],
+    );
+
+    let (result, read, _) = run_command(&mut fdc, 0, COMMAND_READ_DATA_SINGLE, &[0, 0], &[]);
+
+    assert_eq!(result, RESULT_OK);
+    assert_eq!(read, [0xaa; 128]);
+}
+
+#[test]
+fn it_rejects_disc_images_of_the_wrong_size() {
+    assert!(DiscImage::new(&[], false, false).is_err());
+    assert!(DiscImage::new(&vec![0; 80 * 2560 + 1], false, false).is_err());
+    assert!(DiscImage::new(&vec![0; 80 * 2560 + 1], true, false).is_ok());
+
+    // a part sector is padded out
+    let disc = DiscImage::new(&[1; 300], false, false).unwrap();
+    assert_eq!(disc.to_bytes()[..300], [1; 300]);
+    assert_eq!(disc.to_bytes().len(), 2560);
+}
+
+#[test]
+fn it_stops_reading_when_the_disc_is_ejected_as_the_sector_comes_round() {
+    let mut fdc = fdc_with_disc(false);
+
+    fdc.write(REG_COMMAND, 0x40 | COMMAND_READ_DATA_SINGLE, 0);
+    fdc.write(REG_PARAMETER, 0, 0);
+    fdc.write(REG_PARAMETER, 5, 0);
+
+    // the head loads and the search finds the sector
+    fdc.catch_up(fdc.next_wakeup().unwrap());
+
+    fdc.eject_disc(0);
+
+    let cycles = fdc.next_wakeup().unwrap();
+    fdc.catch_up(cycles);
+
+    assert_eq!(fdc.read(REG_STATUS, cycles) & STATUS_BUSY, 0);
+    assert_eq!(fdc.read(REG_RESULT, cycles), RESULT_NOT_READY);
+}
+
+#[test]
+fn it_stops_reading_when_a_swapped_disc_lacks_the_sector() {
+    let mut fdc = fdc_with_disc(false);
+
+    fdc.write(REG_COMMAND, 0x40 | COMMAND_READ_DATA_SINGLE, 0);
+    fdc.write(REG_PARAMETER, 0, 0);
+    fdc.write(REG_PARAMETER, 5, 0);
+
+    fdc.catch_up(fdc.next_wakeup().unwrap());
+
+    let mut disc = DiscImage::new(&numbered_ssd(1), false, false).unwrap();
+    disc.format_track(
+        0,
+        0,
+        &[SectorId {
+            track: 0,
+            head: 0,
+            sector: 0,
+            size_code: 1,
+        }],
+    );
+    fdc.insert_disc(0, disc);
+
+    let cycles = fdc.next_wakeup().unwrap();
+    fdc.catch_up(cycles);
+
+    assert_eq!(fdc.read(REG_RESULT, cycles), RESULT_SECTOR_NOT_FOUND);
+}
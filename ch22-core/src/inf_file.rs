@@ -0,0 +1,71 @@
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+// .inf sidecar describing a file extracted from a disc or tape, e.g.
+// $.ELITE FFFF1900 FFFF8023 00004A00 L
+// addresses of FFFFxxxx, FFxxxx (DFS's 18 bit addresses as usually written) and plain
+// 0000xxxx are in the host, anything else is in a second processor
+#[derive(Debug, PartialEq)]
+pub struct InfFile {
+    pub name: String,
+    pub load_address: u32,
+    pub exec_address: u32,
+    pub length: Option<u32>,
+    pub locked: bool,
+}
+
+impl FromStr for InfFile {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split_whitespace();
+
+        let name = parts.next().ok_or("missing name")?.to_string();
+        let load_address = parse_address(parts.next(), "load address")?;
+        let exec_address = parse_address(parts.next(), "exec address")?;
+
+        let mut length = None;
+        let mut locked = false;
+
+        for part in parts {
+            if part.eq_ignore_ascii_case("L") || part.eq_ignore_ascii_case("Locked") {
+                locked = true;
+            } else if part.contains('=') {
+                // key=value extensions such as CRC= are ignored
+            } else if let Ok(value) = u32::from_str_radix(part, 16) {
+                match length {
+                    None => length = Some(value),
+                    Some(_) => locked = value & ACCESS_LOCKED != 0, // access byte
+                }
+            } else {
+                return Err(format!("unexpected field {part}"));
+            }
+        }
+
+        Ok(InfFile {
+            name,
+            load_address,
+            exec_address,
+            length,
+            locked,
+        })
+    }
+}
+
+// the 16 bit host address, None for a second processor address
+pub fn host_address(address: u32) -> Option<u16> {
+    match address >> 16 {
+        0x0000 | 0x00ff | 0xffff => Some(address as u16),
+        _ => None,
+    }
+}
+
+fn parse_address(part: Option<&str>, field: &str) -> Result<u32, String> {
+    let part = part.ok_or(format!("missing {field}"))?;
+
+    u32::from_str_radix(part, 16).map_err(|_| format!("invalid {field} {part}"))
+}
+
+const ACCESS_LOCKED: u32 = 0x08;
@@ -0,0 +1,62 @@
+use super::*;
+
+#[test]
+fn it_parses_a_locked_file() {
+    let inf_file: InfFile = "$.ELITE  FFFF1900 FFFF8023 00004A00 L\n".parse().unwrap();
+
+    assert_eq!(
+        inf_file,
+        InfFile {
+            name: "$.ELITE".to_string(),
+            load_address: 0xffff1900,
+            exec_address: 0xffff8023,
+            length: Some(0x4a00),
+            locked: true,
+        }
+    );
+}
+
+#[test]
+fn it_parses_optional_fields() {
+    let inf_file: InfFile = "$.BOOT 0E00 0E00".parse().unwrap();
+
+    assert_eq!(inf_file.length, None);
+    assert!(!inf_file.locked);
+
+    let inf_file: InfFile = "GAME 1900 1900 0100 08 CRC=1234".parse().unwrap();
+
+    assert_eq!(inf_file.length, Some(0x100));
+    assert!(inf_file.locked);
+}
+
+#[test]
+fn it_rejects_invalid_addresses() {
+    let error = "$.BOOT 0E00".parse::<InfFile>();
+
+    assert_eq!(error.err(), Some("missing exec address".to_string()));
+}
+
+#[test]
+fn it_takes_host_addresses_in_each_form() {
+    let inf_file: InfFile = "$.ELITE FF1900 FF8023 004A00".parse().unwrap();
+
+    assert_eq!(host_address(inf_file.load_address), Some(0x1900));
+    assert_eq!(host_address(inf_file.exec_address), Some(0x8023));
+
+    let inf_file: InfFile = "$.ELITE 001900 00008023".parse().unwrap();
+
+    assert_eq!(host_address(inf_file.load_address), Some(0x1900));
+    assert_eq!(host_address(inf_file.exec_address), Some(0x8023));
+
+    let inf_file: InfFile = "$.ELITE FFFF1900 FFFF8023".parse().unwrap();
+
+    assert_eq!(host_address(inf_file.load_address), Some(0x1900));
+    assert_eq!(host_address(inf_file.exec_address), Some(0x8023));
+}
+
+#[test]
+fn it_leaves_other_addresses_to_a_second_processor() {
+    assert_eq!(host_address(0x00fe1900), None);
+    assert_eq!(host_address(0x00031900), None);
+    assert_eq!(host_address(0xfffe1900), None);
+}
@@ -0,0 +1,8 @@
+mod host_keyboard;
+mod keyboard_matrix;
+
+#[cfg(test)]
+mod tests;
+
+pub use host_keyboard::{HostKeyboard, HostLayout, KeyMapping};
+pub use keyboard_matrix::{BbcKey, KeyboardMatrix};
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use super::keyboard_matrix::{BbcKey, KeyboardMatrix};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HostLayout {
+    Uk,
+    Us,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyMapping {
+    // host keys press the BBC key in the same place
+    Physical,
+    // host characters press the BBC key producing them, with SHIFT as needed
+    Logical,
+}
+
+// host keys (by DOM code and key values) pressing BBC keys
+// each held host key remembers what it pressed, so releasing it after the
+// host's shift state has changed releases the same BBC key
+pub struct HostKeyboard {
+    layout: HostLayout,
+    mapping: KeyMapping,
+    held: HashMap<String, HeldKey>,
+    // the shift state needed by the latest logical key, while held
+    shift_override: Option<(String, bool)>,
+}
+
+#[derive(Clone, Copy)]
+struct HeldKey {
+    key: BbcKey,
+}
+
+impl Default for HostKeyboard {
+    fn default() -> Self {
+        HostKeyboard {
+            layout: HostLayout::Uk,
+            mapping: KeyMapping::Physical,
+            held: HashMap::new(),
+            shift_override: None,
+        }
+    }
+}
+
+impl HostKeyboard {
+    pub fn set_layout(&mut self, layout: HostLayout, mapping: KeyMapping) {
+        self.layout = layout;
+        self.mapping = mapping;
+    }
+
+    // false if the host key doesn't map to a BBC key
+    pub fn key_down(&mut self, matrix: &mut KeyboardMatrix, code: &str, key: &str) -> bool {
+        let logical = match self.mapping {
+            KeyMapping::Logical => logical_key(key),
+            KeyMapping::Physical => None,
+        };
+
+        let Some((bbc_key, shift)) =
+            logical.or_else(|| physical_key(self.layout, code).map(|key| (key, None)))
+        else {
+            return false;
+        };
+
+        if let Some(shift) = shift {
+            self.shift_override = Some((code.to_string(), shift));
+        }
+
+        self.held.insert(code.to_string(), HeldKey { key: bbc_key });
+
+        self.update_matrix(matrix);
+
+        true
+    }
+
+    pub fn key_up(&mut self, matrix: &mut KeyboardMatrix, code: &str) -> bool {
+        let Some(held_key) = self.held.remove(code) else {
+            return false;
+        };
+
+        if self
+            .shift_override
+            .as_ref()
+            .is_some_and(|(override_code, _)| override_code == code)
+        {
+            self.shift_override = None;
+        }
+
+        // another held host key may press the same BBC key
+        if !self.held.values().any(|held| held.key == held_key.key) {
+            matrix.set_key(held_key.key, false);
+        }
+
+        self.update_matrix(matrix);
+
+        true
+    }
+
+    // e.g. when the host window loses focus and key ups would be missed
+    pub fn release_all(&mut self, matrix: &mut KeyboardMatrix) {
+        self.held.clear();
+        self.shift_override = None;
+
+        matrix.release_all();
+    }
+
+    fn update_matrix(&self, matrix: &mut KeyboardMatrix) {
+        let mut shift = false;
+
+        for held_key in self.held.values() {
+            if held_key.key == BbcKey::SHIFT {
+                shift = true;
+            } else {
+                matrix.set_key(held_key.key, true);
+            }
+        }
+
+        if let Some((_, shift_override)) = self.shift_override {
+            shift = shift_override;
+        }
+
+        matrix.set_key(BbcKey::SHIFT, shift);
+    }
+}
+
+fn physical_key(layout: HostLayout, code: &str) -> Option<BbcKey> {
+    let layout_keys = match layout {
+        HostLayout::Uk => UK_PHYSICAL_KEYS,
+        HostLayout::Us => US_PHYSICAL_KEYS,
+    };
+
+    PHYSICAL_KEYS
+        .iter()
+        .chain(layout_keys)
+        .find(|(host_code, _)| *host_code == code)
+        .map(|(_, key_number)| BbcKey::from_internal(*key_number))
+}
+
+// characters produced by the BBC's unshifted and shifted keys,
+// letters follow the host's shift (and the BBC's caps lock)
+fn logical_key(key: &str) -> Option<(BbcKey, Option<bool>)> {
+    let mut chars = key.chars();
+    let (Some(char), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+
+    if char.is_ascii_alphabetic() {
+        let code = format!("Key{}", char.to_ascii_uppercase());
+
+        return physical_key(HostLayout::Uk, &code).map(|key| (key, None));
+    }
+
+    LOGICAL_KEYS
+        .iter()
+        .find_map(|(key_number, unshifted, shifted)| {
+            if char == *unshifted {
+                Some((BbcKey::from_internal(*key_number), Some(false)))
+            } else if Some(char) == *shifted {
+                Some((BbcKey::from_internal(*key_number), Some(true)))
+            } else {
+                None
+            }
+        })
+}
+
+// DOM codes to internal key numbers
+const PHYSICAL_KEYS: &[(&str, u8)] = &[
+    ("Escape", 0x70),
+    ("F10", 0x20),
+    ("F1", 0x71),
+    ("F2", 0x72),
+    ("F3", 0x73),
+    ("F4", 0x14),
+    ("F5", 0x74),
+    ("F6", 0x75),
+    ("F7", 0x16),
+    ("F8", 0x76),
+    ("F9", 0x77),
+    ("Digit1", 0x30),
+    ("Digit2", 0x31),
+    ("Digit3", 0x11),
+    ("Digit4", 0x12),
+    ("Digit5", 0x13),
+    ("Digit6", 0x34),
+    ("Digit7", 0x24),
+    ("Digit8", 0x15),
+    ("Digit9", 0x26),
+    ("Digit0", 0x27),
+    ("Minus", 0x17),
+    ("Equal", 0x18),
+    ("ArrowLeft", 0x19),
+    ("ArrowRight", 0x79),
+    ("Tab", 0x60),
+    ("KeyQ", 0x10),
+    ("KeyW", 0x21),
+    ("KeyE", 0x22),
+    ("KeyR", 0x33),
+    ("KeyT", 0x23),
+    ("KeyY", 0x44),
+    ("KeyU", 0x35),
+    ("KeyI", 0x25),
+    ("KeyO", 0x36),
+    ("KeyP", 0x37),
+    ("BracketLeft", 0x47),
+    ("BracketRight", 0x38),
+    ("ArrowUp", 0x39),
+    ("ArrowDown", 0x29),
+    ("CapsLock", 0x40),
+    ("ControlLeft", 0x01),
+    ("ControlRight", 0x01),
+    ("KeyA", 0x41),
+    ("KeyS", 0x51),
+    ("KeyD", 0x32),
+    ("KeyF", 0x43),
+    ("KeyG", 0x53),
+    ("KeyH", 0x54),
+    ("KeyJ", 0x45),
+    ("KeyK", 0x46),
+    ("KeyL", 0x56),
+    ("Semicolon", 0x57),
+    ("Quote", 0x48),
+    ("Enter", 0x49),
+    ("PageUp", 0x50),
+    ("ShiftLeft", 0x00),
+    ("ShiftRight", 0x00),
+    ("KeyZ", 0x61),
+    ("KeyX", 0x42),
+    ("KeyC", 0x52),
+    ("KeyV", 0x63),
+    ("KeyB", 0x64),
+    ("KeyN", 0x55),
+    ("KeyM", 0x65),
+    ("Comma", 0x66),
+    ("Period", 0x67),
+    ("Slash", 0x68),
+    ("Backspace", 0x59),
+    ("End", 0x69),
+    ("Space", 0x62),
+    ("Backquote", 0x28),
+];
+
+// the key beside return on ISO keyboards, with an extra key by left shift
+const UK_PHYSICAL_KEYS: &[(&str, u8)] = &[("Backslash", 0x58), ("IntlBackslash", 0x78)];
+
+// the key above return, with ] moved to delete
+const US_PHYSICAL_KEYS: &[(&str, u8)] = &[("Backslash", 0x78), ("Delete", 0x58)];
+
+// internal key numbers with their unshifted and shifted characters
+const LOGICAL_KEYS: &[(u8, char, Option<char>)] = &[
+    (0x30, '1', Some('!')),
+    (0x31, '2', Some('"')),
+    (0x11, '3', Some('#')),
+    (0x12, '4', Some('$')),
+    (0x13, '5', Some('%')),
+    (0x34, '6', Some('&')),
+    (0x24, '7', Some('\'')),
+    (0x15, '8', Some('(')),
+    (0x26, '9', Some(')')),
+    (0x27, '0', None),
+    (0x17, '-', Some('=')),
+    (0x18, '^', Some('~')),
+    (0x78, '\\', Some('|')),
+    (0x47, '@', None),
+    (0x38, '[', Some('{')),
+    (0x28, '_', Some('£')),
+    (0x57, ';', Some('+')),
+    (0x48, ':', Some('*')),
+    (0x58, ']', Some('}')),
+    (0x66, ',', Some('<')),
+    (0x67, '.', Some('>')),
+    (0x68, '/', Some('?')),
+    (0x62, ' ', None),
+];
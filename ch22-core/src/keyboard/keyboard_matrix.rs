@@ -0,0 +1,80 @@
+// a key by its row (0-7) and column (0-9), as in the os's internal key numbers
+// (&row column), e.g. A is &41
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BbcKey {
+    pub row: u8,
+    pub column: u8,
+}
+
+impl BbcKey {
+    pub const SHIFT: BbcKey = BbcKey::from_internal(0x00);
+
+    pub const fn from_internal(key_number: u8) -> Self {
+        BbcKey {
+            row: key_number >> 4,
+            column: key_number & 0x0f,
+        }
+    }
+}
+
+// 10 columns of 8 rows, row 0 holding SHIFT, CTRL and the startup option links
+// which don't raise the key pressed interrupt
+#[derive(Default)]
+pub struct KeyboardMatrix {
+    columns: [u8; KEYBOARD_COLUMNS],
+    links: u8,
+}
+
+impl KeyboardMatrix {
+    pub fn set_key(&mut self, key: BbcKey, pressed: bool) {
+        let BbcKey { row, column } = key;
+
+        if row > 7 || column as usize >= KEYBOARD_COLUMNS {
+            panic!("Invalid key: row {row} column {column}");
+        }
+
+        let bit = 1 << row;
+        let rows = &mut self.columns[column as usize];
+
+        *rows = if pressed { *rows | bit } else { *rows & !bit };
+    }
+
+    pub fn release_all(&mut self) {
+        self.columns = Default::default();
+    }
+
+    // link n (bit n) is read as key &0(9 - n), a fitted link reading as pressed
+    // with none fitted the os starts in mode 7
+    pub fn set_links(&mut self, links: u8) {
+        self.links = links;
+    }
+
+    pub fn is_pressed(&self, key: BbcKey) -> bool {
+        self.column_rows(key.column) & (1 << key.row) != 0
+    }
+
+    // whether a key other than in row 0 is pressed in the column
+    pub fn is_column_pressed(&self, column: u8) -> bool {
+        self.column_rows(column) & 0xfe != 0
+    }
+
+    pub fn is_any_pressed(&self) -> bool {
+        self.columns.iter().any(|rows| rows & 0xfe != 0)
+    }
+
+    // the column decoder only has 10 outputs
+    fn column_rows(&self, column: u8) -> u8 {
+        let Some(rows) = self.columns.get(column as usize) else {
+            return 0;
+        };
+
+        let link = match column {
+            2..=9 => (self.links >> (9 - column)) & 1,
+            _ => 0,
+        };
+
+        rows | link
+    }
+}
+
+const KEYBOARD_COLUMNS: usize = 10;
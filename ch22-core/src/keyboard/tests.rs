@@ -0,0 +1,137 @@
+use super::*;
+
+const A: BbcKey = BbcKey::from_internal(0x41);
+const DIGIT_2: BbcKey = BbcKey::from_internal(0x31);
+const COLON: BbcKey = BbcKey::from_internal(0x48);
+
+#[test]
+fn it_only_raises_key_presses_outside_row_0() {
+    let mut matrix = KeyboardMatrix::default();
+
+    matrix.set_key(BbcKey::SHIFT, true);
+
+    assert!(matrix.is_pressed(BbcKey::SHIFT));
+    assert!(!matrix.is_column_pressed(0));
+    assert!(!matrix.is_any_pressed());
+
+    matrix.set_key(A, true);
+
+    assert!(matrix.is_column_pressed(1));
+    assert!(!matrix.is_column_pressed(2));
+    assert!(matrix.is_any_pressed());
+
+    matrix.set_key(A, false);
+
+    assert!(!matrix.is_pressed(A));
+    assert!(!matrix.is_any_pressed());
+}
+
+#[test]
+fn it_reads_fitted_startup_links_as_row_0_keys() {
+    let mut matrix = KeyboardMatrix::default();
+
+    matrix.set_links(0b1000_0011);
+
+    let links: Vec<u8> = (2..=9)
+        .filter(|column| {
+            matrix.is_pressed(BbcKey {
+                row: 0,
+                column: *column,
+            })
+        })
+        .collect();
+
+    assert_eq!(links, [2, 8, 9]);
+    assert!(!matrix.is_any_pressed());
+    assert!(!matrix.is_pressed(BbcKey { row: 0, column: 12 }));
+}
+
+#[test]
+#[should_panic(expected = "Invalid key")]
+fn it_rejects_keys_outside_the_matrix() {
+    KeyboardMatrix::default().set_key(BbcKey { row: 0, column: 10 }, true);
+}
+
+#[test]
+fn it_maps_host_keys_by_position() {
+    let mut matrix = KeyboardMatrix::default();
+    let mut host_keyboard = HostKeyboard::default();
+
+    assert!(host_keyboard.key_down(&mut matrix, "ShiftLeft", "Shift"));
+    assert!(host_keyboard.key_down(&mut matrix, "Digit2", "\""));
+
+    assert!(matrix.is_pressed(BbcKey::SHIFT));
+    assert!(matrix.is_pressed(DIGIT_2));
+
+    assert!(host_keyboard.key_up(&mut matrix, "ShiftLeft"));
+    assert!(host_keyboard.key_up(&mut matrix, "Digit2"));
+
+    assert!(!matrix.is_pressed(BbcKey::SHIFT));
+    assert!(!matrix.is_pressed(DIGIT_2));
+    assert!(!host_keyboard.key_down(&mut matrix, "AudioVolumeUp", "AudioVolumeUp"));
+}
+
+#[test]
+fn it_places_the_backslash_key_by_layout() {
+    let mut matrix = KeyboardMatrix::default();
+    let mut host_keyboard = HostKeyboard::default();
+
+    host_keyboard.key_down(&mut matrix, "Backslash", "#");
+    assert!(matrix.is_pressed(BbcKey::from_internal(0x58)));
+    host_keyboard.key_up(&mut matrix, "Backslash");
+
+    host_keyboard.set_layout(HostLayout::Us, KeyMapping::Physical);
+
+    host_keyboard.key_down(&mut matrix, "Backslash", "\\");
+    assert!(matrix.is_pressed(BbcKey::from_internal(0x78)));
+}
+
+#[test]
+fn it_maps_host_characters_shifting_as_the_bbc_needs() {
+    let mut matrix = KeyboardMatrix::default();
+    let mut host_keyboard = HostKeyboard::default();
+
+    host_keyboard.set_layout(HostLayout::Us, KeyMapping::Logical);
+
+    // US shift + ' is ", shift + 2 on the BBC
+    host_keyboard.key_down(&mut matrix, "ShiftLeft", "Shift");
+    host_keyboard.key_down(&mut matrix, "Quote", "\"");
+
+    assert!(matrix.is_pressed(DIGIT_2));
+    assert!(matrix.is_pressed(BbcKey::SHIFT));
+
+    host_keyboard.key_up(&mut matrix, "Quote");
+
+    // US shift + ; is :, unshifted on the BBC
+    host_keyboard.key_down(&mut matrix, "Semicolon", ":");
+
+    assert!(!matrix.is_pressed(DIGIT_2));
+    assert!(matrix.is_pressed(COLON));
+    assert!(!matrix.is_pressed(BbcKey::SHIFT));
+
+    // released by code, after the host shift went up first
+    host_keyboard.key_up(&mut matrix, "ShiftLeft");
+    host_keyboard.key_up(&mut matrix, "Semicolon");
+
+    assert!(!matrix.is_pressed(COLON));
+    assert!(!matrix.is_pressed(BbcKey::SHIFT));
+}
+
+#[test]
+fn it_passes_the_host_shift_through_for_letters() {
+    let mut matrix = KeyboardMatrix::default();
+    let mut host_keyboard = HostKeyboard::default();
+
+    host_keyboard.set_layout(HostLayout::Uk, KeyMapping::Logical);
+
+    host_keyboard.key_down(&mut matrix, "ShiftRight", "Shift");
+    host_keyboard.key_down(&mut matrix, "KeyA", "A");
+
+    assert!(matrix.is_pressed(A));
+    assert!(matrix.is_pressed(BbcKey::SHIFT));
+
+    host_keyboard.release_all(&mut matrix);
+
+    assert!(!matrix.is_pressed(A));
+    assert!(!matrix.is_pressed(BbcKey::SHIFT));
+}
@@ -1,7 +1,35 @@
 mod address_spaces;
+mod cheats;
 pub mod cpu;
 mod devices;
+mod fdc;
+mod inf_file;
+mod keyboard;
+mod paste;
+mod shared;
+mod sound;
 mod system;
+mod tube;
+mod user_port;
 mod utils;
+mod via;
 mod video;
 mod word;
+
+pub use address_spaces::{
+    FloatingBus, IOAccess, IOAccessFilter, IOAccessKind, IOSpace, PowerOnRam, Ram, Rom,
+    UnmappedAccess,
+};
+pub use cheats::{Cheat, CheatList, CheatSearch, Comparison, FreezeMode, ValueType};
+pub use devices::{
+    DeviceDescription, DeviceHandle, DeviceSpeed, IOAddressRange, IODevice, IODeviceID, ResetType,
+    TimerDevice, TimerDeviceID,
+};
+pub use inf_file::InfFile;
+pub use keyboard::{BbcKey, HostLayout, KeyMapping};
+pub use system::{
+    AddressMap, Clock, Core, FnAddressMap, MachineBuilder, MemorySpace, ModelBAddressMap,
+};
+pub use user_port::{UserPortDevice, UserPortUpdate};
+pub use via::{NoPeripheral, Via, Via6522, ViaPeripheral};
+pub use word::Word;
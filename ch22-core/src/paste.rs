@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+use crate::address_spaces::Ram;
+use crate::word::Word;
+
+#[cfg(test)]
+mod tests;
+
+// text typed by inserting it straight into the os keyboard buffer, as fast as it drains
+// the buffer is 32 bytes at &03E0-&03FF on every os, found through its pointers
+// (the last byte removed and the last inserted) so nothing depends on the os version
+#[derive(Default)]
+pub struct Paste {
+    pending: VecDeque<u8>,
+}
+
+impl Paste {
+    // appends to anything still being pasted
+    pub fn start(&mut self, text: &str) {
+        self.pending.extend(translate(text));
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    // fills what the os has read since the last field, between instructions
+    // waiting until the os has initialised the buffer
+    pub fn on_field_complete(&mut self, ram: &mut Ram) {
+        let removed = ram.read(Word::from(KEYBOARD_BUFFER_OUT));
+        let mut inserted = ram.read(Word::from(KEYBOARD_BUFFER_IN));
+
+        if removed < KEYBOARD_BUFFER_START || inserted < KEYBOARD_BUFFER_START {
+            return;
+        }
+
+        while let Some(&value) = self.pending.front() {
+            let next = next_pointer(inserted);
+
+            if next == removed {
+                break;
+            }
+
+            ram.write(Word::from(KEYBOARD_BUFFER_PAGE | next as u16), value);
+            inserted = next;
+
+            self.pending.pop_front();
+        }
+
+        ram.write(Word::from(KEYBOARD_BUFFER_IN), inserted);
+    }
+}
+
+// the buffer pointers count up through &E0-&FF, then wrap
+fn next_pointer(pointer: u8) -> u8 {
+    pointer.checked_add(1).unwrap_or(KEYBOARD_BUFFER_START)
+}
+
+// host newlines in any style become RETURN, the BBC's £ is &60,
+// anything else which can't be typed is dropped
+fn translate(text: &str) -> Vec<u8> {
+    let text = text.replace("\r\n", "\n");
+
+    text.chars()
+        .filter_map(|char| match char {
+            '\n' | '\r' => Some(0x0d),
+            '\t' => Some(b' '),
+            '£' => Some(0x60),
+            '‘' | '’' => Some(b'\''),
+            '“' | '”' => Some(b'"'),
+            ' '..='~' => Some(char as u8),
+            _ => None,
+        })
+        .collect()
+}
+
+const KEYBOARD_BUFFER_OUT: u16 = 0x02d8;
+const KEYBOARD_BUFFER_IN: u16 = 0x02e1;
+const KEYBOARD_BUFFER_PAGE: u16 = 0x0300;
+const KEYBOARD_BUFFER_START: u8 = 0xe0;
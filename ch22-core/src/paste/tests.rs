@@ -0,0 +1,93 @@
+use super::*;
+
+// the os's REMV, None if the buffer is empty
+fn remove_key(ram: &mut Ram) -> Option<u8> {
+    let removed = ram.read(Word::from(KEYBOARD_BUFFER_OUT));
+
+    if removed == ram.read(Word::from(KEYBOARD_BUFFER_IN)) {
+        return None;
+    }
+
+    let next = next_pointer(removed);
+    ram.write(Word::from(KEYBOARD_BUFFER_OUT), next);
+
+    Some(ram.read(Word::from(KEYBOARD_BUFFER_PAGE | next as u16)))
+}
+
+fn empty_buffer_ram() -> Ram {
+    let mut ram = Ram::default();
+
+    ram.write(Word::from(KEYBOARD_BUFFER_OUT), 0xf0);
+    ram.write(Word::from(KEYBOARD_BUFFER_IN), 0xf0);
+
+    ram
+}
+
+#[test]
+fn it_inserts_text_after_the_last_key() {
+    let mut ram = empty_buffer_ram();
+    let mut paste = Paste::default();
+
+    paste.start("RUN\n");
+    paste.on_field_complete(&mut ram);
+
+    assert!(!paste.is_active());
+
+    let keys: Vec<u8> = std::iter::from_fn(|| remove_key(&mut ram)).collect();
+
+    assert_eq!(keys, b"RUN\r");
+}
+
+#[test]
+fn it_paces_text_as_the_buffer_drains() {
+    let mut ram = empty_buffer_ram();
+    let mut paste = Paste::default();
+    let text = "0123456789".repeat(5);
+
+    paste.start(&text);
+    paste.on_field_complete(&mut ram);
+
+    // one slot stays free to tell a full buffer from an empty one
+    let first_field: Vec<u8> = std::iter::from_fn(|| remove_key(&mut ram)).collect();
+    assert_eq!(first_field.len(), 31);
+    assert!(paste.is_active());
+
+    paste.on_field_complete(&mut ram);
+
+    let second_field: Vec<u8> = std::iter::from_fn(|| remove_key(&mut ram)).collect();
+    assert_eq!([first_field, second_field].concat(), text.as_bytes());
+    assert!(!paste.is_active());
+}
+
+#[test]
+fn it_translates_host_text_to_bbc_codes() {
+    assert_eq!(
+        translate("10 PRINT “£”\r\n20\tEND\r\u{1F600}"),
+        b"10 PRINT \"`\"\r20 END\r"
+    );
+}
+
+#[test]
+fn it_cancels_a_paste() {
+    let mut ram = empty_buffer_ram();
+    let mut paste = Paste::default();
+
+    paste.start("CHAIN\"GAME\"\n");
+    paste.cancel();
+    paste.on_field_complete(&mut ram);
+
+    assert!(!paste.is_active());
+    assert_eq!(remove_key(&mut ram), None);
+}
+
+#[test]
+fn it_waits_for_the_os_to_set_up_the_buffer() {
+    let mut ram = Ram::default();
+    let mut paste = Paste::default();
+
+    paste.start("A");
+    paste.on_field_complete(&mut ram);
+
+    assert!(paste.is_active());
+    assert_eq!(ram.read(Word::from(0x0301)), 0);
+}
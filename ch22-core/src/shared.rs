@@ -0,0 +1,105 @@
+// state shared between the core and its devices
+// wasm is single threaded so Rc based types are used there,
+// natively Arc based types are used so that a Core is Send
+
+pub use imp::{Latch, MaybeSend, Shared};
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    use std::cell::{Cell, Ref, RefCell, RefMut};
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    pub struct Shared<T>(Rc<RefCell<T>>);
+
+    impl<T> Shared<T> {
+        pub fn new(value: T) -> Self {
+            Shared(Rc::new(RefCell::new(value)))
+        }
+
+        pub fn borrow(&self) -> Ref<'_, T> {
+            self.0.borrow()
+        }
+
+        pub fn borrow_mut(&self) -> RefMut<'_, T> {
+            self.0.borrow_mut()
+        }
+    }
+
+    impl<T> Clone for Shared<T> {
+        fn clone(&self) -> Self {
+            Shared(self.0.clone())
+        }
+    }
+
+    #[derive(Default, Clone)]
+    pub struct Latch(Rc<Cell<u8>>);
+
+    impl Latch {
+        pub fn get(&self) -> u8 {
+            self.0.get()
+        }
+
+        pub fn set(&self, value: u8) {
+            self.0.set(value);
+        }
+    }
+
+    // JS devices are not Send
+    pub trait MaybeSend {}
+
+    impl<T: ?Sized> MaybeSend for T {}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    #[derive(Default)]
+    pub struct Shared<T>(Arc<RwLock<T>>);
+
+    impl<T> Shared<T> {
+        pub fn new(value: T) -> Self {
+            Shared(Arc::new(RwLock::new(value)))
+        }
+
+        // a core is only driven from one thread at a time so the lock is never
+        // contended, overlapping borrows follow RefCell's rules: any number of shared
+        // borrows or a single mutable one, anything else is a bug
+        pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+            self.0.try_read().expect("already mutably borrowed")
+        }
+
+        pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.try_write().expect("already borrowed")
+        }
+    }
+
+    impl<T> Clone for Shared<T> {
+        fn clone(&self) -> Self {
+            Shared(self.0.clone())
+        }
+    }
+
+    #[derive(Default, Clone)]
+    pub struct Latch(Arc<AtomicU8>);
+
+    impl Latch {
+        pub fn get(&self) -> u8 {
+            self.0.load(Ordering::Relaxed)
+        }
+
+        pub fn set(&self, value: u8) {
+            self.0.store(value, Ordering::Relaxed);
+        }
+    }
+
+    // Sync too, as devices and their peripherals are held behind Shared's RwLock
+    pub trait MaybeSend: Send + Sync {}
+
+    impl<T: ?Sized + Send + Sync> MaybeSend for T {}
+}
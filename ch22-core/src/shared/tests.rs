@@ -0,0 +1,21 @@
+use super::*;
+
+#[test]
+fn it_allows_overlapping_shared_borrows() {
+    let shared = Shared::new(1);
+    let other = shared.clone();
+
+    let first = shared.borrow();
+    let second = other.borrow();
+
+    assert_eq!(*first + *second, 2);
+}
+
+#[test]
+#[should_panic(expected = "already borrowed")]
+fn it_rejects_a_mutable_borrow_overlapping_a_shared_one() {
+    let shared = Shared::new(1);
+
+    let _first = shared.borrow();
+    let _second = shared.borrow_mut();
+}
@@ -0,0 +1,14 @@
+mod sn76489;
+mod vgm_recorder;
+
+#[cfg(test)]
+mod tests;
+
+pub use sn76489::Sn76489;
+
+// the 4MHz sound clock divided by 16 steps the chip's counters at 250kHz (every 8 cycles),
+// averaged 8 steps to a sample
+pub const SOUND_CLOCK: u32 = 4_000_000;
+pub const CYCLES_PER_SOUND_STEP: u64 = 8;
+pub const SOUND_STEPS_PER_SAMPLE: u32 = 8;
+pub const SOUND_SAMPLE_RATE: u32 = 31250;
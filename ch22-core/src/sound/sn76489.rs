@@ -0,0 +1,253 @@
+use super::vgm_recorder::VgmRecorder;
+use super::{CYCLES_PER_SOUND_STEP, SOUND_SAMPLE_RATE, SOUND_STEPS_PER_SAMPLE};
+
+// three square wave tone channels and a noise channel, each with a 4 bit attenuation
+// the chip is stepped lazily, catching up before each write and when a field completes
+pub struct Sn76489 {
+    // 10 bit tone periods for channels 0-2, then the noise control for channel 3
+    periods: [u16; 4],
+    attenuations: [u8; 4],
+    counters: [u16; 4],
+    outputs: [bool; 4],
+    // the register a data byte (bit 7 clear) goes to, channel * 2 + 1 for attenuation
+    latched_register: u8,
+    lfsr: u16,
+    steps: u64,
+    sample_sum: f32,
+    sample_steps: u32,
+    samples: Vec<f32>,
+    field_samples: Vec<f32>,
+    recorder: Option<VgmRecorder>,
+}
+
+impl Default for Sn76489 {
+    fn default() -> Self {
+        Sn76489 {
+            periods: [0; 4],
+            attenuations: [0x0f; 4],
+            counters: [0; 4],
+            outputs: [false; 4],
+            latched_register: 0,
+            lfsr: LFSR_RESET,
+            steps: 0,
+            sample_sum: 0.0,
+            sample_steps: 0,
+            samples: Vec::new(),
+            field_samples: Vec::new(),
+            recorder: None,
+        }
+    }
+}
+
+impl Sn76489 {
+    // a byte from the slow data bus, taken as IC32's sound write enable goes low
+    pub fn write(&mut self, value: u8, cycles: u64) {
+        self.catch_up(cycles);
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.write(value, cycles);
+        }
+
+        if value & 0x80 != 0 {
+            self.latched_register = value >> 4 & 0x07;
+        }
+
+        let channel = (self.latched_register >> 1) as usize;
+
+        if self.latched_register & 0x01 != 0 {
+            self.attenuations[channel] = value & 0x0f;
+        } else if channel == NOISE_CHANNEL {
+            self.periods[NOISE_CHANNEL] = (value & 0x07) as u16;
+            self.lfsr = LFSR_RESET;
+        } else if value & 0x80 != 0 {
+            self.periods[channel] = self.periods[channel] & 0x3f0 | (value & 0x0f) as u16;
+        } else {
+            self.periods[channel] = self.periods[channel] & 0x00f | ((value & 0x3f) as u16) << 4;
+        }
+    }
+
+    pub fn catch_up(&mut self, cycles: u64) {
+        let steps = cycles / CYCLES_PER_SOUND_STEP;
+
+        while self.steps < steps {
+            self.step();
+            self.steps += 1;
+        }
+    }
+
+    // makes the samples up to cycles available as the last field's
+    pub fn on_field_complete(&mut self, cycles: u64) {
+        self.catch_up(cycles);
+
+        std::mem::swap(&mut self.samples, &mut self.field_samples);
+        self.samples.clear();
+    }
+
+    // mono samples at SOUND_SAMPLE_RATE, -1 to 1
+    pub fn field_samples(&self) -> &[f32] {
+        &self.field_samples
+    }
+
+    // restarts any recording in progress
+    pub fn start_recording(&mut self, cycles: u64) {
+        self.recorder = Some(VgmRecorder::new(&self.state_writes(), cycles));
+    }
+
+    // false if not recording
+    pub fn mark_recording_loop(&mut self, cycles: u64) -> bool {
+        let Some(recorder) = &mut self.recorder else {
+            return false;
+        };
+
+        recorder.mark_loop(cycles);
+
+        true
+    }
+
+    // the recording as a VGM file, None if not recording
+    pub fn stop_recording(&mut self, cycles: u64, title: &str, author: &str) -> Option<Vec<u8>> {
+        Some(self.recorder.take()?.finish(cycles, title, author))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    // writes setting every register, the latched one last so data bytes still reach it
+    fn state_writes(&self) -> Vec<u8> {
+        let registers = (0..8)
+            .filter(|&register| register != self.latched_register)
+            .chain([self.latched_register]);
+
+        let mut writes = Vec::new();
+
+        for register in registers {
+            let channel = (register >> 1) as usize;
+            let is_tone = register & 0x01 == 0 && channel != NOISE_CHANNEL;
+            let value = if register & 0x01 != 0 {
+                self.attenuations[channel] as u16
+            } else {
+                self.periods[channel]
+            };
+
+            writes.push(0x80 | register << 4 | (value & 0x0f) as u8);
+
+            if is_tone {
+                writes.push((value >> 4) as u8);
+            }
+        }
+
+        writes
+    }
+
+    fn step(&mut self) {
+        let noise_rate = self.periods[NOISE_CHANNEL] & 0x03;
+
+        for channel in 0..NOISE_CHANNEL {
+            let period = self.periods[channel];
+
+            // too fast to hear, the bbc plays samples by holding channels high
+            if period == 1 {
+                self.outputs[channel] = true;
+                continue;
+            }
+
+            if self.counters[channel] <= 1 {
+                // 0 counts down through the whole 10 bits
+                self.counters[channel] = if period == 0 { 0x400 } else { period };
+                self.outputs[channel] = !self.outputs[channel];
+
+                if channel == 2 && noise_rate == NOISE_RATE_TONE_2 {
+                    self.clock_noise();
+                }
+            } else {
+                self.counters[channel] -= 1;
+            }
+        }
+
+        if noise_rate != NOISE_RATE_TONE_2 {
+            if self.counters[NOISE_CHANNEL] <= 1 {
+                self.counters[NOISE_CHANNEL] = 0x10 << noise_rate;
+                self.clock_noise();
+            } else {
+                self.counters[NOISE_CHANNEL] -= 1;
+            }
+        }
+
+        self.mix();
+    }
+
+    // the noise generator's flip flop shifts the lfsr on every other clock
+    fn clock_noise(&mut self) {
+        self.outputs[NOISE_CHANNEL] = !self.outputs[NOISE_CHANNEL];
+
+        if !self.outputs[NOISE_CHANNEL] {
+            return;
+        }
+
+        let is_white = self.periods[NOISE_CHANNEL] & 0x04 != 0;
+        let feedback = if is_white {
+            ((self.lfsr & LFSR_WHITE_NOISE_TAPS).count_ones() & 1) as u16
+        } else {
+            self.lfsr & 0x01
+        };
+
+        self.lfsr = self.lfsr >> 1 | feedback << (LFSR_WIDTH - 1);
+    }
+
+    fn mix(&mut self) {
+        let levels = [
+            self.outputs[0],
+            self.outputs[1],
+            self.outputs[2],
+            self.lfsr & 0x01 != 0,
+        ];
+
+        for (channel, is_high) in levels.into_iter().enumerate() {
+            let volume = VOLUMES[self.attenuations[channel] as usize];
+
+            self.sample_sum += if is_high { volume } else { -volume };
+        }
+
+        self.sample_steps += 1;
+
+        if self.sample_steps == SOUND_STEPS_PER_SAMPLE {
+            // without fields completing to take them, keep only the first second of samples
+            if self.samples.len() < SOUND_SAMPLE_RATE as usize {
+                self.samples
+                    .push(self.sample_sum / (SOUND_STEPS_PER_SAMPLE * 4) as f32);
+            }
+
+            self.sample_sum = 0.0;
+            self.sample_steps = 0;
+        }
+    }
+}
+
+const NOISE_CHANNEL: usize = 3;
+const NOISE_RATE_TONE_2: u16 = 0x03;
+
+// the bbc's 15 bit noise shift register, white noise fed back from bits 0 and 1
+pub const LFSR_WIDTH: u16 = 15;
+pub const LFSR_WHITE_NOISE_TAPS: u16 = 0x0003;
+const LFSR_RESET: u16 = 1 << (LFSR_WIDTH - 1);
+
+// 2dB a step of attenuation, with 15 off
+const VOLUMES: [f32; 16] = [
+    1.0,
+    0.794_328_2,
+    0.630_957_3,
+    0.501_187_2,
+    0.398_107_2,
+    0.316_227_8,
+    0.251_188_6,
+    0.199_526_2,
+    0.158_489_3,
+    0.125_892_5,
+    0.1,
+    0.079_432_82,
+    0.063_095_73,
+    0.050_118_72,
+    0.039_810_72,
+    0.0,
+];
@@ -0,0 +1,223 @@
+use super::*;
+
+// the samples from the last field up to cycles
+fn samples_until(chip: &mut Sn76489, cycles: u64) -> Vec<f32> {
+    chip.on_field_complete(cycles);
+
+    chip.field_samples().to_vec()
+}
+
+// lengths of the runs of equal samples, dropping the first (part) run
+fn run_lengths(samples: &[f32]) -> Vec<usize> {
+    samples
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len())
+        .skip(1)
+        .collect()
+}
+
+#[test]
+fn it_plays_tones_at_the_written_period() {
+    let mut chip = Sn76489::default();
+
+    // channel 0, period &040 at full volume
+    chip.write(0x80, 0);
+    chip.write(0x04, 0);
+    chip.write(0x90, 0);
+
+    let samples = samples_until(&mut chip, 8 * 8 * 100);
+
+    assert_eq!(samples.len(), 100);
+    assert!(samples.iter().all(|&sample| sample.abs() == 0.25));
+
+    // 64 steps high then 64 low
+    let runs = run_lengths(&samples);
+    assert!(runs[..runs.len() - 1].iter().all(|&len| len == 8));
+}
+
+#[test]
+fn it_updates_the_latched_register_with_data_bytes() {
+    let mut chip = Sn76489::default();
+
+    // channel 1 volume latched, then a data byte for attenuation 10
+    chip.write(0xb0, 0);
+    chip.write(0x0a, 0);
+
+    let samples = samples_until(&mut chip, 8 * 8);
+
+    // period 0 leaves it high for 1024 steps
+    assert!(
+        samples
+            .iter()
+            .all(|&sample| (sample - 0.1 / 4.0).abs() < 1e-6)
+    );
+}
+
+#[test]
+fn it_silences_channels_at_full_attenuation() {
+    let mut chip = Sn76489::default();
+
+    chip.write(0x81, 0);
+    chip.write(0x90, 0);
+    chip.write(0x9f, 8 * 8 * 10);
+
+    let samples = samples_until(&mut chip, 8 * 8 * 20);
+
+    assert!(samples[..10].iter().all(|&sample| sample.abs() == 0.25));
+    assert!(samples[10..].iter().all(|&sample| sample == 0.0));
+}
+
+#[test]
+fn it_holds_period_1_tones_high_for_sample_playback() {
+    let mut chip = Sn76489::default();
+
+    chip.write(0x81, 0);
+    chip.write(0x00, 0);
+    chip.write(0x90, 0);
+
+    let samples = samples_until(&mut chip, 8 * 8 * 10);
+
+    assert!(samples.iter().all(|&sample| sample == 0.25));
+}
+
+#[test]
+fn it_shifts_periodic_noise_through_15_bits() {
+    let mut chip = Sn76489::default();
+
+    // periodic noise shifting every 32 steps (4 samples), at full volume
+    chip.write(0xe0, 0);
+    chip.write(0xf0, 0);
+
+    let samples = samples_until(&mut chip, 8 * 8 * 4 * 15 * 4);
+
+    let runs = run_lengths(&samples);
+    assert_eq!(runs[..4], [4, 56, 4, 56]);
+}
+
+#[test]
+fn it_feeds_white_noise_back_from_bits_0_and_1() {
+    let mut chip = Sn76489::default();
+
+    chip.write(0xe4, 0);
+    chip.write(0xf0, 0);
+
+    let samples = samples_until(&mut chip, 8 * 8 * 4 * 32);
+
+    // shifting from the first step, the 1 in &4000 reaches bit 0 on the 14th shift,
+    // fed back as it passes bit 1 and again at bit 0 (periodic noise only at bit 0)
+    let ones: Vec<usize> = samples
+        .chunks(4)
+        .enumerate()
+        .filter_map(|(shift, samples)| (samples[0] > 0.0).then_some(shift))
+        .collect();
+
+    assert_eq!(ones, [13, 27, 28]);
+}
+
+fn u32_at(vgm: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(vgm[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn it_records_writes_as_a_vgm_file_for_the_bbc() {
+    let mut chip = Sn76489::default();
+
+    chip.start_recording(1000);
+    // 44.1 then 88.2 samples in
+    chip.write(0x90, 1000 + 2000);
+    chip.write(0x85, 1000 + 4000);
+    assert!(chip.mark_recording_loop(1000 + 40000));
+
+    let vgm = chip.stop_recording(1000 + 80000, "Tune", "Me").unwrap();
+
+    assert!(!chip.is_recording());
+
+    assert_eq!(&vgm[..4], b"Vgm ");
+    assert_eq!(u32_at(&vgm, 0x04) as usize, vgm.len() - 4);
+    assert_eq!(u32_at(&vgm, 0x08), 0x151);
+    assert_eq!(u32_at(&vgm, 0x0c), 4_000_000);
+    assert_eq!(u32_at(&vgm, 0x18), 1764);
+    assert_eq!(u32_at(&vgm, 0x20), 882);
+    assert_eq!(u32_at(&vgm, 0x24), 50);
+    assert_eq!(&vgm[0x28..0x2c], [0x03, 0x00, 15, 0x05]);
+    assert_eq!(u32_at(&vgm, 0x34), 0x0c);
+
+    // the chip's state, latched register last, then the writes
+    let commands: Vec<u8> = [
+        0x9f, 0xa0, 0x00, 0xbf, 0xc0, 0x00, 0xdf, 0xe0, 0xff, 0x80, 0x00,
+    ]
+    .into_iter()
+    .flat_map(|value| [0x50, value])
+    .chain([0x61, 44, 0, 0x50, 0x90, 0x61, 44, 0, 0x50, 0x85])
+    .chain([0x61, 0x1a, 0x03, 0x63, 0x66])
+    .collect();
+
+    let gd3_start = 0x14 + u32_at(&vgm, 0x14) as usize;
+    assert_eq!(&vgm[0x40..gd3_start], commands);
+
+    let loop_start = 0x1c + u32_at(&vgm, 0x1c) as usize;
+    assert_eq!(&vgm[loop_start..gd3_start], [0x63, 0x66]);
+
+    // title, then the system and author among the empty strings
+    let gd3 = &vgm[gd3_start..];
+    assert_eq!(&gd3[..4], b"Gd3 ");
+    assert_eq!(u32_at(gd3, 4), 0x100);
+    assert_eq!(u32_at(gd3, 8) as usize, gd3.len() - 12);
+
+    let strings: Vec<u16> = gd3[12..]
+        .chunks(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let strings: Vec<String> = strings
+        .split(|&unit| unit == 0)
+        .map(String::from_utf16_lossy)
+        .collect();
+
+    assert_eq!(
+        strings,
+        [
+            "Tune",
+            "",
+            "",
+            "",
+            "BBC Micro",
+            "",
+            "Me",
+            "",
+            "",
+            "",
+            "",
+            ""
+        ]
+    );
+}
+
+#[test]
+fn it_packs_short_waits_and_only_marks_loops_while_recording() {
+    let mut chip = Sn76489::default();
+
+    assert!(!chip.mark_recording_loop(0));
+    assert_eq!(chip.stop_recording(0, "", ""), None);
+
+    chip.write(0xc5, 0);
+    chip.write(0x12, 0);
+    chip.start_recording(0);
+
+    // 16 samples
+    chip.write(0xd3, 726);
+
+    let vgm = chip.stop_recording(726, "", "").unwrap();
+
+    // tone 2's period restored last, leaving it latched for data bytes
+    let commands: Vec<u8> = [
+        0x80, 0x00, 0x9f, 0xa0, 0x00, 0xbf, 0xdf, 0xe0, 0xff, 0xc5, 0x12,
+    ]
+    .into_iter()
+    .flat_map(|value| [0x50, value])
+    .chain([0x7f, 0x50, 0xd3, 0x66])
+    .collect();
+
+    let gd3_start = 0x14 + u32_at(&vgm, 0x14) as usize;
+    assert_eq!(&vgm[0x40..gd3_start], commands);
+    assert_eq!(u32_at(&vgm, 0x1c), 0);
+}
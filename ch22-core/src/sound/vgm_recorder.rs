@@ -0,0 +1,145 @@
+use super::SOUND_CLOCK;
+use super::sn76489::{LFSR_WHITE_NOISE_TAPS, LFSR_WIDTH};
+
+// sound chip writes timed in 44.1kHz samples, written out as a VGM 1.51 file
+pub struct VgmRecorder {
+    start_cycles: u64,
+    samples: u64,
+    commands: Vec<u8>,
+    // offset into commands and sample count at the loop marker
+    loop_start: Option<(usize, u64)>,
+}
+
+impl VgmRecorder {
+    // initial_writes bring a player's chip to the state recording started from
+    pub fn new(initial_writes: &[u8], cycles: u64) -> Self {
+        let mut recorder = VgmRecorder {
+            start_cycles: cycles,
+            samples: 0,
+            commands: Vec::new(),
+            loop_start: None,
+        };
+
+        for &value in initial_writes {
+            recorder.write(value, cycles);
+        }
+
+        recorder
+    }
+
+    pub fn write(&mut self, value: u8, cycles: u64) {
+        self.wait_until(cycles);
+        self.commands.extend([VGM_PSG_WRITE, value]);
+    }
+
+    // a later marker replaces an earlier one
+    pub fn mark_loop(&mut self, cycles: u64) {
+        self.wait_until(cycles);
+        self.loop_start = Some((self.commands.len(), self.samples));
+    }
+
+    pub fn finish(mut self, cycles: u64, title: &str, author: &str) -> Vec<u8> {
+        self.wait_until(cycles);
+        self.commands.push(VGM_END);
+
+        let gd3 = gd3_tag(title, author);
+        let gd3_start = VGM_HEADER_LEN + self.commands.len();
+        let file_len = gd3_start + gd3.len();
+
+        let mut header = [0; VGM_HEADER_LEN];
+        let mut put = |offset: usize, value: u32| {
+            header[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        put(0x00, u32::from_le_bytes(*b"Vgm "));
+        put(0x04, (file_len - 0x04) as u32);
+        put(0x08, VGM_VERSION);
+        put(0x0c, SOUND_CLOCK);
+        put(0x14, (gd3_start - 0x14) as u32);
+        put(0x18, self.samples as u32);
+
+        if let Some((loop_offset, loop_samples)) = self.loop_start {
+            put(0x1c, (VGM_HEADER_LEN + loop_offset - 0x1c) as u32);
+            put(0x20, (self.samples - loop_samples) as u32);
+        }
+
+        put(0x24, VGM_FIELD_RATE);
+        put(0x34, (VGM_HEADER_LEN - 0x34) as u32);
+
+        header[0x28..0x2a].copy_from_slice(&LFSR_WHITE_NOISE_TAPS.to_le_bytes());
+        header[0x2a] = LFSR_WIDTH as u8;
+        header[0x2b] = VGM_SN76489_FLAGS;
+
+        [&header[..], &self.commands, &gd3].concat()
+    }
+
+    // sample positions are taken from the start so rounding never drifts
+    fn wait_until(&mut self, cycles: u64) {
+        let samples = (cycles - self.start_cycles) * VGM_SAMPLE_RATE / CYCLES_PER_SECOND;
+        let mut wait = samples.saturating_sub(self.samples);
+
+        self.samples += wait;
+
+        while wait > 0 {
+            match wait {
+                1..=16 => {
+                    self.commands.push(VGM_WAIT_SHORT + (wait - 1) as u8);
+                    wait = 0;
+                }
+                VGM_WAIT_60TH_SAMPLES => {
+                    self.commands.push(VGM_WAIT_60TH);
+                    wait = 0;
+                }
+                VGM_WAIT_50TH_SAMPLES => {
+                    self.commands.push(VGM_WAIT_50TH);
+                    wait = 0;
+                }
+                _ => {
+                    let chunk = wait.min(0xffff);
+
+                    self.commands.push(VGM_WAIT);
+                    self.commands.extend((chunk as u16).to_le_bytes());
+                    wait -= chunk;
+                }
+            }
+        }
+    }
+}
+
+// track and author in english, the system, with the japanese and remaining fields left empty
+fn gd3_tag(title: &str, author: &str) -> Vec<u8> {
+    let fields = [title, "", "", "", "BBC Micro", "", author, "", "", "", ""];
+
+    let strings: Vec<u8> = fields
+        .iter()
+        .flat_map(|field| field.encode_utf16().chain([0]))
+        .flat_map(u16::to_le_bytes)
+        .collect();
+
+    let mut gd3 = b"Gd3 ".to_vec();
+    gd3.extend(GD3_VERSION.to_le_bytes());
+    gd3.extend((strings.len() as u32).to_le_bytes());
+    gd3.extend(strings);
+
+    gd3
+}
+
+const CYCLES_PER_SECOND: u64 = 2_000_000;
+
+const VGM_VERSION: u32 = 0x0151;
+const VGM_HEADER_LEN: usize = 0x40;
+const VGM_SAMPLE_RATE: u64 = 44100;
+const VGM_FIELD_RATE: u32 = 50;
+// frequency 0 is &400, and no game gear stereo
+const VGM_SN76489_FLAGS: u8 = 0x05;
+
+const VGM_PSG_WRITE: u8 = 0x50;
+const VGM_WAIT: u8 = 0x61;
+const VGM_WAIT_60TH: u8 = 0x62;
+const VGM_WAIT_50TH: u8 = 0x63;
+const VGM_END: u8 = 0x66;
+const VGM_WAIT_SHORT: u8 = 0x70;
+const VGM_WAIT_60TH_SAMPLES: u64 = 735;
+const VGM_WAIT_50TH_SAMPLES: u64 = 882;
+
+const GD3_VERSION: u32 = 0x0100;
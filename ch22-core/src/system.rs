@@ -2,7 +2,13 @@ mod address_map;
 mod clock;
 mod core;
 mod cpu_bus;
+mod machine_builder;
+mod memory_space;
 mod runner;
 mod system_ffi;
 
+pub use address_map::{AddressMap, FnAddressMap, ModelBAddressMap};
 pub use clock::Clock;
+pub use core::Core;
+pub use machine_builder::MachineBuilder;
+pub use memory_space::MemorySpace;
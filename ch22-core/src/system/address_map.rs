@@ -1,7 +1,7 @@
-use std::cell::Cell;
-
-use super::{clock::Clock, core::ROMS_LEN};
+use super::clock::Clock;
 use crate::address_spaces::{IOSpace, Ram, Rom};
+use crate::devices::RomSelect;
+use crate::shared::Latch;
 use crate::word::Word;
 
 pub trait AddressMap {
@@ -10,9 +10,8 @@ pub trait AddressMap {
         address: Word,
         clock: &mut Clock,
         ram: &mut Ram,
-        roms: &[Rom; ROMS_LEN],
+        roms: &[Rom],
         io_space: &mut IOSpace,
-        rom_select_latch: &Cell<usize>,
     ) -> u8;
 
     fn write(
@@ -27,7 +26,7 @@ pub trait AddressMap {
 
 pub struct FnAddressMap<FRead, FWrite>
 where
-    FRead: FnMut(Word, &mut Clock, &mut Ram, &[Rom; ROMS_LEN], &mut IOSpace, &Cell<usize>) -> u8,
+    FRead: FnMut(Word, &mut Clock, &mut Ram, &[Rom], &mut IOSpace) -> u8,
     FWrite: FnMut(Word, u8, &mut Clock, &mut Ram, &mut IOSpace),
 {
     pub read: FRead,
@@ -36,7 +35,7 @@ where
 
 impl<FRead, FWrite> AddressMap for FnAddressMap<FRead, FWrite>
 where
-    FRead: FnMut(Word, &mut Clock, &mut Ram, &[Rom; ROMS_LEN], &mut IOSpace, &Cell<usize>) -> u8,
+    FRead: FnMut(Word, &mut Clock, &mut Ram, &[Rom], &mut IOSpace) -> u8,
     FWrite: FnMut(Word, u8, &mut Clock, &mut Ram, &mut IOSpace),
 {
     fn read(
@@ -44,11 +43,10 @@ where
         address: Word,
         clock: &mut Clock,
         ram: &mut Ram,
-        roms: &[Rom; ROMS_LEN],
+        roms: &[Rom],
         io_space: &mut IOSpace,
-        rom_select_latch: &Cell<usize>,
     ) -> u8 {
-        (self.read)(address, clock, ram, roms, io_space, rom_select_latch)
+        (self.read)(address, clock, ram, roms, io_space)
     }
 
     fn write(
@@ -62,3 +60,65 @@ where
         (self.write)(address, value, clock, ram, io_space)
     }
 }
+
+// BBC Model B layout: 32k ram, 16 paged roms selected by the rom select latch and the OS rom
+pub struct ModelBAddressMap {
+    rom_select_latch: Latch,
+}
+
+impl Default for ModelBAddressMap {
+    fn default() -> Self {
+        let rom_select_latch = Latch::default();
+
+        rom_select_latch.set(15);
+
+        ModelBAddressMap { rom_select_latch }
+    }
+}
+
+impl ModelBAddressMap {
+    pub fn create_rom_select_device(&self) -> RomSelect {
+        RomSelect::new(self.rom_select_latch.clone())
+    }
+}
+
+impl AddressMap for ModelBAddressMap {
+    fn read(
+        &mut self,
+        address: Word,
+        clock: &mut Clock,
+        ram: &mut Ram,
+        roms: &[Rom],
+        io_space: &mut IOSpace,
+    ) -> u8 {
+        match address.1 {
+            ..0x80 => ram.read(address),
+            0x80..0xc0 => roms[self.rom_select_latch.get() as usize].read(address.rebased_to(0x80)),
+            0xc0..0xfc => roms[MODEL_B_OS_ROM].read(address.rebased_to(0xc0)),
+            0xfc..0xff => io_space.read(address, clock),
+            0xff.. => roms[MODEL_B_OS_ROM].read(address.rebased_to(0xc0)),
+        }
+    }
+
+    fn write(
+        &mut self,
+        address: Word,
+        value: u8,
+        clock: &mut Clock,
+        ram: &mut Ram,
+        io_space: &mut IOSpace,
+    ) {
+        match address.1 {
+            ..0x80 => ram.write(address, value),
+            0x80..0xc0 => (), // paged rom
+            0xc0..0xfc => (), // os rom
+            0xfc..0xff => io_space.write(address, value, clock),
+            0xff.. => (), // os rom
+        }
+    }
+}
+
+pub const MODEL_B_RAM_SIZE: usize = 0x8000;
+pub const MODEL_B_ROM_SIZE: usize = 0x4000;
+pub const MODEL_B_OS_ROM: usize = 16;
+pub const MODEL_B_ROMS_LEN: usize = 17;
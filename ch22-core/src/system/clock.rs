@@ -1,15 +1,25 @@
-use crate::devices::TimerDeviceList;
+use crate::devices::Scheduler;
 
+#[cfg(test)]
+mod tests;
+
+// counts cycles against a budget running up to the next scheduled device trigger
+// ram and rom accesses only tick the count, devices are synced when io is accessed or
+// interrupts are polled, or by the runner between instructions once the budget is spent
 pub struct Clock<'a> {
     cycles: &'a mut u64,
-    timer_devices: &'a mut TimerDeviceList,
+    scheduler: &'a mut Scheduler,
+    budget: u64,
 }
 
 impl<'a> Clock<'a> {
-    pub fn new(cycles: &'a mut u64, timer_devices: &'a mut TimerDeviceList) -> Self {
+    pub fn new(cycles: &'a mut u64, scheduler: &'a mut Scheduler) -> Self {
+        let budget = next_budget(scheduler);
+
         Clock {
             cycles,
-            timer_devices,
+            scheduler,
+            budget,
         }
     }
 
@@ -17,15 +27,35 @@ impl<'a> Clock<'a> {
         *self.cycles
     }
 
+    pub fn get_budget(&self) -> u64 {
+        self.budget
+    }
+
+    pub fn tick(&mut self) {
+        *self.cycles += 1;
+    }
+
     pub fn one_mhz_sync(&mut self) {
         if *self.cycles & 1 != 0 {
-            self.inc();
+            self.tick();
         }
     }
 
-    pub fn inc(&mut self) {
-        *self.cycles += 1;
+    // brings devices due by now up to date, then budgets up to the next trigger
+    pub fn sync_devices(&mut self) {
+        if *self.cycles >= self.budget {
+            self.scheduler.sync(*self.cycles);
+
+            self.budget = next_budget(self.scheduler);
+        }
+    }
 
-        self.timer_devices.sync(*self.cycles);
+    // picks up triggers set since the budget was taken
+    pub fn refresh_budget(&mut self) {
+        self.budget = next_budget(self.scheduler);
     }
 }
+
+fn next_budget(scheduler: &mut Scheduler) -> u64 {
+    scheduler.next_device_trigger().unwrap_or(u64::MAX)
+}
@@ -0,0 +1,65 @@
+use super::*;
+use crate::devices::timer_device_mock::TimerDeviceMock;
+
+#[test]
+fn it_counts_cycles_without_syncing_devices() {
+    let mut scheduler = Scheduler::default();
+    let device = Box::new(TimerDeviceMock::new(Some(20)));
+    let device_accesses = device.get_accesses();
+    let device_id = scheduler.add_device(device);
+    scheduler.set_device_trigger(device_id, Some(10));
+
+    let mut cycles = 0;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    for _ in 0..15 {
+        clock.tick();
+    }
+
+    assert_eq!(clock.get_budget(), 10);
+    assert!(device_accesses.borrow().syncs.is_empty());
+
+    clock.sync_devices();
+
+    assert_eq!(clock.get_budget(), 20);
+    assert_eq!(device_accesses.borrow().syncs, [15]);
+    assert_eq!(cycles, 15);
+}
+
+#[test]
+fn it_only_syncs_devices_once_the_budget_is_spent() {
+    let mut scheduler = Scheduler::default();
+    let device = Box::new(TimerDeviceMock::new(None));
+    let device_accesses = device.get_accesses();
+    let device_id = scheduler.add_device(device);
+    scheduler.set_device_trigger(device_id, Some(10));
+
+    let mut cycles = 9;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    clock.sync_devices();
+
+    assert!(device_accesses.borrow().syncs.is_empty());
+
+    clock.tick();
+    clock.sync_devices();
+
+    assert_eq!(clock.get_budget(), u64::MAX);
+    assert_eq!(device_accesses.borrow().syncs, [10]);
+}
+
+#[test]
+fn it_refreshes_the_budget_from_the_scheduler() {
+    let mut scheduler = Scheduler::default();
+    let device_id = scheduler.add_device(Box::new(TimerDeviceMock::new(None)));
+
+    let mut cycles = 0;
+    let mut clock = Clock::new(&mut cycles, &mut scheduler);
+
+    assert_eq!(clock.get_budget(), u64::MAX);
+
+    clock.scheduler.set_device_trigger(device_id, Some(30));
+    clock.refresh_budget();
+
+    assert_eq!(clock.get_budget(), 30);
+}
@@ -1,82 +1,635 @@
-use std::cell::Cell;
-use std::rc::Rc;
+use std::collections::HashMap;
 
 use super::{
     Clock,
-    address_map::{AddressMap, FnAddressMap},
+    address_map::{
+        AddressMap, MODEL_B_RAM_SIZE, MODEL_B_ROM_SIZE, MODEL_B_ROMS_LEN, ModelBAddressMap,
+    },
     cpu_bus::CpuBus,
+    machine_builder::MachineBuilder,
+    memory_space::MemorySpace,
     runner::{Runner, RunnerTrait},
 };
-use crate::address_spaces::{IOSpace, Ram, Rom};
-use crate::devices::{RomSelect, TimerDeviceList};
+use crate::address_spaces::{
+    FloatingBus, IOAccess, IOAccessFilter, IOSpace, PowerOnRam, Ram, Rom, UnmappedAccess,
+};
+use crate::cheats::CheatList;
+use crate::devices::{
+    CoreEvent, DeviceDescription, IOAddressRange, IODevice, IODeviceID, ResetType, Scheduler,
+    TimerDevice, TimerDeviceID,
+};
+use crate::fdc::{DiscImage, FDC_ADDRESS_RANGE, Fdc};
+use crate::inf_file::host_address;
+use crate::keyboard::{BbcKey, HostKeyboard, HostLayout, KeyMapping, KeyboardMatrix};
+use crate::paste::Paste;
+use crate::shared::{Latch, Shared};
+use crate::sound::Sn76489;
+use crate::tube::{PARASITE_CLOCK_MHZ, TUBE_ADDRESS_RANGE, TUBE_SYNC_INTERVAL, Tube};
+use crate::user_port::{
+    AmxMouse, EmptyUserPort, MouseInput, USER_VIA_ADDRESS_RANGE, UserPort, UserPortDevice,
+};
+use crate::via::{
+    IC32_CAPS_LOCK_LED, IC32_SHIFT_LOCK_LED, SYSTEM_VIA_ADDRESS_RANGE, SystemViaPeripheral, Via,
+    ViaIODevice, ViaPeripheral,
+};
 use crate::video::Video;
-use crate::{cpu::Cpu, devices::DeviceSpeed};
+use crate::word::Word;
+use crate::{
+    cpu::{Cpu, InterruptType},
+    devices::DeviceSpeed,
+};
+
+#[cfg(test)]
+mod tests;
 
-#[derive(Default)]
-pub struct Core {
+pub struct Core<A: AddressMap> {
     cycles: u64,
     cpu: Cpu,
     ram: Ram,
-    pub roms: [Rom; ROMS_LEN],
-    pub io_space: IOSpace,
-    pub ic32_latch: Rc<Cell<u8>>,
-    rom_select_latch: Rc<Cell<usize>>,
-    pub timer_devices: TimerDeviceList,
-    pub video: Video,
+    roms: Vec<Rom>,
+    pub(crate) io_space: IOSpace,
+    pub(crate) ic32_latch: Latch,
+    keyboard: Shared<KeyboardMatrix>,
+    host_keyboard: HostKeyboard,
+    system_via: Option<Via>,
+    pub(crate) sound: Shared<Sn76489>,
+    fdc: Option<Fdc>,
+    user_port: UserPort,
+    user_port_timer_id: Option<TimerDeviceID>,
+    mouse: Shared<MouseInput>,
+    pub(crate) scheduler: Scheduler,
+    // timers driving io devices, removed along with them
+    device_timer_ids: HashMap<IODeviceID, TimerDeviceID>,
+    pub(crate) video: Video,
+    address_map: A,
+    cheats: CheatList,
+    paste: Paste,
 }
 
-impl Core {
+// enough for the os to initialise (and clear memory) before a binary is loaded
+const OS_INIT_FIELDS: u32 = 50;
+
+// what peeks beyond the end of ram or a rom bank read as
+const UNMAPPED_PEEK_VALUE: u8 = 0xff;
+
+impl Default for Core<ModelBAddressMap> {
+    fn default() -> Self {
+        MachineBuilder::new(ModelBAddressMap::default())
+            .ram_size(MODEL_B_RAM_SIZE)
+            .roms(MODEL_B_ROMS_LEN, MODEL_B_ROM_SIZE)
+            .build()
+    }
+}
+
+impl Core<ModelBAddressMap> {
     pub fn setup(&mut self) {
         self.video.init();
 
-        self.io_space.add_device(
-            &[
-                0xfe00, 0xfe01, 0xfe02, 0xfe03, 0xfe04, 0xfe05, 0xfe06, 0xfe07,
-            ],
+        let crtc_id = self.io_space.add_device(
+            IOAddressRange::mirrored(0xfe00, 0x08, 0x01),
             Box::new(self.video.create_crtc_registers_device()),
             None,
             DeviceSpeed::OneMhz,
         );
 
-        self.io_space.add_device(
-            &[0xfe20, 0xfe21, 0xfe22, 0xfe23],
+        let ula_id = self.io_space.add_device(
+            IOAddressRange::mirrored(0xfe20, 0x10, 0x01),
             Box::new(self.video.create_ula_registers_device()),
             None,
             DeviceSpeed::OneMhz,
         );
 
-        self.rom_select_latch.set(15);
-
-        self.io_space.add_device(
-            &[0xfe30, 0xfe31, 0xfe32, 0xfe33],
-            Box::new(RomSelect::new(self.rom_select_latch.clone())),
+        let rom_select_id = self.io_space.add_device(
+            IOAddressRange::mirrored(0xfe30, 0x10, 0x00),
+            Box::new(self.address_map.create_rom_select_device()),
             None,
             DeviceSpeed::TwoMhz,
         );
+
+        let system_via = Via::new(
+            Box::new(SystemViaPeripheral::new(
+                self.ic32_latch.clone(),
+                self.keyboard.clone(),
+                self.sound.clone(),
+            )),
+            self.cycles,
+        );
+
+        let system_via_id = self.add_via_devices(
+            SYSTEM_VIA_ADDRESS_RANGE,
+            &system_via,
+            system_via.create_io_device().with_vsync_on_ca1(),
+            Some(InterruptType::IRQ),
+        );
+
+        self.system_via = Some(system_via);
+
+        let user_via = Via::new(Box::new(self.user_port.create_peripheral()), self.cycles);
+
+        let user_via_id = self.add_via_devices(
+            USER_VIA_ADDRESS_RANGE,
+            &user_via,
+            user_via.create_io_device(),
+            Some(InterruptType::IRQ),
+        );
+
+        self.user_port_timer_id = Some(
+            self.scheduler
+                .add_device(Box::new(self.user_port.create_timer_device(user_via))),
+        );
+
+        // none of these see RES, so keep their state through BREAK
+        // (the os tells power on from BREAK by the system VIA's IER surviving)
+        for device_id in [crtc_id, ula_id, rom_select_id, system_via_id, user_via_id] {
+            self.io_space.set_reset_on_break(device_id, false);
+        }
     }
 
-    fn address_map() -> impl AddressMap {
-        FnAddressMap {
-            read: |address, clock, ram, roms, io_space, rom_select_latch| match address.1 {
-                ..0x80 => ram.read(address),
-                0x80..0xc0 => roms[rom_select_latch.get()].read(address.rebased_to(0x80)),
-                0xc0..0xfc => roms[OS_ROM].read(address.rebased_to(0xc0)),
-                0xfc..0xff => io_space.read(address, clock),
-                0xff.. => roms[OS_ROM].read(address.rebased_to(0xc0)),
-            },
-            write: |address, value, clock, ram, io_space| {
-                match address.1 {
-                    ..0x80 => ram.write(address, value),
-                    0x80..0xc0 => (), // paged rom
-                    0xc0..0xfc => (), // os rom
-                    0xfc..0xff => io_space.write(address, value, clock),
-                    0xff.. => (), // os rom
-                }
-            },
+    pub fn key_down(&mut self, key: BbcKey) {
+        self.keyboard.borrow_mut().set_key(key, true);
+        self.update_keyboard_interrupt();
+    }
+
+    pub fn key_up(&mut self, key: BbcKey) {
+        self.keyboard.borrow_mut().set_key(key, false);
+        self.update_keyboard_interrupt();
+    }
+
+    // host keys by DOM code and key value, false if the key isn't mapped
+    pub fn host_key_down(&mut self, code: &str, key: &str) -> bool {
+        let is_mapped =
+            self.host_keyboard
+                .key_down(&mut self.keyboard.borrow_mut(), code, key);
+        self.update_keyboard_interrupt();
+
+        is_mapped
+    }
+
+    pub fn host_key_up(&mut self, code: &str) -> bool {
+        let is_mapped = self
+            .host_keyboard
+            .key_up(&mut self.keyboard.borrow_mut(), code);
+        self.update_keyboard_interrupt();
+
+        is_mapped
+    }
+
+    pub fn release_all_keys(&mut self) {
+        self.host_keyboard
+            .release_all(&mut self.keyboard.borrow_mut());
+        self.update_keyboard_interrupt();
+    }
+
+    pub fn set_keyboard_layout(&mut self, layout: HostLayout, mapping: KeyMapping) {
+        self.host_keyboard.set_layout(layout, mapping);
+    }
+
+    // read by the os at power on and BREAK, see KeyboardMatrix::set_links
+    pub fn set_startup_links(&mut self, links: u8) {
+        self.keyboard.borrow_mut().set_links(links);
+    }
+
+    // caps lock and shift lock, lit while their IC32 outputs are low
+    pub fn keyboard_leds(&self) -> (bool, bool) {
+        let ic32 = self.ic32_latch.get();
+
+        (ic32 & IC32_CAPS_LOCK_LED == 0, ic32 & IC32_SHIFT_LOCK_LED == 0)
+    }
+
+    // replaces whatever is on the user port, the user VIA keeps its state
+    pub fn plug_user_port_device(&mut self, device: Box<dyn UserPortDevice>) {
+        self.user_port.plug(device);
+        self.update_user_port();
+    }
+
+    pub fn unplug_user_port_device(&mut self) {
+        self.plug_user_port_device(Box::new(EmptyUserPort));
+    }
+
+    pub fn plug_amx_mouse(&mut self) {
+        self.plug_user_port_device(Box::new(AmxMouse::new(self.mouse.clone())));
+    }
+
+    // host relative motion, x right and y down
+    pub fn mouse_move(&mut self, dx: i32, dy: i32) {
+        let mut mouse = self.mouse.borrow_mut();
+        let was_moving = mouse.is_moving();
+
+        mouse.add_motion(dx, dy);
+
+        // a mouse already stepping picks the motion up at its next step
+        let start_moving = !was_moving && mouse.is_moving();
+        drop(mouse);
+
+        if start_moving {
+            self.update_user_port();
         }
     }
 
+    // bit 0 left, bit 1 middle, bit 2 right
+    pub fn set_mouse_buttons(&mut self, buttons: u8) {
+        self.mouse.borrow_mut().buttons = buttons;
+    }
+
+    // logs sound chip writes from now, starting from the chip's current state
+    pub fn start_vgm_recording(&mut self) {
+        self.sound.borrow_mut().start_recording(self.cycles);
+    }
+
+    // where playback loops back to, false if not recording
+    pub fn mark_vgm_loop(&mut self) -> bool {
+        self.sound.borrow_mut().mark_recording_loop(self.cycles)
+    }
+
+    // the recording as a VGM file, None if not recording
+    pub fn stop_vgm_recording(&mut self, title: &str, author: &str) -> Option<Vec<u8>> {
+        self.sound
+            .borrow_mut()
+            .stop_recording(self.cycles, title, author)
+    }
+
+    pub fn is_recording_vgm(&self) -> bool {
+        self.sound.borrow().is_recording()
+    }
+
+    // an 8271 at &FE80 with two (empty) drives, for the DFS
+    pub fn add_fdc(&mut self) {
+        let fdc = Fdc::default();
+
+        let device_id = self.io_space.add_device(
+            FDC_ADDRESS_RANGE,
+            Box::new(fdc.create_io_device()),
+            Some(InterruptType::NMI),
+            DeviceSpeed::TwoMhz,
+        );
+
+        self.add_device_timer(device_id, Box::new(fdc.create_timer_device()), self.cycles);
+
+        self.fdc = Some(fdc);
+    }
+
+    // an .ssd or .dsd image into drive 0 or 1, replacing any disc there
+    pub fn insert_disc(
+        &mut self,
+        drive: usize,
+        data: &[u8],
+        double_sided: bool,
+        write_protected: bool,
+    ) -> Result<(), String> {
+        let Some(fdc) = &self.fdc else {
+            return Err("No disc controller".to_string());
+        };
+
+        if drive >= 2 {
+            return Err(format!("Invalid drive: {drive}"));
+        }
+
+        let disc = DiscImage::new(data, double_sided, write_protected)?;
+        fdc.chip().borrow_mut().insert_disc(drive, disc);
+
+        Ok(())
+    }
+
+    // false if the drive was empty
+    pub fn eject_disc(&mut self, drive: usize) -> bool {
+        self.fdc
+            .as_ref()
+            .is_some_and(|fdc| fdc.chip().borrow_mut().eject_disc(drive).is_some())
+    }
+
+    // written to since it was inserted or last saved
+    pub fn is_disc_dirty(&self, drive: usize) -> bool {
+        self.fdc.as_ref().is_some_and(|fdc| {
+            fdc.chip()
+                .borrow()
+                .disc(drive)
+                .is_some_and(DiscImage::is_dirty)
+        })
+    }
+
+    // the disc in the layout it was inserted with, None if the drive is empty
+    pub fn save_disc(&mut self, drive: usize) -> Option<Vec<u8>> {
+        let mut fdc = self.fdc.as_ref()?.chip().borrow_mut();
+        let disc = fdc.disc_mut(drive)?;
+
+        disc.mark_clean();
+
+        Some(disc.to_bytes())
+    }
+
+    pub fn add_tube(&mut self, boot_rom: &[u8]) {
+        let tube = Tube::new(boot_rom, PARASITE_CLOCK_MHZ, self.cycles);
+
+        let device_id = self.io_space.add_device(
+            TUBE_ADDRESS_RANGE,
+            Box::new(tube.create_host_device()),
+            Some(InterruptType::IRQ),
+            DeviceSpeed::TwoMhz,
+        );
+
+        self.add_device_timer(
+            device_id,
+            Box::new(tube.create_timer_device()),
+            self.cycles + TUBE_SYNC_INTERVAL,
+        );
+    }
+}
+
+impl<A: AddressMap> Core<A> {
+    pub(crate) fn new(address_map: A, ram: Ram, roms: Vec<Rom>) -> Self {
+        let mut core = Core {
+            cycles: 0,
+            cpu: Cpu::default(),
+            ram,
+            roms,
+            io_space: IOSpace::default(),
+            ic32_latch: Latch::default(),
+            keyboard: Shared::default(),
+            host_keyboard: HostKeyboard::default(),
+            system_via: None,
+            sound: Shared::default(),
+            fdc: None,
+            user_port: UserPort::default(),
+            user_port_timer_id: None,
+            mouse: Shared::default(),
+            scheduler: Scheduler::default(),
+            device_timer_ids: HashMap::new(),
+            video: Video::default(),
+            address_map,
+            cheats: CheatList::default(),
+            paste: Paste::default(),
+        };
+
+        core.scheduler.schedule_core_event(CoreEvent::Scanline, 0);
+
+        core
+    }
+
+    pub fn get_cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn ram(&self) -> &Ram {
+        &self.ram
+    }
+
+    pub fn ram_mut(&mut self) -> &mut Ram {
+        &mut self.ram
+    }
+
+    pub fn cheats(&self) -> &CheatList {
+        &self.cheats
+    }
+
+    pub fn cheats_mut(&mut self) -> &mut CheatList {
+        &mut self.cheats
+    }
+
+    // boots the os then starts a program loaded straight into ram, skipping the filing system
+    // addresses are host addresses as in .inf files (see host_address), a second processor
+    // address or a binary running past the end of ram is rejected before the machine is reset
+    pub fn load_binary(
+        &mut self,
+        data: &[u8],
+        load_address: u32,
+        exec_address: u32,
+    ) -> Result<(), String> {
+        let load_address = host_address(load_address)
+            .ok_or(format!("Not a host load address: {load_address:08x}"))?;
+        let exec_address = host_address(exec_address)
+            .ok_or(format!("Not a host exec address: {exec_address:08x}"))?;
+
+        let load_end = load_address as usize + data.len();
+
+        if load_end > self.ram.len() {
+            return Err(format!(
+                "Binary doesn't fit in RAM: {load_address:04x}-{load_end:04x}"
+            ));
+        }
+
+        self.reset();
+        self.run_fields(OS_INIT_FIELDS);
+
+        for (offset, value) in data.iter().enumerate() {
+            self.ram
+                .write(Word::from(load_address + offset as u16), *value);
+        }
+
+        self.cpu.start_program(Word::from(exec_address));
+
+        Ok(())
+    }
+
+    // types text through the os keyboard buffer, translating newlines and £
+    pub fn paste_text(&mut self, text: &str) {
+        self.paste.start(text);
+    }
+
+    pub fn cancel_paste(&mut self) {
+        self.paste.cancel();
+    }
+
+    pub fn is_pasting(&self) -> bool {
+        self.paste.is_active()
+    }
+
+    pub fn load_rom(&mut self, bank: usize, data: &[u8]) {
+        self.rom_mut(bank).load(data);
+    }
+
+    // reads without side effects: devices are peeked and no cycles pass
+    pub fn peek(&mut self, space: MemorySpace, address: u16) -> u8 {
+        match space {
+            MemorySpace::Cpu => self.with_peeking(|address_map, clock, ram, roms, io_space| {
+                address_map.read(address.into(), clock, ram, roms, io_space)
+            }),
+            MemorySpace::Ram => self.ram.get(address).unwrap_or(UNMAPPED_PEEK_VALUE),
+            MemorySpace::Rom(bank) => self
+                .roms
+                .get(bank)
+                .and_then(|rom| rom.get(address))
+                .unwrap_or(UNMAPPED_PEEK_VALUE),
+        }
+    }
+
+    // writes without side effects: io writes through the cpu view are dropped,
+    // as are writes beyond the end of ram or a rom bank
+    pub fn poke(&mut self, space: MemorySpace, address: u16, value: u8) {
+        let target = match space {
+            MemorySpace::Cpu => {
+                self.with_peeking(|address_map, clock, ram, _, io_space| {
+                    address_map.write(address.into(), value, clock, ram, io_space)
+                });
+
+                return;
+            }
+            MemorySpace::Ram => self.ram.get_mut(address),
+            MemorySpace::Rom(bank) => self.roms.get_mut(bank).and_then(|rom| rom.get_mut(address)),
+        };
+
+        if let Some(target) = target {
+            *target = value;
+        }
+    }
+
+    pub fn add_io_device(
+        &mut self,
+        address_range: IOAddressRange,
+        device: Box<dyn IODevice>,
+        interrupt_type: Option<InterruptType>,
+        speed: DeviceSpeed,
+    ) -> IODeviceID {
+        self.io_space
+            .add_device(address_range, device, interrupt_type, speed)
+    }
+
+    // the handle-taking methods below reject stale handles, returning false or None
+
+    // along with the timer driving it, for devices such as VIAs that come with one
+    pub fn remove_io_device(&mut self, device_id: IODeviceID) -> Option<Box<dyn IODevice>> {
+        let device = self.io_space.remove_device(device_id)?;
+
+        if let Some(timer_device_id) = self.device_timer_ids.remove(&device_id) {
+            self.scheduler.remove_device(timer_device_id);
+        }
+
+        Some(device)
+    }
+
+    pub fn replace_io_device(
+        &mut self,
+        device_id: IODeviceID,
+        device: Box<dyn IODevice>,
+    ) -> Option<Box<dyn IODevice>> {
+        self.io_space.replace_device(device_id, device)
+    }
+
+    pub fn set_device_interrupt(&mut self, device_id: IODeviceID, interrupt: bool) -> bool {
+        self.io_space.set_interrupt(device_id, interrupt)
+    }
+
+    pub fn set_floating_bus(&mut self, floating_bus: FloatingBus) {
+        self.io_space.set_floating_bus(floating_bus);
+    }
+
+    pub fn set_unmapped_logging(&mut self, enabled: bool) {
+        self.io_space.set_unmapped_logging(enabled);
+    }
+
+    pub fn take_unmapped_accesses(&mut self) -> Vec<UnmappedAccess> {
+        self.io_space.take_unmapped_accesses()
+    }
+
+    // None if the handle is stale or the device has no state to save
+    pub fn save_io_device_state(&self, device_id: IODeviceID) -> Option<Vec<u8>> {
+        self.io_space.save_device_state(device_id)
+    }
+
+    pub fn load_io_device_state(
+        &mut self,
+        device_id: IODeviceID,
+        state: &[u8],
+    ) -> Result<(), String> {
+        self.io_space.load_device_state(device_id, state)
+    }
+
+    pub fn describe_io_devices(&self) -> Vec<(IODeviceID, DeviceDescription)> {
+        self.io_space.describe_devices()
+    }
+
+    // whether BREAK reaches the device as well as power on, true when added
+    pub fn set_device_reset_on_break(
+        &mut self,
+        device_id: IODeviceID,
+        reset_on_break: bool,
+    ) -> bool {
+        self.io_space.set_reset_on_break(device_id, reset_on_break)
+    }
+
+    // keeps the latest capacity device accesses, 0 disables logging
+    pub fn set_io_access_logging(&mut self, capacity: usize) {
+        self.io_space.set_access_logging(capacity);
+    }
+
+    pub fn set_io_access_filter(&mut self, filter: IOAccessFilter) {
+        self.io_space.set_access_filter(filter);
+    }
+
+    pub fn take_io_accesses(&mut self) -> Vec<IOAccess> {
+        self.io_space.take_accesses()
+    }
+
+    // a 6522 on the 1MHz bus, woken by the scheduler as its timers run out
+    pub fn add_via(
+        &mut self,
+        address_range: IOAddressRange,
+        peripheral: Box<dyn ViaPeripheral>,
+        interrupt_type: Option<InterruptType>,
+    ) -> (IODeviceID, Via) {
+        let via = Via::new(peripheral, self.cycles);
+
+        let device_id = self.add_via_devices(
+            address_range,
+            &via,
+            via.create_io_device(),
+            interrupt_type,
+        );
+
+        (device_id, via)
+    }
+
+    pub fn add_timer_device(&mut self, device: Box<dyn TimerDevice>) -> TimerDeviceID {
+        self.scheduler.add_device(device)
+    }
+
+    pub fn remove_timer_device(
+        &mut self,
+        device_id: TimerDeviceID,
+    ) -> Option<Box<dyn TimerDevice>> {
+        self.scheduler.remove_device(device_id)
+    }
+
+    pub fn replace_timer_device(
+        &mut self,
+        device_id: TimerDeviceID,
+        device: Box<dyn TimerDevice>,
+    ) -> Option<Box<dyn TimerDevice>> {
+        self.scheduler.replace_device(device_id, device)
+    }
+
+    pub fn set_device_trigger(&mut self, device_id: TimerDeviceID, trigger: Option<u64>) -> bool {
+        self.scheduler.set_device_trigger(device_id, trigger)
+    }
+
+    pub fn set_device_period(
+        &mut self,
+        device_id: TimerDeviceID,
+        first_trigger: u64,
+        period: u64,
+    ) -> bool {
+        self.scheduler
+            .set_device_period(device_id, first_trigger, period)
+    }
+
+    // BREAK, asserting RES on the cpu and the devices wired to it
     pub fn reset(&mut self) {
+        self.io_space.reset(ResetType::Break);
+
+        self.with_runner(|runner| {
+            runner.reset();
+        });
+    }
+
+    // everything else starts from scratch, time carries on so scheduled triggers still hold
+    pub fn power_on(&mut self, ram_contents: PowerOnRam) {
+        self.ram.power_on(ram_contents);
+
+        self.video.init();
+
+        self.io_space.reset(ResetType::PowerOn);
+
+        self.cpu = Cpu::default();
+
         self.with_runner(|runner| {
             runner.reset();
         });
@@ -84,32 +637,91 @@ impl Core {
 
     pub fn run_one_field(&mut self) -> u64 {
         loop {
-            self.run(self.video.get_next_scanline_trigger());
+            if let Some(next_core_event) = self.scheduler.next_core_event() {
+                self.run(next_core_event);
+            }
+
+            while let Some(event) = self.scheduler.pop_core_event(self.cycles) {
+                match event {
+                    CoreEvent::Scanline => {
+                        let is_field_complete = self.process_scanline();
 
-            let is_field_complete = self.process_scanline();
+                        self.scheduler.schedule_core_event(
+                            CoreEvent::Scanline,
+                            self.video.get_next_scanline_trigger(),
+                        );
 
-            if is_field_complete {
-                return self.cycles;
+                        if is_field_complete {
+                            self.io_space.on_field_complete(self.cycles);
+                            self.sound.borrow_mut().on_field_complete(self.cycles);
+                            self.cheats.on_field_complete(&mut self.ram);
+                            self.paste.on_field_complete(&mut self.ram);
+
+                            return self.cycles;
+                        }
+                    }
+                }
             }
         }
     }
 
-    fn run(&mut self, until: u64) {
+    pub fn run_fields(&mut self, field_count: u32) -> u64 {
+        for _ in 0..field_count {
+            self.run_one_field();
+        }
+
+        self.cycles
+    }
+
+    pub fn run(&mut self, until: u64) -> u64 {
         self.with_runner(|runner| {
             runner.run(until);
-        })
+        });
+
+        self.cycles
+    }
+
+    fn rom_mut(&mut self, bank: usize) -> &mut Rom {
+        let Some(rom) = self.roms.get_mut(bank) else {
+            panic!("Invalid ROM bank: {bank}");
+        };
+
+        rom
+    }
+
+    fn with_peeking<T>(
+        &mut self,
+        peek_fn: impl FnOnce(&mut A, &mut Clock, &mut Ram, &[Rom], &mut IOSpace) -> T,
+    ) -> T {
+        // the address map gets a throwaway copy of the cycles so time can't move on
+        let mut cycles = self.cycles;
+        let mut clock = Clock::new(&mut cycles, &mut self.scheduler);
+
+        self.io_space.set_peeking(true);
+
+        let value = peek_fn(
+            &mut self.address_map,
+            &mut clock,
+            &mut self.ram,
+            &self.roms,
+            &mut self.io_space,
+        );
+
+        self.io_space.set_peeking(false);
+
+        value
     }
 
     fn with_runner(&mut self, run_fn: impl FnOnce(&mut dyn RunnerTrait)) {
-        let clock = Clock::new(&mut self.cycles, &mut self.timer_devices);
+        let clock = Clock::new(&mut self.cycles, &mut self.scheduler);
 
         let cpu_bus = CpuBus::new(
             clock,
             &mut self.ram,
             &self.roms,
             &mut self.io_space,
-            &self.rom_select_latch,
-            Self::address_map(),
+            &mut self.address_map,
+            &self.cheats,
         );
 
         let mut runner = Runner {
@@ -120,14 +732,61 @@ impl Core {
         run_fn(&mut runner);
     }
 
+    // CA2 follows the keys, so the system VIA has to see them change
+    fn update_keyboard_interrupt(&mut self) {
+        if let Some(system_via) = &self.system_via {
+            system_via
+                .chip()
+                .borrow_mut()
+                .resample_peripheral(self.cycles);
+        }
+    }
+
+    fn update_user_port(&mut self) {
+        if let Some(timer_id) = self.user_port_timer_id {
+            self.scheduler
+                .set_device_trigger(timer_id, Some(self.cycles));
+        }
+    }
+
+    fn add_via_devices(
+        &mut self,
+        address_range: IOAddressRange,
+        via: &Via,
+        io_device: ViaIODevice,
+        interrupt_type: Option<InterruptType>,
+    ) -> IODeviceID {
+        let device_id = self.io_space.add_device(
+            address_range,
+            Box::new(io_device),
+            interrupt_type,
+            DeviceSpeed::OneMhz,
+        );
+
+        self.add_device_timer(device_id, Box::new(via.create_timer_device()), self.cycles);
+
+        device_id
+    }
+
+    fn add_device_timer(
+        &mut self,
+        io_device_id: IODeviceID,
+        timer_device: Box<dyn TimerDevice>,
+        first_trigger: u64,
+    ) {
+        let timer_device_id = self.scheduler.add_device(timer_device);
+
+        self.scheduler
+            .set_device_trigger(timer_device_id, Some(first_trigger));
+
+        self.device_timer_ids.insert(io_device_id, timer_device_id);
+    }
+
     fn process_scanline(&mut self) -> bool {
         self.video.process_scanline(
             self.ic32_latch.get(),
-            |range| self.ram.slice(range),
-            |vsync| self.io_space.on_vsync_change(vsync),
+            |range| self.ram.screen_slice(range),
+            |vsync| self.io_space.on_vsync_change(vsync, self.cycles),
         )
     }
 }
-
-pub const OS_ROM: usize = 16;
-pub const ROMS_LEN: usize = 17;
@@ -0,0 +1,719 @@
+use super::*;
+use crate::devices::StaticDevice;
+use crate::devices::io_device_mock::{IODeviceMock, MemoryAccess};
+use crate::system::address_map::{FnAddressMap, MODEL_B_OS_ROM};
+use crate::via::NoPeripheral;
+use crate::video::MAX_LINES;
+use crate::video::field_line_flags::DISPLAYED;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn it_can_run_on_another_thread() {
+    let mut core = Box::new(Core::default());
+    core.setup();
+
+    let cycles = std::thread::spawn(move || {
+        core.reset();
+        core.run_one_field()
+    })
+    .join()
+    .unwrap();
+
+    assert!(cycles > 0);
+}
+
+#[test]
+fn it_peeks_paged_out_rom_banks() {
+    let mut core = Core::default();
+    core.setup();
+
+    core.poke(MemorySpace::Rom(3), 0x0010, 0x42);
+    core.poke(MemorySpace::Rom(15), 0x0010, 0x24);
+
+    assert_eq!(core.peek(MemorySpace::Rom(3), 0x0010), 0x42);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0x8010), 0x24);
+}
+
+#[test]
+fn it_ignores_peeks_and_pokes_beyond_ram_and_rom_banks() {
+    let mut core = Core::default();
+    core.setup();
+
+    core.poke(MemorySpace::Ram, 0x9000, 0x12);
+    core.poke(MemorySpace::Rom(3), 0x4000, 0x34);
+    core.poke(MemorySpace::Rom(17), 0x0000, 0x56);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x9000), 0xff);
+    assert_eq!(core.peek(MemorySpace::Rom(3), 0x4000), 0xff);
+    assert_eq!(core.peek(MemorySpace::Rom(17), 0x0000), 0xff);
+    assert!((0x8000..=0xffff).all(|address| core.peek(MemorySpace::Ram, address) == 0xff));
+}
+
+#[test]
+fn it_peeks_io_through_the_cpu_view_without_side_effects() {
+    let mut core = Core::default();
+    core.setup();
+
+    core.poke(MemorySpace::Cpu, 0xfe30, 3);
+    core.poke(MemorySpace::Cpu, 0x1234, 0x56);
+
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe30), 15);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe20), 0xfe);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfc00), 0xff);
+    assert_eq!(core.peek(MemorySpace::Ram, 0x1234), 0x56);
+    assert_eq!(core.get_cycles(), 0);
+}
+
+#[test]
+fn it_freezes_cheats_on_cpu_writes_and_field_completion() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x05, // LDA #5
+        0x85, 0x70, // STA &70
+        0x85, 0x71, // STA &71
+        0x4c, 0x00, 0xc0, // JMP &C000
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0; // reset vector &C000
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    *core.cheats_mut() = "0070 u8 9 write Lives\n0071 u8 7 field Energy"
+        .parse()
+        .unwrap();
+
+    core.reset();
+    core.run(1000);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 9);
+    assert_eq!(core.peek(MemorySpace::Ram, 0x71), 5);
+
+    core.run_one_field();
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x71), 7);
+}
+
+#[test]
+fn it_loads_and_runs_a_binary_after_os_initialisation() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    os_rom[..3].copy_from_slice(&[0x4c, 0x00, 0xc0]); // JMP &C000
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    let program = [
+        0xa9, 0x42, // LDA #&42
+        0x85, 0x70, // STA &70
+        0x4c, 0x04, 0x19, // JMP &1904
+    ];
+
+    core.load_binary(&program, 0xffff1900, 0xffff1900).unwrap();
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x1900), 0xa9);
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 0);
+
+    let registers = core.cpu.registers();
+
+    assert_eq!(u16::from(registers.program_counter), 0x1900);
+    assert_eq!(registers.stack_pointer, 0xff);
+    assert!(registers.flags.interrupt_disable);
+    assert!(!registers.flags.decimal_mode);
+
+    core.run_one_field();
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 0x42);
+}
+
+#[test]
+fn it_rejects_binaries_it_cannot_load_before_resetting() {
+    let mut core = Core::default();
+    core.setup();
+
+    core.poke(MemorySpace::Ram, 0x70, 0x42);
+    let cycles = core.get_cycles();
+
+    assert_eq!(
+        core.load_binary(&[0xea], 0x00fe1900, 0xffff1900),
+        Err("Not a host load address: 00fe1900".to_string())
+    );
+    assert_eq!(
+        core.load_binary(&[0xea], 0xffff1900, 0x00031900),
+        Err("Not a host exec address: 00031900".to_string())
+    );
+    assert_eq!(
+        core.load_binary(&[0xea; 0x100], 0xffff7f80, 0xffff7f80),
+        Err("Binary doesn't fit in RAM: 7f80-8080".to_string())
+    );
+    assert_eq!(
+        core.load_binary(&[0xea], 0xffff8000, 0xffff8000),
+        Err("Binary doesn't fit in RAM: 8000-8001".to_string())
+    );
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 0x42);
+    assert_eq!(core.get_cycles(), cycles);
+}
+
+#[test]
+fn it_stretches_one_mhz_accesses_to_whole_one_mhz_cycles() {
+    let mut core = Core::default();
+    core.setup();
+
+    let device = Box::new(IODeviceMock::new(&[(0xfe40, 0)], false));
+    let device_accesses = device.get_accesses();
+    core.add_io_device(
+        IOAddressRange::new(0xfe40, 1),
+        device,
+        None,
+        DeviceSpeed::OneMhz,
+    );
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa2, 0x00, // LDX #0
+        0xad, 0x40, 0xfe, // LDA &FE40
+        0xad, 0x40, 0xfe, // LDA &FE40
+        0x8d, 0x40, 0xfe, // STA &FE40
+        0x9d, 0x40, 0xfe, // STA &FE40,X
+        0x4c, 0x0e, 0xc0, // JMP &C00E
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(100);
+
+    let memory = &device_accesses.borrow().memory;
+    let MemoryAccess::Read(_, first_read) = memory[0] else {
+        panic!("Expected a read: {memory:?}");
+    };
+
+    // bus cycles as listed in the MCS6500 hardware manual (appendix A), stretched by the
+    // Model B's 1MHz cycle stretch logic: 1 extra 2MHz cycle for an access in phase with
+    // the 1MHz clock and 2 out of phase
+    // the access cycle of an absolute LDA or STA following another 1MHz access is out of
+    // phase, so each takes 3us. STA abs,X reads the address before adding the carry in
+    // its 4th cycle, here &FE40 itself, so the device sees a dummy read out of phase
+    // followed by the write in phase, 4us
+    assert_eq!(first_read & 1, 1);
+    assert_eq!(
+        *memory,
+        [
+            MemoryAccess::Read(0xfe40, first_read),
+            MemoryAccess::Read(0xfe40, first_read + 6),
+            MemoryAccess::Write(0xfe40, 0, first_read + 13),
+            MemoryAccess::Read(0xfe40, first_read + 18),
+            MemoryAccess::Write(0xfe40, 0, first_read + 21),
+        ]
+    );
+}
+
+#[test]
+fn it_keeps_ram_and_unwired_devices_through_break_but_not_power_on() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x03, // LDA #3
+        0x8d, 0x30, 0xfe, // STA &FE30
+        0x4c, 0x05, 0xc0, // JMP &C005
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    let device = Box::new(IODeviceMock::new(&[], false));
+    let device_accesses = device.get_accesses();
+    let device_id = core.add_io_device(
+        IOAddressRange::new(0xfe40, 1),
+        device,
+        None,
+        DeviceSpeed::OneMhz,
+    );
+
+    core.reset();
+    core.run(100);
+    core.poke(MemorySpace::Ram, 0x1234, 0x56);
+
+    core.reset();
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x1234), 0x56);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe30), 3);
+    assert_eq!(
+        device_accesses.borrow().resets,
+        [ResetType::Break, ResetType::Break]
+    );
+
+    core.set_device_reset_on_break(device_id, false);
+    core.reset();
+
+    assert_eq!(
+        device_accesses.borrow().resets,
+        [ResetType::Break, ResetType::Break]
+    );
+
+    core.power_on(PowerOnRam::Cleared);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x1234), 0);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe30), 0);
+    assert_eq!(
+        device_accesses.borrow().resets,
+        [ResetType::Break, ResetType::Break, ResetType::PowerOn]
+    );
+}
+
+#[test]
+fn it_keeps_both_vias_through_break_but_not_power_on() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x5a, // LDA #&5A
+        0x8d, 0x42, 0xfe, // STA &FE42 (DDRB)
+        0x8d, 0x62, 0xfe, // STA &FE62
+        0xa9, 0xee, // LDA #&EE
+        0x8d, 0x4c, 0xfe, // STA &FE4C (PCR)
+        0x8d, 0x6c, 0xfe, // STA &FE6C
+        0xa9, 0x82, // LDA #&82
+        0x8d, 0x4e, 0xfe, // STA &FE4E (IER: enable CA1)
+        0x8d, 0x6e, 0xfe, // STA &FE6E
+        0x4c, 0x18, 0xc0, // JMP &C018
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(100);
+
+    core.reset();
+
+    for base in [0xfe40, 0xfe60] {
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x02), 0x5a);
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x0c), 0xee);
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x0e), 0x82);
+    }
+
+    core.power_on(PowerOnRam::Cleared);
+
+    for base in [0xfe40, 0xfe60] {
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x02), 0x00);
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x0c), 0x00);
+        assert_eq!(core.peek(MemorySpace::Cpu, base + 0x0e), 0x80);
+    }
+}
+
+#[test]
+fn it_randomises_ram_repeatably_at_power_on() {
+    let mut first = Core::default();
+    let mut second = Core::default();
+
+    first.power_on(PowerOnRam::Randomised(1234));
+    second.power_on(PowerOnRam::Randomised(1234));
+
+    assert_eq!(first.ram().as_slice(), second.ram().as_slice());
+    assert!(first.ram().as_slice().iter().any(|value| *value != 0));
+}
+
+#[test]
+fn it_tells_devices_about_field_completion() {
+    let mut core = Core::default();
+    core.setup();
+
+    let device = Box::new(IODeviceMock::new(&[], false));
+    let device_accesses = device.get_accesses();
+    core.add_io_device(
+        IOAddressRange::new(0xfe40, 1),
+        device,
+        None,
+        DeviceSpeed::OneMhz,
+    );
+
+    let first_field_end = core.run_one_field();
+    let second_field_end = core.run_one_field();
+
+    assert_eq!(
+        device_accesses.borrow().field_completes,
+        [first_field_end, second_field_end]
+    );
+}
+
+#[test]
+fn it_describes_and_restores_device_state_by_handle() {
+    let mut core = Core::default();
+    core.setup();
+
+    let device_id = core.add_io_device(
+        IOAddressRange::new(0xfe40, 1),
+        Box::new(StaticDevice {
+            read_value: 0x42,
+            panic_on_write: false,
+        }),
+        None,
+        DeviceSpeed::OneMhz,
+    );
+
+    let descriptions: Vec<String> = core
+        .describe_io_devices()
+        .iter()
+        .map(|(_, description)| description.name.clone())
+        .collect();
+
+    assert_eq!(
+        descriptions,
+        [
+            "CRTC",
+            "Video ULA",
+            "ROM select",
+            "6522 VIA",
+            "6522 VIA",
+            "Static"
+        ]
+    );
+    assert_eq!(core.save_io_device_state(device_id), None);
+    assert!(core.load_io_device_state(device_id, &[]).is_err());
+
+    let (rom_select_id, _) = core
+        .describe_io_devices()
+        .into_iter()
+        .find(|(_, description)| description.name == "ROM select")
+        .unwrap();
+
+    assert_eq!(core.save_io_device_state(rom_select_id), Some(vec![15]));
+
+    core.remove_io_device(device_id);
+
+    assert_eq!(core.save_io_device_state(device_id), None);
+    assert!(core.load_io_device_state(device_id, &[]).is_err());
+}
+
+#[test]
+fn it_takes_free_running_via_timer_interrupts() {
+    let mut core = Core::default();
+    core.setup();
+
+    core.add_via(
+        IOAddressRange::mirrored(0xfc60, 0x20, 0x0f),
+        Box::new(NoPeripheral),
+        Some(InterruptType::IRQ),
+    );
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0xc0, // LDA #&C0
+        0x8d, 0x6e, 0xfc, // STA &FC6E (enable T1 interrupts)
+        0xa9, 0x40, // LDA #&40
+        0x8d, 0x6b, 0xfc, // STA &FC6B (T1 free run)
+        0xa9, 0x00, // LDA #0
+        0x8d, 0x64, 0xfc, // STA &FC64
+        0xa9, 0x01, // LDA #1
+        0x8d, 0x65, 0xfc, // STA &FC65 (T1 = &100)
+        0x58, // CLI
+        0x4c, 0x15, 0xc0, // JMP &C015
+        // irq handler at &C018
+        0xad, 0x64, 0xfc, // LDA &FC64
+        0xe6, 0x70, // INC &70
+        0x40, // RTI
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    os_rom[0x3ffe] = 0x18; // irq vector &C018
+    os_rom[0x3fff] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(5000);
+
+    let interrupts = core.peek(MemorySpace::Ram, 0x70);
+
+    // free running period is N + 2us
+    core.run(5000 + 10 * 2 * 0x102);
+
+    assert!(interrupts > 0);
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), interrupts + 10);
+}
+
+#[test]
+fn it_removes_a_via_along_with_its_timer() {
+    let mut core = Core::default();
+    core.setup();
+
+    let (device_id, _) = core.add_via(
+        IOAddressRange::mirrored(0xfc60, 0x20, 0x0f),
+        Box::new(NoPeripheral),
+        Some(InterruptType::IRQ),
+    );
+    let timer_device_id = core.device_timer_ids[&device_id];
+
+    assert!(core.remove_io_device(device_id).is_some());
+
+    assert!(!core.device_timer_ids.contains_key(&device_id));
+    assert!(core.remove_timer_device(timer_device_id).is_none());
+}
+
+#[test]
+fn it_counts_amx_mouse_motion_on_user_via_cb1() {
+    let mut core = Core::default();
+    core.setup();
+    core.plug_amx_mouse();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x90, // LDA #&90
+        0x8d, 0x6e, 0xfe, // STA &FE6E (enable CB1 interrupts, falling edge)
+        0x58, // CLI
+        0x4c, 0x06, 0xc0, // JMP &C006
+        // irq handler at &C009
+        0xad, 0x60, 0xfe, // LDA &FE60 (clears CB1)
+        0x29, 0x01, // AND #1 (x direction)
+        0xf0, 0x04, // BEQ left
+        0xe6, 0x70, // INC &70
+        0xd0, 0x02, // BNE done
+        0xc6, 0x70, // left: DEC &70
+        0x40, // done: RTI
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    os_rom[0x3ffe] = 0x09; // irq vector &C009
+    os_rom[0x3fff] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(1000);
+    core.poke(MemorySpace::Ram, 0x70, 0x80);
+
+    core.mouse_move(5, 0);
+    core.run(20000);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 0x85);
+
+    core.mouse_move(-2, 0);
+    core.run(40000);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x70), 0x83);
+}
+
+#[test]
+fn it_writes_the_slow_data_bus_to_the_sound_chip_as_ic32_enables_it() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let mut program = vec![
+        0xa9, 0xff, // LDA #&FF
+        0x8d, 0x43, 0xfe, // STA &FE43 (port A outputs)
+        0xa9, 0x0f, // LDA #&0F
+        0x8d, 0x42, 0xfe, // STA &FE42 (port B outputs)
+        0xa9, 0x08, // LDA #&08
+        0x8d, 0x40, 0xfe, // STA &FE40 (sound write enable high)
+    ];
+    // channel 0, period &040 at full volume
+    for value in [0x80, 0x04, 0x90] {
+        program.extend([
+            0xa9, value, // LDA #value
+            0x8d, 0x4f, 0xfe, // STA &FE4F
+            0xa9, 0x00, // LDA #0
+            0x8d, 0x40, 0xfe, // STA &FE40 (sound write enable low)
+            0xa9, 0x08, // LDA #&08
+            0x8d, 0x40, 0xfe, // STA &FE40
+        ]);
+    }
+    let end = 0xc000 + program.len() as u16;
+    program.extend([0x4c, end as u8, (end >> 8) as u8]); // JMP to itself
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run_fields(2);
+
+    let sound = core.sound.borrow();
+    let samples = sound.field_samples();
+
+    // a field's worth (40000 cycles, give or take where it splits a sample)
+    // of 64 steps high then 64 low
+    assert!(samples.len().abs_diff(40000 / 64) <= 1);
+    assert!(samples.iter().all(|&sample| sample.abs() == 0.25));
+    assert_eq!(samples.chunk_by(|a, b| a == b).nth(1).unwrap().len(), 8);
+}
+
+#[test]
+fn it_drives_ic32_from_the_system_via_and_flags_vsync_on_ca1() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0xff, // LDA #&FF
+        0x8d, 0x42, 0xfe, // STA &FE42 (port B outputs)
+        0xa9, 0x0c, // LDA #&0C
+        0x8d, 0x40, 0xfe, // STA &FE40 (C0 high)
+        0xa9, 0x0f, // LDA #&0F
+        0x8d, 0x40, 0xfe, // STA &FE40 (shift lock LED off)
+        0x4c, 0x0f, 0xc0, // JMP &C00F
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(100);
+
+    assert_eq!(core.ic32_latch.get(), 0x90);
+    assert_eq!(core.keyboard_leds(), (true, false));
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe4d) & 0x02, 0);
+
+    core.run_one_field();
+
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe4d) & 0x02, 0x02);
+}
+
+#[test]
+fn it_flags_key_presses_on_the_system_via_ca2() {
+    let mut core = Core::default();
+    core.setup();
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x04, // LDA #&04
+        0x8d, 0x4c, 0xfe, // STA &FE4C (CA2 positive edge)
+        0xa9, 0x0f, // LDA #&0F
+        0x8d, 0x42, 0xfe, // STA &FE42
+        0xa9, 0x0b, // LDA #&0B
+        0x8d, 0x40, 0xfe, // STA &FE40 (keyboard scanning)
+        0x4c, 0x0f, 0xc0, // JMP &C00F
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(100);
+
+    core.key_down(BbcKey::SHIFT);
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe4d) & 0x01, 0);
+
+    assert!(core.host_key_down("KeyA", "a"));
+    assert_eq!(core.peek(MemorySpace::Cpu, 0xfe4d) & 0x01, 0x01);
+}
+
+#[test]
+fn it_reads_sectors_from_the_disc_controller_under_nmi() {
+    let mut core = Core::default();
+    core.setup();
+
+    assert!(core.insert_disc(0, &[0; 2560], false, false).is_err());
+
+    core.add_fdc();
+
+    // each sector filled with its track and sector number
+    let disc: Vec<u8> = (0..20)
+        .flat_map(|sector| [(sector / 10 * 0x10 + sector % 10) as u8; 256])
+        .collect();
+    assert!(core.insert_disc(2, &disc, false, false).is_err());
+    assert!(core.insert_disc(0, &disc, false, false).is_ok());
+
+    let mut os_rom = vec![0; MODEL_B_ROM_SIZE];
+    let program = [
+        0xa9, 0x53, // LDA #&53
+        0x8d, 0x80, 0xfe, // STA &FE80 (read data, drive 0)
+        0xa9, 0x01, // LDA #1
+        0x8d, 0x81, 0xfe, // STA &FE81 (track 1)
+        0xa9, 0x02, // LDA #2
+        0x8d, 0x81, 0xfe, // STA &FE81 (sector 2)
+        0xa9, 0x21, // LDA #&21
+        0x8d, 0x81, 0xfe, // STA &FE81 (1 sector of 256 bytes)
+        0x4c, 0x14, 0xc0, // JMP &C014
+        // nmi handler at &C017
+        0x48, // PHA
+        0xad, 0x80, 0xfe, // LDA &FE80
+        0x29, 0x04, // AND #4 (data request)
+        0xf0, 0x0c, // BEQ result
+        0xad, 0x84, 0xfe, // LDA &FE84
+        0xa6, 0x70, // LDX &70
+        0x9d, 0x00, 0x10, // STA &1000,X
+        0xe6, 0x70, // INC &70
+        0x68, // PLA
+        0x40, // RTI
+        0xad, 0x81, 0xfe, // result: LDA &FE81
+        0x85, 0x71, // STA &71
+        0x68, // PLA
+        0x40, // RTI
+    ];
+    os_rom[..program.len()].copy_from_slice(&program);
+    os_rom[0x3ffa] = 0x17; // nmi vector &C017
+    os_rom[0x3ffb] = 0xc0;
+    os_rom[0x3ffd] = 0xc0;
+    core.load_rom(MODEL_B_OS_ROM, &os_rom);
+
+    core.reset();
+    core.run(10);
+    core.poke(MemorySpace::Ram, 0x70, 0);
+    core.poke(MemorySpace::Ram, 0x71, 0xff);
+
+    // up to a revolution for the sector to come round
+    core.run(1_000_000);
+
+    assert_eq!(core.peek(MemorySpace::Ram, 0x71), 0);
+    assert!((0x1000..0x1100).all(|address| core.peek(MemorySpace::Ram, address) == 0x12));
+
+    assert!(!core.is_disc_dirty(0));
+    assert_eq!(core.save_disc(0), Some(disc));
+    assert!(core.eject_disc(0));
+    assert!(!core.eject_disc(0));
+}
+
+#[test]
+fn it_mirrors_screen_memory_through_ram_smaller_than_32k() {
+    // 16k ram mirrored up to &7FFF, as on a model A
+    let mut core = MachineBuilder::new(FnAddressMap {
+        read: |address, _clock, ram, _roms, _io_space| {
+            ram.read(Word::from(u16::from(address) & 0x3fff))
+        },
+        write: |address, value, _clock, ram, _io_space| {
+            ram.write(Word::from(u16::from(address) & 0x3fff), value)
+        },
+    })
+    .ram_size(0x4000)
+    .build();
+
+    core.video.init();
+
+    // each byte holds its page
+    for address in 0..0x4000 {
+        core.poke(MemorySpace::Ram, address, (address >> 8) as u8);
+    }
+    for (address, value) in [
+        (0x0202, 0x4c), // JMP &0202
+        (0x0203, 0x02),
+        (0x0204, 0x02),
+        (0x3ffc, 0x02), // reset vector at &FFFC
+        (0x3ffd, 0x02),
+    ] {
+        core.poke(MemorySpace::Ram, address, value);
+    }
+
+    core.reset();
+    core.run_fields(2);
+
+    // the 20k screen at &3000 continues from &0000 after &4000, so each line starts with
+    // the page it fetched from (rows are &280 bytes, the first byte of raster 0 at offset 16)
+    let first_chars: Vec<u8> = (0..MAX_LINES)
+        .map(|line_index| core.video.field().get_line(line_index).get_raw_data())
+        .filter(|raw_data| raw_data[0] & DISPLAYED != 0)
+        .step_by(8)
+        .map(|raw_data| raw_data[16])
+        .take(9)
+        .collect();
+
+    assert_eq!(
+        first_chars,
+        [0x30, 0x32, 0x35, 0x37, 0x3a, 0x3c, 0x3f, 0x01, 0x04]
+    );
+}
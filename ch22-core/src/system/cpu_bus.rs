@@ -1,74 +1,89 @@
-use std::cell::Cell;
-
-use super::{address_map::AddressMap, clock::Clock, core::ROMS_LEN};
+use super::{address_map::AddressMap, clock::Clock};
 use crate::address_spaces::{IOSpace, Ram, Rom};
+use crate::cheats::CheatList;
 use crate::cpu::{CpuIO, InterruptType};
 use crate::word::Word;
 
 pub struct CpuBus<'a, A: AddressMap> {
     clock: Clock<'a>,
     ram: &'a mut Ram,
-    roms: &'a [Rom; ROMS_LEN],
+    roms: &'a [Rom],
     io_space: &'a mut IOSpace,
-    rom_select_latch: &'a Cell<usize>,
-    address_map: A,
+    address_map: &'a mut A,
+    cheats: &'a CheatList,
 }
 
 impl<'a, A: AddressMap> CpuBus<'a, A> {
     pub fn new(
         clock: Clock<'a>,
         ram: &'a mut Ram,
-        roms: &'a [Rom; ROMS_LEN],
+        roms: &'a [Rom],
         io_space: &'a mut IOSpace,
-        rom_select_latch: &'a Cell<usize>,
-        address_map: A,
+        address_map: &'a mut A,
+        cheats: &'a CheatList,
     ) -> Self {
         Self {
             clock,
             ram,
             roms,
             io_space,
-            rom_select_latch,
             address_map,
+            cheats,
         }
     }
 }
 
 impl<A: AddressMap> CpuIO for CpuBus<'_, A> {
-    fn phantom_read(&mut self, _address: Word) {
-        self.end_previous_cycle();
+    // the cpu puts the address on the bus like any other read, so a dummy read in io
+    // space is a full access: 1MHz devices stretch it and see it as a read
+    fn phantom_read(&mut self, address: Word) {
+        self.read(address);
     }
 
     fn read(&mut self, address: Word) -> u8 {
-        self.end_previous_cycle();
-
-        self.address_map.read(
-            address,
-            &mut self.clock,
-            self.ram,
-            self.roms,
-            self.io_space,
-            self.rom_select_latch,
-        )
+        self.clock.tick();
+
+        let value =
+            self.address_map
+                .read(address, &mut self.clock, self.ram, self.roms, self.io_space);
+
+        self.io_space.set_bus_value(value);
+
+        value
     }
 
     fn write(&mut self, address: Word, value: u8) {
-        self.end_previous_cycle();
+        self.clock.tick();
 
         self.address_map
             .write(address, value, &mut self.clock, self.ram, self.io_space);
+
+        self.io_space.set_bus_value(value);
+
+        self.cheats.on_write(address, self.ram);
     }
 
     fn get_interrupt(&mut self, interrupt_type: InterruptType) -> bool {
+        self.sync_devices();
+
         self.io_space.get_interrupt(interrupt_type, &self.clock)
     }
 }
 
 impl<A: AddressMap> CpuBus<'_, A> {
-    fn end_previous_cycle(&mut self) {
-        self.io_space.phase_2(&self.clock);
+    // cheap unless a phase 2 write is pending or the clock's budget is spent
+    pub fn sync_devices(&mut self) {
+        self.io_space.phase_2();
+
+        self.clock.sync_devices();
+    }
+
+    pub fn refresh_budget(&mut self) {
+        self.clock.refresh_budget();
+    }
 
-        self.clock.inc();
+    pub fn set_instruction_pc(&mut self, pc: Word) {
+        self.io_space.set_instruction_pc(pc);
     }
 
     pub fn get_cycles(&self) -> u64 {
@@ -0,0 +1,97 @@
+use super::{address_map::AddressMap, core::Core};
+use crate::address_spaces::{Ram, Rom};
+use crate::cpu::InterruptType;
+use crate::devices::{DeviceSpeed, IOAddressRange, IODevice, TimerDevice};
+
+// builds a machine around a custom address map
+// the devices' handles aren't kept, so add any device that has to be removed, replaced or
+// signalled later to the built core with add_io_device or add_timer_device instead
+pub struct MachineBuilder<A: AddressMap> {
+    address_map: A,
+    ram_size: usize,
+    roms_len: usize,
+    rom_size: usize,
+    io_devices: Vec<IODeviceEntry>,
+    timer_devices: Vec<(Box<dyn TimerDevice>, Option<u64>)>,
+}
+
+struct IODeviceEntry {
+    address_range: IOAddressRange,
+    device: Box<dyn IODevice>,
+    interrupt_type: Option<InterruptType>,
+    speed: DeviceSpeed,
+}
+
+impl<A: AddressMap> MachineBuilder<A> {
+    pub fn new(address_map: A) -> Self {
+        MachineBuilder {
+            address_map,
+            ram_size: 0,
+            roms_len: 0,
+            rom_size: 0,
+            io_devices: Vec::new(),
+            timer_devices: Vec::new(),
+        }
+    }
+
+    pub fn ram_size(mut self, ram_size: usize) -> Self {
+        self.ram_size = ram_size;
+
+        self
+    }
+
+    pub fn roms(mut self, roms_len: usize, rom_size: usize) -> Self {
+        self.roms_len = roms_len;
+        self.rom_size = rom_size;
+
+        self
+    }
+
+    pub fn io_device(
+        mut self,
+        address_range: impl Into<IOAddressRange>,
+        device: Box<dyn IODevice>,
+        interrupt_type: Option<InterruptType>,
+        speed: DeviceSpeed,
+    ) -> Self {
+        self.io_devices.push(IODeviceEntry {
+            address_range: address_range.into(),
+            device,
+            interrupt_type,
+            speed,
+        });
+
+        self
+    }
+
+    pub fn timer_device(mut self, device: Box<dyn TimerDevice>, trigger: Option<u64>) -> Self {
+        self.timer_devices.push((device, trigger));
+
+        self
+    }
+
+    pub fn build(self) -> Core<A> {
+        let roms = (0..self.roms_len)
+            .map(|_| Rom::new(self.rom_size))
+            .collect();
+
+        let mut core = Core::new(self.address_map, Ram::new(self.ram_size), roms);
+
+        for entry in self.io_devices {
+            core.add_io_device(
+                entry.address_range,
+                entry.device,
+                entry.interrupt_type,
+                entry.speed,
+            );
+        }
+
+        for (device, trigger) in self.timer_devices {
+            let device_id = core.add_timer_device(device);
+
+            core.set_device_trigger(device_id, trigger);
+        }
+
+        core
+    }
+}
@@ -0,0 +1,9 @@
+// an address space that can be peeked or poked by a debugger
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemorySpace {
+    // memory as seen by the cpu through the address map, io reads use IODevice::peek
+    Cpu,
+    Ram,
+    // a rom bank addressed from 0, whether or not it is paged in
+    Rom(usize),
+}
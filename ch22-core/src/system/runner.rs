@@ -14,11 +14,24 @@ pub trait RunnerTrait {
 impl<'a, A: AddressMap> RunnerTrait for Runner<'a, A> {
     fn reset(&mut self) {
         self.cpu.reset(&mut self.cpu_bus);
+
+        self.cpu_bus.sync_devices();
     }
 
+    // ram and rom accesses only count cycles, devices catch up between instructions
+    // once the budget to the next trigger is spent, or sooner when io or interrupts need them
     fn run(&mut self, until: u64) {
+        self.cpu_bus.refresh_budget();
+
         while self.cpu_bus.get_cycles() < until {
+            self.cpu_bus.sync_devices();
+
+            self.cpu_bus
+                .set_instruction_pc(self.cpu.get_program_counter());
+
             self.cpu.handle_next_instruction(&mut self.cpu_bus);
         }
+
+        self.cpu_bus.sync_devices();
     }
 }
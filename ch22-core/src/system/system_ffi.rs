@@ -1,20 +1,27 @@
 use std::mem::size_of;
 
-use js_sys::Function;
 use wasm_bindgen::prelude::*;
 
-use super::core::{Core, ROMS_LEN};
-use crate::cpu::InterruptType;
-use crate::devices::{
-    DeviceSpeed, IODeviceID, JsIODevice, JsTimerDevice, StaticDevice, TimerDeviceID,
-};
+use super::address_map::{MODEL_B_ROMS_LEN, ModelBAddressMap};
+use super::{core::Core, memory_space::MemorySpace};
+use crate::address_spaces::{FloatingBus, IOAccessFilter, PowerOnRam};
+use crate::cheats::{CheatList, CheatSearch, Comparison, ValueType};
+use crate::devices::{DeviceSpeed, IOAddressRange, IODeviceID, StaticDevice, TimerDeviceID};
+use crate::inf_file::InfFile;
+use crate::keyboard::{BbcKey, HostLayout, KeyMapping};
+use crate::sound::SOUND_SAMPLE_RATE;
 use crate::utils;
 use crate::video::Field;
 
+// JS devices are only available when targeting wasm
+#[cfg(target_arch = "wasm32")]
+mod js_devices_ffi;
+
 #[wasm_bindgen(js_name = System)]
 #[derive(Default)]
 pub struct SystemFfi {
-    core: Core,
+    core: Core<ModelBAddressMap>,
+    cheat_search: Option<CheatSearch>,
 }
 
 #[wasm_bindgen(js_class = System)]
@@ -37,99 +44,422 @@ impl SystemFfi {
         size_of::<Field>()
     }
 
+    // the last field's samples, valid until the next field completes
+    pub fn sound_field_start(&self) -> *const f32 {
+        self.core.sound.borrow().field_samples().as_ptr()
+    }
+
+    pub fn sound_field_len(&self) -> usize {
+        self.core.sound.borrow().field_samples().len()
+    }
+
+    pub fn sound_sample_rate(&self) -> u32 {
+        SOUND_SAMPLE_RATE
+    }
+
+    pub fn start_vgm_recording(&mut self) {
+        self.core.start_vgm_recording();
+    }
+
+    pub fn mark_vgm_loop(&mut self) -> bool {
+        self.core.mark_vgm_loop()
+    }
+
+    pub fn stop_vgm_recording(&mut self, title: &str, author: &str) -> Option<Vec<u8>> {
+        self.core.stop_vgm_recording(title, author)
+    }
+
+    pub fn is_recording_vgm(&self) -> bool {
+        self.core.is_recording_vgm()
+    }
+
+    pub fn add_fdc(&mut self) {
+        self.core.add_fdc();
+    }
+
+    pub fn insert_disc(
+        &mut self,
+        drive: usize,
+        data: &[u8],
+        double_sided: bool,
+        write_protected: bool,
+    ) -> Result<(), String> {
+        self.core
+            .insert_disc(drive, data, double_sided, write_protected)
+    }
+
+    pub fn eject_disc(&mut self, drive: usize) -> bool {
+        self.core.eject_disc(drive)
+    }
+
+    pub fn is_disc_dirty(&self, drive: usize) -> bool {
+        self.core.is_disc_dirty(drive)
+    }
+
+    pub fn save_disc(&mut self, drive: usize) -> Option<Vec<u8>> {
+        self.core.save_disc(drive)
+    }
+
     pub fn load_rom(&mut self, bank: usize, data: &[u8]) {
-        if bank >= ROMS_LEN {
-            panic!("Invalid ROM bank: {bank}");
+        self.core.load_rom(bank, data);
+    }
+
+    pub fn load_binary(
+        &mut self,
+        data: &[u8],
+        load_address: u32,
+        exec_address: u32,
+    ) -> Result<(), String> {
+        self.core.load_binary(data, load_address, exec_address)
+    }
+
+    pub fn load_binary_with_inf(&mut self, data: &[u8], inf: &str) -> Result<(), String> {
+        let inf_file: InfFile = inf.parse()?;
+
+        if let Some(length) = inf_file.length
+            && length as usize != data.len()
+        {
+            return Err(format!(
+                "{} is {} bytes, expected {length}",
+                inf_file.name,
+                data.len()
+            ));
         }
 
-        self.core.roms[bank].load(data);
+        self.core
+            .load_binary(data, inf_file.load_address, inf_file.exec_address)
+    }
+
+    pub fn peek(&mut self, space: u8, address: u16) -> Result<u8, String> {
+        Ok(self.core.peek(memory_space(space)?, address))
+    }
+
+    pub fn poke(&mut self, space: u8, address: u16, value: u8) -> Result<(), String> {
+        self.core.poke(memory_space(space)?, address, value);
+
+        Ok(())
+    }
+
+    pub fn cheat_search_start(
+        &mut self,
+        width: usize,
+        bcd: bool,
+        big_endian: bool,
+    ) -> Result<usize, String> {
+        let value_type = ValueType::new(width, bcd, big_endian)?;
+
+        let cheat_search = CheatSearch::new(self.core.ram(), value_type);
+        let candidates_len = cheat_search.candidates().len();
+
+        self.cheat_search = Some(cheat_search);
+
+        Ok(candidates_len)
+    }
+
+    pub fn cheat_search_filter(&mut self, comparison: u8) -> usize {
+        let comparison = match comparison {
+            CHEAT_SEARCH_EQUAL => Comparison::Equal,
+            CHEAT_SEARCH_CHANGED => Comparison::Changed,
+            CHEAT_SEARCH_DECREASED => Comparison::Decreased,
+            CHEAT_SEARCH_INCREASED => Comparison::Increased,
+            _ => panic!("Invalid comparison: {comparison}"),
+        };
+
+        let Some(cheat_search) = &mut self.cheat_search else {
+            panic!("Cheat search not started");
+        };
+
+        cheat_search.filter(self.core.ram(), comparison)
+    }
+
+    pub fn cheat_search_candidates(&self) -> Vec<u16> {
+        self.cheat_search
+            .as_ref()
+            .map(|cheat_search| cheat_search.candidates().to_vec())
+            .unwrap_or_default()
+    }
+
+    pub fn add_cheats(&mut self, text: &str) -> Result<(), String> {
+        let cheat_list: CheatList = text.parse()?;
+
+        self.core.cheats_mut().extend(cheat_list);
+
+        Ok(())
+    }
+
+    pub fn clear_cheats(&mut self) {
+        self.core.cheats_mut().clear();
+    }
+
+    pub fn save_cheats(&self) -> String {
+        self.core.cheats().to_string()
+    }
+
+    pub fn set_floating_bus(&mut self, floating_bus: u8, fixed_value: u8) {
+        let floating_bus = match floating_bus {
+            FLOATING_BUS_FIXED => FloatingBus::Fixed(fixed_value),
+            FLOATING_BUS_LAST_VALUE => FloatingBus::LastBusValue,
+            FLOATING_BUS_OPEN => FloatingBus::OpenBus,
+            _ => panic!("Invalid floating bus: {floating_bus}"),
+        };
+
+        self.core.set_floating_bus(floating_bus);
+    }
+
+    pub fn set_unmapped_logging(&mut self, enabled: bool) {
+        self.core.set_unmapped_logging(enabled);
+    }
+
+    // one access per line, oldest first
+    pub fn take_unmapped_accesses(&mut self) -> String {
+        self.core
+            .take_unmapped_accesses()
+            .iter()
+            .map(|access| format!("{access}\n"))
+            .collect()
+    }
+
+    pub fn set_io_access_logging(&mut self, capacity: usize) {
+        self.core.set_io_access_logging(capacity);
+    }
+
+    // only log accesses to the device (if given) within the addresses (inclusive)
+    pub fn set_io_access_filter(
+        &mut self,
+        device_id: Option<u32>,
+        first_address: u16,
+        last_address: u16,
+    ) {
+        self.core.set_io_access_filter(IOAccessFilter {
+            device_id: device_id.map(IODeviceID::from_bits),
+            addresses: Some(first_address..=last_address),
+        });
+    }
+
+    // one access per line, oldest first
+    pub fn take_io_accesses(&mut self) -> String {
+        self.core
+            .take_io_accesses()
+            .iter()
+            .map(|access| format!("{access}\n"))
+            .collect()
     }
 
     pub fn add_static_device(
         &mut self,
-        addresses: &[u16],
+        base: u16,
+        size: u16,
+        mirror_mask: u16,
         read_value: u8,
         one_mhz: bool,
         panic_on_write: bool,
-    ) -> IODeviceID {
+    ) -> Result<u32, String> {
+        let address_range = IOAddressRange::try_mirrored(base, size, mirror_mask)?;
+
         let speed = match one_mhz {
             true => DeviceSpeed::OneMhz,
             false => DeviceSpeed::TwoMhz,
         };
 
-        self.core.io_space.add_device(
-            addresses,
-            Box::new(StaticDevice {
-                read_value,
-                panic_on_write,
-            }),
-            None,
-            speed,
-        )
+        Ok(self
+            .core
+            .io_space
+            .add_device(
+                address_range,
+                Box::new(StaticDevice {
+                    read_value,
+                    panic_on_write,
+                }),
+                None,
+                speed,
+            )
+            .to_bits())
     }
 
-    pub fn add_js_io_device(
-        &mut self,
-        addresses: &[u16],
-        js_read: Function,
-        js_write: Function,
-        js_on_vsync_change: Option<Function>,
-        js_handle_trigger: Function,
-        flags: u8,
-    ) -> IODeviceID {
-        let interrupt_type = match flags & (JS_DEVICE_IRQ | JS_DEVICE_NMI) {
-            JS_DEVICE_IRQ => Some(InterruptType::IRQ),
-            JS_DEVICE_NMI => Some(InterruptType::NMI),
-            _ => None,
+    pub fn add_tube(&mut self, boot_rom: &[u8]) {
+        self.core.add_tube(boot_rom);
+    }
+
+    pub fn paste_text(&mut self, text: &str) {
+        self.core.paste_text(text);
+    }
+
+    pub fn cancel_paste(&mut self) {
+        self.core.cancel_paste();
+    }
+
+    pub fn is_pasting(&self) -> bool {
+        self.core.is_pasting()
+    }
+
+    pub fn key_down(&mut self, row: u8, column: u8) {
+        self.core.key_down(BbcKey { row, column });
+    }
+
+    pub fn key_up(&mut self, row: u8, column: u8) {
+        self.core.key_up(BbcKey { row, column });
+    }
+
+    pub fn host_key_down(&mut self, code: &str, key: &str) -> bool {
+        self.core.host_key_down(code, key)
+    }
+
+    pub fn host_key_up(&mut self, code: &str) -> bool {
+        self.core.host_key_up(code)
+    }
+
+    pub fn release_all_keys(&mut self) {
+        self.core.release_all_keys();
+    }
+
+    pub fn set_keyboard_layout(&mut self, layout: u8, mapping: u8) {
+        let layout = match layout {
+            KEYBOARD_LAYOUT_UK => HostLayout::Uk,
+            KEYBOARD_LAYOUT_US => HostLayout::Us,
+            _ => panic!("Invalid keyboard layout: {layout}"),
         };
 
-        let speed = match flags & JS_DEVICE_ONE_MHZ {
-            JS_DEVICE_ONE_MHZ => DeviceSpeed::OneMhz,
-            _ => DeviceSpeed::TwoMhz,
+        let mapping = match mapping {
+            KEY_MAPPING_PHYSICAL => KeyMapping::Physical,
+            KEY_MAPPING_LOGICAL => KeyMapping::Logical,
+            _ => panic!("Invalid key mapping: {mapping}"),
         };
 
-        let ic32_latch = self.core.ic32_latch.clone();
-        self.core.io_space.add_device(
-            addresses,
-            Box::new(JsIODevice::new(
-                js_read,
-                js_write,
-                js_on_vsync_change,
-                js_handle_trigger,
-                flags & JS_DEVICE_PHASE_2_WRITE != 0,
-                ic32_latch,
-            )),
-            interrupt_type,
-            speed,
-        )
-    }
-
-    pub fn add_js_timer_device(&mut self, js_handle_trigger: Function) -> TimerDeviceID {
-        self.core
-            .timer_devices
-            .add_device(Box::new(JsTimerDevice::new(js_handle_trigger)))
+        self.core.set_keyboard_layout(layout, mapping);
+    }
+
+    pub fn set_startup_links(&mut self, links: u8) {
+        self.core.set_startup_links(links);
+    }
+
+    // bit 0 caps lock, bit 1 shift lock
+    pub fn get_keyboard_leds(&self) -> u8 {
+        let (caps_lock, shift_lock) = self.core.keyboard_leds();
+
+        caps_lock as u8 | (shift_lock as u8) << 1
+    }
+
+    pub fn plug_amx_mouse(&mut self) {
+        self.core.plug_amx_mouse();
+    }
+
+    pub fn unplug_user_port_device(&mut self) {
+        self.core.unplug_user_port_device();
+    }
+
+    pub fn mouse_move(&mut self, dx: i32, dy: i32) {
+        self.core.mouse_move(dx, dy);
+    }
+
+    // bit 0 left, bit 1 middle, bit 2 right
+    pub fn set_mouse_buttons(&mut self, buttons: u8) {
+        self.core.set_mouse_buttons(buttons);
     }
 
     pub fn reset(&mut self) {
         self.core.reset();
     }
 
+    // ram is cleared without a seed
+    pub fn power_on(&mut self, random_seed: Option<u32>) {
+        let ram_contents = match random_seed {
+            Some(seed) => PowerOnRam::Randomised(seed as u64),
+            None => PowerOnRam::Cleared,
+        };
+
+        self.core.power_on(ram_contents);
+    }
+
     pub fn run_one_field(&mut self) -> u64 {
         self.core.run_one_field()
     }
 
-    pub fn set_device_interrupt(&mut self, device_id: IODeviceID, interrupt: bool) {
-        self.core.io_space.set_interrupt(device_id, interrupt);
+    pub fn run_fields(&mut self, field_count: u32) -> u64 {
+        self.core.run_fields(field_count)
+    }
+
+    pub fn set_warp_mode(&mut self, snapshot_field_interval: Option<u32>) {
+        self.core.video.set_warp(snapshot_field_interval);
     }
 
-    pub fn set_device_trigger(&mut self, device_id: TimerDeviceID, trigger: Option<u64>) {
+    // device handles are passed to JS as bits, see DeviceHandle::to_bits
+
+    pub fn remove_io_device(&mut self, device_id: u32) -> bool {
         self.core
-            .timer_devices
-            .set_device_trigger(device_id, trigger);
+            .remove_io_device(IODeviceID::from_bits(device_id))
+            .is_some()
+    }
+
+    pub fn remove_timer_device(&mut self, device_id: u32) -> bool {
+        self.core
+            .remove_timer_device(TimerDeviceID::from_bits(device_id))
+            .is_some()
+    }
+
+    pub fn set_device_interrupt(&mut self, device_id: u32, interrupt: bool) -> bool {
+        self.core
+            .set_device_interrupt(IODeviceID::from_bits(device_id), interrupt)
+    }
+
+    // one device per line, its id then name and registers
+    pub fn describe_io_devices(&self) -> String {
+        self.core
+            .describe_io_devices()
+            .iter()
+            .map(|(device_id, description)| format!("{} {description}\n", device_id.to_bits()))
+            .collect()
+    }
+
+    pub fn save_io_device_state(&self, device_id: u32) -> Option<Vec<u8>> {
+        self.core
+            .save_io_device_state(IODeviceID::from_bits(device_id))
+    }
+
+    pub fn load_io_device_state(&mut self, device_id: u32, state: &[u8]) -> Result<(), String> {
+        self.core
+            .load_io_device_state(IODeviceID::from_bits(device_id), state)
+    }
+
+    pub fn set_device_reset_on_break(&mut self, device_id: u32, reset_on_break: bool) -> bool {
+        self.core
+            .set_device_reset_on_break(IODeviceID::from_bits(device_id), reset_on_break)
+    }
+
+    pub fn set_device_trigger(&mut self, device_id: u32, trigger: Option<u64>) -> bool {
+        self.core
+            .set_device_trigger(TimerDeviceID::from_bits(device_id), trigger)
+    }
+
+    pub fn set_device_period(&mut self, device_id: u32, first_trigger: u64, period: u64) -> bool {
+        self.core
+            .set_device_period(TimerDeviceID::from_bits(device_id), first_trigger, period)
+    }
+}
+
+fn memory_space(space: u8) -> Result<MemorySpace, String> {
+    match space {
+        MEMORY_SPACE_CPU => Ok(MemorySpace::Cpu),
+        MEMORY_SPACE_RAM => Ok(MemorySpace::Ram),
+        bank if (bank as usize) < MODEL_B_ROMS_LEN => Ok(MemorySpace::Rom(bank as usize)),
+        _ => Err(format!("Invalid memory space: {space}")),
     }
 }
 
-const JS_DEVICE_ONE_MHZ: u8 = 0b0000_0001;
-const JS_DEVICE_NMI: u8 = 0b0000_0010;
-const JS_DEVICE_IRQ: u8 = 0b0000_0100;
-const JS_DEVICE_PHASE_2_WRITE: u8 = 0b0001_0000;
+// values below MODEL_B_ROMS_LEN select a rom bank
+const MEMORY_SPACE_CPU: u8 = 0x80;
+const MEMORY_SPACE_RAM: u8 = 0x81;
+
+const CHEAT_SEARCH_EQUAL: u8 = 0;
+const CHEAT_SEARCH_CHANGED: u8 = 1;
+const CHEAT_SEARCH_DECREASED: u8 = 2;
+const CHEAT_SEARCH_INCREASED: u8 = 3;
+
+const FLOATING_BUS_FIXED: u8 = 0;
+const FLOATING_BUS_LAST_VALUE: u8 = 1;
+const FLOATING_BUS_OPEN: u8 = 2;
+
+const KEYBOARD_LAYOUT_UK: u8 = 0;
+const KEYBOARD_LAYOUT_US: u8 = 1;
+
+const KEY_MAPPING_PHYSICAL: u8 = 0;
+const KEY_MAPPING_LOGICAL: u8 = 1;
@@ -0,0 +1,108 @@
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use super::SystemFfi;
+use crate::cpu::InterruptType;
+use crate::devices::{
+    DeviceSpeed, IOAddressRange, IODeviceID, JsIODevice, JsTimerDevice, TimerDeviceID,
+};
+
+#[wasm_bindgen(js_class = System)]
+impl SystemFfi {
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_js_io_device(
+        &mut self,
+        base: u16,
+        size: u16,
+        mirror_mask: u16,
+        js_read: Function,
+        js_write: Function,
+        js_on_vsync_change: Option<Function>,
+        js_handle_trigger: Function,
+        flags: u8,
+        js_reset: Option<Function>,
+    ) -> Result<u32, String> {
+        let address_range = IOAddressRange::try_mirrored(base, size, mirror_mask)?;
+
+        let interrupt_type = match flags & (JS_DEVICE_IRQ | JS_DEVICE_NMI) {
+            JS_DEVICE_IRQ => Some(InterruptType::IRQ),
+            JS_DEVICE_NMI => Some(InterruptType::NMI),
+            _ => None,
+        };
+
+        let speed = match flags & JS_DEVICE_ONE_MHZ {
+            JS_DEVICE_ONE_MHZ => DeviceSpeed::OneMhz,
+            _ => DeviceSpeed::TwoMhz,
+        };
+
+        Ok(self
+            .core
+            .io_space
+            .add_device(
+                address_range,
+                Box::new(JsIODevice::new(
+                    js_read,
+                    js_write,
+                    js_on_vsync_change,
+                    js_handle_trigger,
+                    js_reset,
+                    flags & JS_DEVICE_PHASE_2_WRITE != 0,
+                )),
+                interrupt_type,
+                speed,
+            )
+            .to_bits())
+    }
+
+    pub fn add_js_timer_device(&mut self, js_handle_trigger: Function) -> u32 {
+        self.core
+            .scheduler
+            .add_device(Box::new(JsTimerDevice::new(js_handle_trigger)))
+            .to_bits()
+    }
+
+    // swaps the callbacks keeping the handle, addresses, interrupt type and speed,
+    // so only the phase 2 flag is taken from flags
+    // false if the device has been removed
+    #[allow(clippy::too_many_arguments)]
+    pub fn replace_js_io_device(
+        &mut self,
+        device_id: u32,
+        js_read: Function,
+        js_write: Function,
+        js_on_vsync_change: Option<Function>,
+        js_handle_trigger: Function,
+        flags: u8,
+        js_reset: Option<Function>,
+    ) -> bool {
+        self.core
+            .replace_io_device(
+                IODeviceID::from_bits(device_id),
+                Box::new(JsIODevice::new(
+                    js_read,
+                    js_write,
+                    js_on_vsync_change,
+                    js_handle_trigger,
+                    js_reset,
+                    flags & JS_DEVICE_PHASE_2_WRITE != 0,
+                )),
+            )
+            .is_some()
+    }
+
+    // swaps the callback keeping the handle and any pending trigger
+    // false if the device has been removed
+    pub fn replace_js_timer_device(&mut self, device_id: u32, js_handle_trigger: Function) -> bool {
+        self.core
+            .replace_timer_device(
+                TimerDeviceID::from_bits(device_id),
+                Box::new(JsTimerDevice::new(js_handle_trigger)),
+            )
+            .is_some()
+    }
+}
+
+const JS_DEVICE_ONE_MHZ: u8 = 0b0000_0001;
+const JS_DEVICE_NMI: u8 = 0b0000_0010;
+const JS_DEVICE_IRQ: u8 = 0b0000_0100;
+const JS_DEVICE_PHASE_2_WRITE: u8 = 0b0001_0000;
@@ -0,0 +1,42 @@
+mod parasite_bus;
+mod second_processor;
+mod tube_host_device;
+mod tube_timer_device;
+mod tube_ula;
+
+use crate::devices::IOAddressRange;
+use crate::shared::Shared;
+use second_processor::SecondProcessor;
+use tube_host_device::TubeHostDevice;
+use tube_timer_device::TubeTimerDevice;
+
+pub use tube_timer_device::TUBE_SYNC_INTERVAL;
+
+// 8 registers repeated across &FEE0-&FEFF
+pub const TUBE_ADDRESS_RANGE: IOAddressRange = IOAddressRange {
+    base: 0xfee0,
+    size: 0x20,
+    mirror_mask: 0x07,
+};
+
+pub const PARASITE_CLOCK_MHZ: u64 = 3;
+
+pub struct Tube {
+    second_processor: Shared<SecondProcessor>,
+}
+
+impl Tube {
+    pub fn new(boot_rom: &[u8], clock_mhz: u64, host_cycles: u64) -> Self {
+        Tube {
+            second_processor: Shared::new(SecondProcessor::new(boot_rom, clock_mhz, host_cycles)),
+        }
+    }
+
+    pub fn create_host_device(&self) -> TubeHostDevice {
+        TubeHostDevice::new(self.second_processor.clone())
+    }
+
+    pub fn create_timer_device(&self) -> TubeTimerDevice {
+        TubeTimerDevice::new(self.second_processor.clone())
+    }
+}
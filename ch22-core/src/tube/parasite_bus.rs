@@ -0,0 +1,68 @@
+use super::tube_ula::TubeUla;
+use crate::cpu::{CpuIO, InterruptType};
+use crate::word::Word;
+
+pub struct ParasiteBus<'a> {
+    pub ula: &'a mut TubeUla,
+    pub ram: &'a mut [u8],
+    pub boot_rom: &'a [u8],
+    pub rom_paged_in: &'a mut bool,
+    pub cycles: &'a mut u64,
+}
+
+impl CpuIO for ParasiteBus<'_> {
+    fn phantom_read(&mut self, _address: Word) {
+        *self.cycles += 1;
+    }
+
+    fn read(&mut self, address: Word) -> u8 {
+        *self.cycles += 1;
+
+        let address: u16 = address.into();
+
+        if is_tube_register(address) {
+            // any access to the tube registers pages out the boot rom
+            *self.rom_paged_in = false;
+
+            self.ula.parasite_read(address as u8 & 0x07)
+        } else if *self.rom_paged_in && address as usize >= self.rom_start() {
+            self.boot_rom[address as usize - self.rom_start()]
+        } else {
+            self.ram[address as usize]
+        }
+    }
+
+    fn write(&mut self, address: Word, value: u8) {
+        *self.cycles += 1;
+
+        let address: u16 = address.into();
+
+        if is_tube_register(address) {
+            *self.rom_paged_in = false;
+
+            self.ula.parasite_write(address as u8 & 0x07, value);
+        } else {
+            self.ram[address as usize] = value;
+        }
+    }
+
+    fn get_interrupt(&mut self, interrupt_type: InterruptType) -> bool {
+        match interrupt_type {
+            InterruptType::IRQ => self.ula.parasite_irq(),
+            InterruptType::NMI => self.ula.parasite_nmi(),
+        }
+    }
+}
+
+impl ParasiteBus<'_> {
+    fn rom_start(&self) -> usize {
+        PARASITE_RAM_SIZE - self.boot_rom.len()
+    }
+}
+
+fn is_tube_register(address: u16) -> bool {
+    address & 0xfff8 == PARASITE_TUBE_REGISTERS_START
+}
+
+pub const PARASITE_RAM_SIZE: usize = 0x10000;
+const PARASITE_TUBE_REGISTERS_START: u16 = 0xfef8;
@@ -0,0 +1,99 @@
+use super::parasite_bus::{PARASITE_RAM_SIZE, ParasiteBus};
+use super::tube_ula::TubeUla;
+use crate::cpu::Cpu;
+
+#[cfg(test)]
+mod tests;
+
+pub struct SecondProcessor {
+    ula: TubeUla,
+    cpu: Cpu,
+    ram: Box<[u8]>,
+    boot_rom: Box<[u8]>,
+    rom_paged_in: bool,
+    cycles: u64,
+    clock_mhz: u64,
+    held_in_reset: bool,
+}
+
+impl SecondProcessor {
+    pub fn new(boot_rom: &[u8], clock_mhz: u64, host_cycles: u64) -> Self {
+        if boot_rom.is_empty() || boot_rom.len() > MAX_BOOT_ROM_SIZE {
+            panic!("Invalid tube boot ROM size: {}", boot_rom.len());
+        }
+
+        SecondProcessor {
+            ula: TubeUla::default(),
+            cpu: Cpu::default(),
+            ram: vec![0; PARASITE_RAM_SIZE].into_boxed_slice(),
+            boot_rom: boot_rom.into(),
+            rom_paged_in: true,
+            cycles: host_cycles * clock_mhz / HOST_CLOCK_MHZ,
+            clock_mhz,
+            held_in_reset: true,
+        }
+    }
+
+    // runs the parasite until it has caught up with the host
+    pub fn sync(&mut self, host_cycles: u64) {
+        let target_cycles = host_cycles * self.clock_mhz / HOST_CLOCK_MHZ;
+
+        if self.ula.is_parasite_reset() {
+            self.held_in_reset = true;
+            self.cycles = target_cycles;
+
+            return;
+        }
+
+        if self.held_in_reset {
+            self.held_in_reset = false;
+            self.rom_paged_in = true;
+
+            let (cpu, mut bus) = self.cpu_and_bus();
+            cpu.reset(&mut bus);
+        }
+
+        while self.cycles < target_cycles {
+            let (cpu, mut bus) = self.cpu_and_bus();
+            cpu.handle_next_instruction(&mut bus);
+        }
+    }
+
+    // the parasite restarts on the next sync
+    pub fn host_reset(&mut self) {
+        self.ula.reset();
+        self.held_in_reset = true;
+    }
+
+    pub fn host_read(&mut self, register: u8) -> u8 {
+        self.ula.host_read(register)
+    }
+
+    pub fn host_peek(&self, register: u8) -> u8 {
+        self.ula.host_peek(register)
+    }
+
+    pub fn host_write(&mut self, register: u8, value: u8) {
+        self.ula.host_write(register, value);
+    }
+
+    pub fn host_irq(&self) -> bool {
+        self.ula.host_irq()
+    }
+
+    fn cpu_and_bus(&mut self) -> (&mut Cpu, ParasiteBus<'_>) {
+        (
+            &mut self.cpu,
+            ParasiteBus {
+                ula: &mut self.ula,
+                ram: &mut self.ram,
+                boot_rom: &self.boot_rom,
+                rom_paged_in: &mut self.rom_paged_in,
+                cycles: &mut self.cycles,
+            },
+        )
+    }
+}
+
+const HOST_CLOCK_MHZ: u64 = 2;
+const MAX_BOOT_ROM_SIZE: usize = 0x1000;
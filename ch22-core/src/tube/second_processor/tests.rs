@@ -0,0 +1,73 @@
+use super::*;
+
+// boot rom at 0xf800, reset vector points to start of rom
+fn create_boot_rom(program: &[u8]) -> Vec<u8> {
+    let mut boot_rom = vec![0xea; 0x800]; // NOP
+
+    boot_rom[..program.len()].copy_from_slice(program);
+
+    boot_rom[0x7fc] = 0x00;
+    boot_rom[0x7fd] = 0xf8;
+
+    boot_rom
+}
+
+#[test]
+fn it_runs_the_parasite_at_its_own_clock_rate() {
+    let mut second_processor = SecondProcessor::new(&create_boot_rom(&[]), 3, 0);
+
+    second_processor.sync(1000);
+    second_processor.sync(2000);
+
+    // 2 cycle NOPs may overrun the target by a cycle
+    assert!((3000..3002).contains(&second_processor.cycles));
+}
+
+#[test]
+fn it_passes_data_written_by_the_parasite_to_the_host() {
+    let program = [
+        0xa9, 0x41, // LDA #&41
+        0x8d, 0xf9, 0xfe, // STA &FEF9
+        0x4c, 0x05, 0xf8, // JMP &F805
+    ];
+    let mut second_processor = SecondProcessor::new(&create_boot_rom(&program), 3, 0);
+
+    second_processor.sync(100);
+
+    assert_eq!(second_processor.host_read(0) & 0x80, 0x80);
+    assert_eq!(second_processor.host_read(1), 0x41);
+}
+
+#[test]
+fn it_pages_out_the_boot_rom_after_tube_access() {
+    let program = [
+        0xad, 0xf8, 0xfe, // LDA &FEF8
+        0x4c, 0x03, 0xf8, // JMP &F803
+    ];
+    let mut second_processor = SecondProcessor::new(&create_boot_rom(&program), 3, 0);
+
+    assert!(second_processor.rom_paged_in);
+
+    second_processor.sync(100);
+
+    assert!(!second_processor.rom_paged_in);
+}
+
+#[test]
+fn it_holds_the_parasite_in_reset_while_p_flag_set() {
+    let mut second_processor = SecondProcessor::new(&create_boot_rom(&[]), 3, 0);
+
+    second_processor.host_write(0, 0xa0); // set P
+
+    second_processor.sync(1000);
+
+    assert_eq!(second_processor.cycles, 1500);
+    assert!(second_processor.held_in_reset);
+
+    second_processor.host_write(0, 0x20); // clear P
+
+    second_processor.sync(2000);
+
+    assert!(!second_processor.held_in_reset);
+    assert!(second_processor.cycles >= 3000);
+}
@@ -0,0 +1,67 @@
+use super::second_processor::SecondProcessor;
+use crate::devices::{DeviceDescription, IODevice, ResetType};
+use crate::shared::Shared;
+use crate::word::Word;
+
+pub struct TubeHostDevice {
+    second_processor: Shared<SecondProcessor>,
+}
+
+impl TubeHostDevice {
+    pub fn new(second_processor: Shared<SecondProcessor>) -> Self {
+        TubeHostDevice { second_processor }
+    }
+}
+
+impl IODevice for TubeHostDevice {
+    fn read(&mut self, _address: Word, register: u16, cycles: u64) -> u8 {
+        let mut second_processor = self.second_processor.borrow_mut();
+
+        second_processor.sync(cycles);
+
+        second_processor.host_read(register as u8)
+    }
+
+    fn write(&mut self, _address: Word, register: u16, value: u8, cycles: u64) -> bool {
+        let mut second_processor = self.second_processor.borrow_mut();
+
+        second_processor.sync(cycles);
+
+        second_processor.host_write(register as u8, value);
+
+        false
+    }
+
+    fn get_interrupt(&mut self, cycles: u64) -> bool {
+        let mut second_processor = self.second_processor.borrow_mut();
+
+        second_processor.sync(cycles);
+
+        second_processor.host_irq()
+    }
+
+    fn peek(&self, _address: Word, register: u16) -> Option<u8> {
+        Some(self.second_processor.borrow().host_peek(register as u8))
+    }
+
+    // host RES resets the ula, which holds the parasite in reset
+    fn reset(&mut self, _reset_type: ResetType) {
+        self.second_processor.borrow_mut().host_reset();
+    }
+
+    // state isn't saved, the parasite would need to be snapshotted with it
+    fn describe(&self) -> Option<DeviceDescription> {
+        let second_processor = self.second_processor.borrow();
+
+        Some(DeviceDescription {
+            name: "Tube".to_string(),
+            registers: (1..=4)
+                .map(|index| {
+                    let status = second_processor.host_peek((index - 1) * 2);
+
+                    (format!("R{index}STAT"), status as u16)
+                })
+                .collect(),
+        })
+    }
+}
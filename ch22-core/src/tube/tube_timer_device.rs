@@ -0,0 +1,24 @@
+use super::second_processor::SecondProcessor;
+use crate::devices::TimerDevice;
+use crate::shared::Shared;
+
+pub struct TubeTimerDevice {
+    second_processor: Shared<SecondProcessor>,
+}
+
+impl TubeTimerDevice {
+    pub fn new(second_processor: Shared<SecondProcessor>) -> Self {
+        TubeTimerDevice { second_processor }
+    }
+}
+
+impl TimerDevice for TubeTimerDevice {
+    fn sync(&mut self, cycles: u64) -> Option<u64> {
+        self.second_processor.borrow_mut().sync(cycles);
+
+        Some(cycles + TUBE_SYNC_INTERVAL)
+    }
+}
+
+// maximum number of host cycles the parasite can lag behind
+pub const TUBE_SYNC_INTERVAL: u64 = 16;
@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod tests;
+
+pub struct TubeUla {
+    flags: u8,
+    host_to_parasite: [TubeFifo; 4],
+    parasite_to_host: [TubeFifo; 4],
+}
+
+impl Default for TubeUla {
+    fn default() -> Self {
+        TubeUla {
+            flags: 0,
+            host_to_parasite: [
+                TubeFifo::new(1),
+                TubeFifo::new(1),
+                TubeFifo::new(1),
+                TubeFifo::new(1),
+            ],
+            parasite_to_host: [
+                TubeFifo::new(R1_PARASITE_TO_HOST_LEN),
+                TubeFifo::new(1),
+                TubeFifo::new(1),
+                TubeFifo::new(1),
+            ],
+        }
+    }
+}
+
+impl TubeUla {
+    pub fn reset(&mut self) {
+        *self = TubeUla::default();
+    }
+
+    pub fn host_read(&mut self, register: u8) -> u8 {
+        let index = (register >> 1) as usize & 0x03;
+
+        if register & 0x01 == 0 {
+            self.host_status(index)
+        } else {
+            self.parasite_to_host[index].pop()
+        }
+    }
+
+    // as host_read but leaves the data registers untouched
+    pub fn host_peek(&self, register: u8) -> u8 {
+        let index = (register >> 1) as usize & 0x03;
+
+        if register & 0x01 == 0 {
+            self.host_status(index)
+        } else {
+            self.parasite_to_host[index].peek()
+        }
+    }
+
+    pub fn host_write(&mut self, register: u8, value: u8) {
+        let index = (register >> 1) as usize & 0x03;
+
+        if register & 0x01 == 0 {
+            if index == 0 {
+                self.write_control(value);
+            }
+        } else {
+            self.host_to_parasite[index].push(value);
+        }
+    }
+
+    pub fn parasite_read(&mut self, register: u8) -> u8 {
+        let index = (register >> 1) as usize & 0x03;
+
+        if register & 0x01 == 0 {
+            let status = status_bits(&self.host_to_parasite[index], &self.parasite_to_host[index]);
+
+            if index == 0 {
+                status | (self.flags & STATUS_FLAGS_MASK)
+            } else {
+                status
+            }
+        } else {
+            self.host_to_parasite[index].pop()
+        }
+    }
+
+    pub fn parasite_write(&mut self, register: u8, value: u8) {
+        let index = (register >> 1) as usize & 0x03;
+
+        if register & 0x01 != 0 {
+            self.parasite_to_host[index].push(value);
+        }
+    }
+
+    pub fn host_irq(&self) -> bool {
+        self.flags & FLAG_Q != 0 && self.parasite_to_host[3].has_data()
+    }
+
+    pub fn parasite_irq(&self) -> bool {
+        (self.flags & FLAG_I != 0 && self.host_to_parasite[0].has_data())
+            || (self.flags & FLAG_J != 0 && self.host_to_parasite[3].has_data())
+    }
+
+    pub fn parasite_nmi(&self) -> bool {
+        // asserted when the parasite can either read or write register 3
+        self.flags & FLAG_M != 0
+            && (self.host_to_parasite[2].has_data() || self.parasite_to_host[2].is_empty())
+    }
+
+    pub fn is_parasite_reset(&self) -> bool {
+        self.flags & FLAG_P != 0
+    }
+
+    fn host_status(&self, index: usize) -> u8 {
+        let status = status_bits(&self.parasite_to_host[index], &self.host_to_parasite[index]);
+
+        if index == 0 {
+            status | (self.flags & STATUS_FLAGS_MASK)
+        } else {
+            status
+        }
+    }
+
+    fn write_control(&mut self, value: u8) {
+        if value & FLAG_S != 0 {
+            self.flags |= value & !FLAG_S;
+        } else {
+            self.flags &= !value;
+        }
+
+        if self.flags & FLAG_T != 0 {
+            let flags = self.flags & !FLAG_T;
+
+            self.reset();
+
+            self.flags = flags;
+        }
+
+        let r3_handshake_len = if self.flags & FLAG_V != 0 { 2 } else { 1 };
+
+        self.host_to_parasite[2].set_handshake_len(r3_handshake_len);
+        self.parasite_to_host[2].set_handshake_len(r3_handshake_len);
+    }
+}
+
+fn status_bits(incoming: &TubeFifo, outgoing: &TubeFifo) -> u8 {
+    (if incoming.has_data() {
+        STATUS_DATA_AVAILABLE
+    } else {
+        0
+    }) | (if outgoing.has_space() {
+        STATUS_NOT_FULL
+    } else {
+        0
+    })
+}
+
+struct TubeFifo {
+    data: VecDeque<u8>,
+    capacity: usize,
+    // number of bytes transferred per handshake, 2 for register 3 in two byte mode
+    handshake_len: usize,
+    last_value: u8,
+}
+
+impl TubeFifo {
+    fn new(capacity: usize) -> Self {
+        TubeFifo {
+            data: VecDeque::with_capacity(capacity),
+            capacity,
+            handshake_len: 1,
+            last_value: 0,
+        }
+    }
+
+    fn set_handshake_len(&mut self, handshake_len: usize) {
+        self.handshake_len = handshake_len;
+        self.capacity = handshake_len;
+        self.data.truncate(handshake_len);
+    }
+
+    fn has_data(&self) -> bool {
+        self.data.len() >= self.handshake_len
+    }
+
+    fn has_space(&self) -> bool {
+        self.data.len() + self.handshake_len <= self.capacity
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn push(&mut self, value: u8) {
+        if self.data.len() < self.capacity {
+            self.data.push_back(value);
+        }
+    }
+
+    fn peek(&self) -> u8 {
+        self.data.front().copied().unwrap_or(self.last_value)
+    }
+
+    fn pop(&mut self) -> u8 {
+        if let Some(value) = self.data.pop_front() {
+            self.last_value = value;
+        }
+
+        self.last_value
+    }
+}
+
+const R1_PARASITE_TO_HOST_LEN: usize = 24;
+
+const STATUS_DATA_AVAILABLE: u8 = 0x80;
+const STATUS_NOT_FULL: u8 = 0x40;
+const STATUS_FLAGS_MASK: u8 = 0x3f;
+
+pub const FLAG_Q: u8 = 0x01; // host irq from register 4
+pub const FLAG_I: u8 = 0x02; // parasite irq from register 1
+pub const FLAG_J: u8 = 0x04; // parasite irq from register 4
+pub const FLAG_M: u8 = 0x08; // parasite nmi from register 3
+pub const FLAG_V: u8 = 0x10; // two byte register 3
+pub const FLAG_P: u8 = 0x20; // parasite reset
+pub const FLAG_T: u8 = 0x40; // clear all registers
+pub const FLAG_S: u8 = 0x80; // set (or clear) the selected flags
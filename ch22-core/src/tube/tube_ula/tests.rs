@@ -0,0 +1,172 @@
+use super::*;
+
+#[test]
+fn it_passes_data_from_parasite_to_host_through_register_1_fifo() {
+    let mut ula = TubeUla::default();
+
+    for value in 0..24 {
+        ula.parasite_write(1, value);
+    }
+
+    // fifo now full
+    assert_eq!(ula.parasite_read(0) & STATUS_NOT_FULL, 0);
+    assert_eq!(
+        ula.host_read(0) & STATUS_DATA_AVAILABLE,
+        STATUS_DATA_AVAILABLE
+    );
+
+    for value in 0..24 {
+        assert_eq!(ula.host_read(1), value);
+    }
+
+    assert_eq!(ula.host_read(0) & STATUS_DATA_AVAILABLE, 0);
+    assert_eq!(ula.parasite_read(0) & STATUS_NOT_FULL, STATUS_NOT_FULL);
+}
+
+#[test]
+fn it_passes_single_bytes_from_host_to_parasite() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(5, 0x42);
+
+    assert_eq!(ula.host_read(4) & STATUS_NOT_FULL, 0);
+    assert_eq!(
+        ula.parasite_read(4) & STATUS_DATA_AVAILABLE,
+        STATUS_DATA_AVAILABLE
+    );
+    assert_eq!(ula.parasite_read(5), 0x42);
+    assert_eq!(ula.host_read(4) & STATUS_NOT_FULL, STATUS_NOT_FULL);
+}
+
+#[test]
+fn it_sets_and_clears_control_flags() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_Q | FLAG_I | FLAG_J);
+
+    assert_eq!(
+        ula.host_read(0) & STATUS_FLAGS_MASK,
+        FLAG_Q | FLAG_I | FLAG_J
+    );
+
+    ula.host_write(0, FLAG_I);
+
+    assert_eq!(ula.host_read(0) & STATUS_FLAGS_MASK, FLAG_Q | FLAG_J);
+}
+
+#[test]
+fn it_clears_all_registers_when_t_flag_set() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_Q);
+    ula.parasite_write(1, 0x11);
+    ula.host_write(7, 0x22);
+
+    ula.host_write(0, FLAG_S | FLAG_T);
+
+    assert_eq!(ula.host_read(0), STATUS_NOT_FULL | FLAG_Q);
+    assert_eq!(ula.parasite_read(6), STATUS_NOT_FULL);
+}
+
+#[test]
+fn it_raises_host_irq_from_register_4_when_q_set() {
+    let mut ula = TubeUla::default();
+
+    ula.parasite_write(7, 0x01);
+
+    assert!(!ula.host_irq());
+
+    ula.host_write(0, FLAG_S | FLAG_Q);
+
+    assert!(ula.host_irq());
+
+    ula.host_read(7);
+
+    assert!(!ula.host_irq());
+}
+
+#[test]
+fn it_raises_parasite_irq_from_registers_1_and_4() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_I | FLAG_J);
+
+    ula.host_write(1, 0x01);
+    assert!(ula.parasite_irq());
+    ula.parasite_read(1);
+    assert!(!ula.parasite_irq());
+
+    ula.host_write(7, 0x01);
+    assert!(ula.parasite_irq());
+    ula.parasite_read(7);
+    assert!(!ula.parasite_irq());
+}
+
+#[test]
+fn it_raises_parasite_nmi_from_register_3_in_one_byte_mode() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_M);
+
+    // parasite can write
+    assert!(ula.parasite_nmi());
+
+    ula.parasite_write(5, 0x01);
+    assert!(!ula.parasite_nmi());
+
+    // parasite can read
+    ula.host_write(5, 0x02);
+    assert!(ula.parasite_nmi());
+}
+
+#[test]
+fn it_transfers_register_3_in_pairs_in_two_byte_mode() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_M | FLAG_V);
+
+    ula.parasite_write(5, 0x01);
+    assert!(!ula.parasite_nmi());
+    assert_eq!(ula.host_read(4) & STATUS_DATA_AVAILABLE, 0);
+
+    ula.parasite_write(5, 0x02);
+    assert_eq!(
+        ula.host_read(4) & STATUS_DATA_AVAILABLE,
+        STATUS_DATA_AVAILABLE
+    );
+
+    ula.host_write(5, 0x03);
+    assert!(!ula.parasite_nmi());
+    ula.host_write(5, 0x04);
+    assert!(ula.parasite_nmi());
+
+    assert_eq!(ula.host_read(5), 0x01);
+    assert_eq!(ula.host_read(5), 0x02);
+    assert_eq!(ula.parasite_read(5), 0x03);
+    assert_eq!(ula.parasite_read(5), 0x04);
+}
+
+#[test]
+fn it_holds_the_parasite_in_reset_while_p_set() {
+    let mut ula = TubeUla::default();
+
+    ula.host_write(0, FLAG_S | FLAG_P);
+    assert!(ula.is_parasite_reset());
+
+    ula.host_write(0, FLAG_P);
+    assert!(!ula.is_parasite_reset());
+}
+
+#[test]
+fn it_peeks_host_registers_without_popping_data() {
+    let mut ula = TubeUla::default();
+
+    ula.parasite_write(1, 0x12);
+    ula.parasite_write(1, 0x34);
+
+    assert_eq!(ula.host_peek(1), 0x12);
+    assert_eq!(ula.host_peek(1), 0x12);
+    assert_eq!(ula.host_peek(0), ula.host_read(0));
+    assert_eq!(ula.host_read(1), 0x12);
+    assert_eq!(ula.host_peek(1), 0x34);
+}
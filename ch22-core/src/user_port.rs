@@ -0,0 +1,51 @@
+mod amx_mouse;
+mod user_port_device;
+mod user_port_peripheral;
+mod user_port_timer_device;
+
+#[cfg(test)]
+mod tests;
+
+use crate::devices::IOAddressRange;
+use crate::shared::Shared;
+use crate::via::Via;
+
+pub use amx_mouse::{AmxMouse, MouseInput};
+pub use user_port_device::{EmptyUserPort, UserPortDevice, UserPortUpdate};
+pub use user_port_peripheral::UserPortPeripheral;
+pub use user_port_timer_device::UserPortTimerDevice;
+
+// 16 registers repeated across &FE60-&FE7F
+pub const USER_VIA_ADDRESS_RANGE: IOAddressRange = IOAddressRange {
+    base: 0xfe60,
+    size: 0x20,
+    mirror_mask: 0x0f,
+};
+
+// the socket shared by the user VIA and the device's timer, devices can be swapped while running
+#[derive(Clone)]
+pub struct UserPort {
+    device: Shared<Box<dyn UserPortDevice>>,
+}
+
+impl Default for UserPort {
+    fn default() -> Self {
+        UserPort {
+            device: Shared::new(Box::new(EmptyUserPort)),
+        }
+    }
+}
+
+impl UserPort {
+    pub fn plug(&self, device: Box<dyn UserPortDevice>) {
+        *self.device.borrow_mut() = device;
+    }
+
+    pub fn create_peripheral(&self) -> UserPortPeripheral {
+        UserPortPeripheral::new(self.device.clone())
+    }
+
+    pub fn create_timer_device(&self, via: Via) -> UserPortTimerDevice {
+        UserPortTimerDevice::new(self.device.clone(), via)
+    }
+}
@@ -0,0 +1,99 @@
+use super::user_port_device::{UserPortDevice, UserPortUpdate};
+use crate::shared::Shared;
+
+// host motion waiting to be stepped out, and the buttons held
+#[derive(Default)]
+pub struct MouseInput {
+    pub dx: i32,
+    pub dy: i32,
+    // bit 0 left, bit 1 middle, bit 2 right
+    pub buttons: u8,
+}
+
+impl MouseInput {
+    // x right and y down as on the host, motion beyond a few hundred steps is dropped
+    // rather than leaving the pointer drifting on long after the host mouse stops
+    pub fn add_motion(&mut self, dx: i32, dy: i32) {
+        self.dx = (self.dx + dx).clamp(-MAX_PENDING_STEPS, MAX_PENDING_STEPS);
+        self.dy = (self.dy + dy).clamp(-MAX_PENDING_STEPS, MAX_PENDING_STEPS);
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.dx != 0 || self.dy != 0
+    }
+}
+
+// each axis produces two quadrature signals, X on CB1 and PB0, Y on CB2 and PB2
+// software takes an interrupt from the CB line and reads direction from port B
+// buttons pull PB5 (left), PB6 (middle) and PB7 (right) low
+pub struct AmxMouse {
+    input: Shared<MouseInput>,
+    x_phase: u8,
+    y_phase: u8,
+}
+
+impl AmxMouse {
+    pub fn new(input: Shared<MouseInput>) -> Self {
+        AmxMouse {
+            input,
+            x_phase: 0,
+            y_phase: 0,
+        }
+    }
+}
+
+impl UserPortDevice for AmxMouse {
+    fn port_b_input(&mut self, _output: u8, _ddr: u8) -> u8 {
+        let buttons = self.input.borrow().buttons;
+
+        let x = quadrature(self.x_phase).1 as u8;
+        let y = quadrature(self.y_phase).1 as u8;
+        let left = (buttons & 0x01 == 0) as u8;
+        let middle = (buttons & 0x02 == 0) as u8;
+        let right = (buttons & 0x04 == 0) as u8;
+
+        0x1a | x | y << 2 | left << 5 | middle << 6 | right << 7
+    }
+
+    // one quadrature step on each moving axis, up on the BBC being down on the host
+    fn update(&mut self, cycles: u64) -> UserPortUpdate {
+        let mut input = self.input.borrow_mut();
+
+        let x_step = input.dx.signum();
+        let y_step = -input.dy.signum();
+
+        self.x_phase = (self.x_phase as i32 + x_step).rem_euclid(4) as u8;
+        self.y_phase = (self.y_phase as i32 + y_step).rem_euclid(4) as u8;
+
+        // a unit of host motion is a whole cycle, one edge of each kind on the CB line
+        if self.x_phase == 0 {
+            input.dx -= x_step;
+        }
+
+        if self.y_phase == 0 {
+            input.dy += y_step;
+        }
+
+        UserPortUpdate {
+            cb1: Some(quadrature(self.x_phase).0),
+            cb2: Some(quadrature(self.y_phase).0),
+            next_update: input.is_moving().then_some(cycles + AMX_STEP_INTERVAL),
+        }
+    }
+}
+
+// gray code, the first signal leading the second moving forwards, so the second
+// (port B) is high as the first (CB1/CB2) falls moving right or up
+fn quadrature(phase: u8) -> (bool, bool) {
+    match phase {
+        0 => (false, false),
+        1 => (true, false),
+        2 => (true, true),
+        _ => (false, true),
+    }
+}
+
+// 200us between steps (800us a unit), quick enough to track the host without swamping the os
+pub const AMX_STEP_INTERVAL: u64 = 400;
+
+const MAX_PENDING_STEPS: i32 = 256;
@@ -0,0 +1,99 @@
+use super::amx_mouse::AMX_STEP_INTERVAL;
+use super::*;
+
+// CB1, CB2 and port B after each update until the mouse stops
+fn run_mouse(mouse: &mut AmxMouse) -> Vec<(bool, bool, u8)> {
+    let mut lines = Vec::new();
+    let mut cycles = 0;
+
+    loop {
+        let update = mouse.update(cycles);
+
+        lines.push((
+            update.cb1.unwrap(),
+            update.cb2.unwrap(),
+            mouse.port_b_input(0, 0),
+        ));
+
+        match update.next_update {
+            Some(next_update) => {
+                assert_eq!(next_update, cycles + AMX_STEP_INTERVAL);
+                cycles = next_update;
+            }
+            None => break lines,
+        }
+    }
+}
+
+#[test]
+fn it_steps_a_whole_quadrature_cycle_per_unit() {
+    let input = Shared::<MouseInput>::default();
+    let mut mouse = AmxMouse::new(input.clone());
+
+    input.borrow_mut().add_motion(1, 0);
+
+    assert_eq!(
+        run_mouse(&mut mouse),
+        [
+            (true, false, 0xfa),
+            (true, false, 0xfb),
+            (false, false, 0xfb),
+            (false, false, 0xfa),
+        ]
+    );
+    assert!(!input.borrow().is_moving());
+}
+
+// port B just after the CB1 (or CB2) line falls
+fn port_b_as_cb_falls(lines: &[(bool, bool, u8)], cb2: bool) -> u8 {
+    let level = |line: &(bool, bool, u8)| if cb2 { line.1 } else { line.0 };
+
+    lines
+        .windows(2)
+        .find(|pair| level(&pair[0]) && !level(&pair[1]))
+        .map(|pair| pair[1].2)
+        .unwrap()
+}
+
+#[test]
+fn it_gives_direction_on_port_b_as_cb_falls() {
+    let input = Shared::<MouseInput>::default();
+    let mut mouse = AmxMouse::new(input.clone());
+
+    // right, up on the BBC, then back again
+    for (dx, dy, cb2, direction) in [
+        (1, 0, false, 0x01),
+        (0, -1, true, 0x04),
+        (-1, 0, false, 0x00),
+        (0, 1, true, 0x00),
+    ] {
+        input.borrow_mut().add_motion(dx, dy);
+        let port_b = port_b_as_cb_falls(&run_mouse(&mut mouse), cb2);
+
+        assert_eq!(port_b & 0x05, direction);
+    }
+}
+
+#[test]
+fn it_pulls_port_b_low_for_buttons() {
+    let input = Shared::<MouseInput>::default();
+    let mut mouse = AmxMouse::new(input.clone());
+
+    assert_eq!(mouse.port_b_input(0, 0), 0xfa);
+
+    input.borrow_mut().buttons = 0x01;
+    assert_eq!(mouse.port_b_input(0, 0), 0xda);
+
+    input.borrow_mut().buttons = 0x06;
+    assert_eq!(mouse.port_b_input(0, 0), 0x3a);
+}
+
+#[test]
+fn it_limits_pending_motion() {
+    let mut input = MouseInput::default();
+
+    input.add_motion(1000, -1000);
+    input.add_motion(-6, 6);
+
+    assert_eq!((input.dx, input.dy), (250, -250));
+}
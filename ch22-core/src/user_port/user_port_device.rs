@@ -0,0 +1,27 @@
+use crate::shared::MaybeSend;
+
+// hardware on the user VIA's port B and CB1/CB2 lines (mice, trackballs, home made boards)
+pub trait UserPortDevice: MaybeSend {
+    // PB0-7 as the device drives them, 1 where it leaves them pulled up
+    fn port_b_input(&mut self, _output: u8, _ddr: u8) -> u8 {
+        0xff
+    }
+    fn port_b_output(&mut self, _output: u8, _ddr: u8, _cycles: u64) {}
+    fn cb2_output(&mut self, _level: bool, _cycles: u64) {}
+    // brings the device up to cycles
+    fn update(&mut self, _cycles: u64) -> UserPortUpdate {
+        UserPortUpdate::default()
+    }
+}
+
+// the control lines the device drives (None where it doesn't) and when it next needs updating
+#[derive(Debug, Default, PartialEq)]
+pub struct UserPortUpdate {
+    pub cb1: Option<bool>,
+    pub cb2: Option<bool>,
+    pub next_update: Option<u64>,
+}
+
+pub struct EmptyUserPort;
+
+impl UserPortDevice for EmptyUserPort {}
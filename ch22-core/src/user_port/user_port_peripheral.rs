@@ -0,0 +1,28 @@
+use super::user_port_device::UserPortDevice;
+use crate::shared::Shared;
+use crate::via::ViaPeripheral;
+
+// the user VIA's port B side, port A drives the printer port which is left unconnected
+pub struct UserPortPeripheral {
+    device: Shared<Box<dyn UserPortDevice>>,
+}
+
+impl UserPortPeripheral {
+    pub fn new(device: Shared<Box<dyn UserPortDevice>>) -> Self {
+        UserPortPeripheral { device }
+    }
+}
+
+impl ViaPeripheral for UserPortPeripheral {
+    fn port_b_input(&mut self, output: u8, ddr: u8) -> u8 {
+        self.device.borrow_mut().port_b_input(output, ddr)
+    }
+
+    fn port_b_output(&mut self, output: u8, ddr: u8, cycles: u64) {
+        self.device.borrow_mut().port_b_output(output, ddr, cycles);
+    }
+
+    fn cb2_output(&mut self, level: bool, cycles: u64) {
+        self.device.borrow_mut().cb2_output(level, cycles);
+    }
+}
@@ -0,0 +1,33 @@
+use super::user_port_device::UserPortDevice;
+use crate::devices::TimerDevice;
+use crate::shared::Shared;
+use crate::via::Via;
+
+// passes the device's control lines to the user VIA when it asks to be updated
+pub struct UserPortTimerDevice {
+    device: Shared<Box<dyn UserPortDevice>>,
+    via: Via,
+}
+
+impl UserPortTimerDevice {
+    pub fn new(device: Shared<Box<dyn UserPortDevice>>, via: Via) -> Self {
+        UserPortTimerDevice { device, via }
+    }
+}
+
+impl TimerDevice for UserPortTimerDevice {
+    fn sync(&mut self, cycles: u64) -> Option<u64> {
+        let update = self.device.borrow_mut().update(cycles);
+        let mut via = self.via.chip().borrow_mut();
+
+        if let Some(level) = update.cb1 {
+            via.set_cb1(level, cycles);
+        }
+
+        if let Some(level) = update.cb2 {
+            via.set_cb2(level, cycles);
+        }
+
+        update.next_update
+    }
+}
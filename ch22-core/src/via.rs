@@ -0,0 +1,50 @@
+mod system_via_peripheral;
+mod via_6522;
+mod via_io_device;
+mod via_peripheral;
+mod via_timer_device;
+
+#[cfg(test)]
+mod tests;
+
+use crate::devices::IOAddressRange;
+use crate::shared::Shared;
+use via_timer_device::ViaTimerDevice;
+
+pub use system_via_peripheral::*;
+pub use via_6522::Via6522;
+pub use via_io_device::ViaIODevice;
+pub use via_peripheral::{NoPeripheral, ViaPeripheral};
+
+// 16 registers repeated across &FE40-&FE5F
+pub const SYSTEM_VIA_ADDRESS_RANGE: IOAddressRange = IOAddressRange {
+    base: 0xfe40,
+    size: 0x20,
+    mirror_mask: 0x0f,
+};
+
+#[derive(Clone)]
+pub struct Via {
+    via: Shared<Via6522>,
+}
+
+impl Via {
+    pub fn new(peripheral: Box<dyn ViaPeripheral>, cycles: u64) -> Self {
+        Via {
+            via: Shared::new(Via6522::new(peripheral, cycles)),
+        }
+    }
+
+    pub fn create_io_device(&self) -> ViaIODevice {
+        ViaIODevice::new(self.via.clone())
+    }
+
+    pub fn create_timer_device(&self) -> ViaTimerDevice {
+        ViaTimerDevice::new(self.via.clone())
+    }
+
+    // for driving the control lines from outside the cpu's accesses
+    pub fn chip(&self) -> &Shared<Via6522> {
+        &self.via
+    }
+}
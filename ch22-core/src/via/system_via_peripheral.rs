@@ -0,0 +1,93 @@
+use super::via_peripheral::ViaPeripheral;
+use crate::keyboard::{BbcKey, KeyboardMatrix};
+use crate::shared::{Latch, Shared};
+use crate::sound::Sn76489;
+
+// port B drives the IC32 addressable latch: PB0-2 pick one of its outputs and PB3 sets it
+// port A is the slow data bus, selecting a key (PA0-3 its column and PA4-6 its row,
+// reading it back on PA7) or holding a byte for the sound chip
+pub struct SystemViaPeripheral {
+    ic32_latch: Latch,
+    keyboard: Shared<KeyboardMatrix>,
+    sound: Shared<Sn76489>,
+    port_a: u8,
+}
+
+impl SystemViaPeripheral {
+    pub fn new(
+        ic32_latch: Latch,
+        keyboard: Shared<KeyboardMatrix>,
+        sound: Shared<Sn76489>,
+    ) -> Self {
+        SystemViaPeripheral {
+            ic32_latch,
+            keyboard,
+            sound,
+            port_a: 0xff,
+        }
+    }
+
+    fn is_keyboard_enabled(&self) -> bool {
+        self.ic32_latch.get() & IC32_KEYBOARD_WRITE == 0
+    }
+}
+
+impl ViaPeripheral for SystemViaPeripheral {
+    fn port_a_input(&mut self, output: u8, ddr: u8) -> u8 {
+        if !self.is_keyboard_enabled() {
+            return 0xff;
+        }
+
+        let pins = output & ddr | !ddr;
+        let key = BbcKey {
+            row: pins >> 4 & 0x07,
+            column: pins & 0x0f,
+        };
+
+        0x7f | (self.keyboard.borrow().is_pressed(key) as u8) << 7
+    }
+
+    fn port_a_output(&mut self, output: u8, ddr: u8, _cycles: u64) {
+        self.port_a = output & ddr | !ddr;
+    }
+
+    // joystick fire buttons (PB4/5) and the speech interrupt and ready lines (PB6/7)
+    // are pulled up with nothing attached
+    fn port_b_output(&mut self, output: u8, ddr: u8, cycles: u64) {
+        let pins = output & ddr | !ddr;
+        let bit = 1 << (pins & 0x07);
+
+        let ic32 = self.ic32_latch.get();
+        let new_ic32 = if pins & 0x08 != 0 {
+            ic32 | bit
+        } else {
+            ic32 & !bit
+        };
+
+        self.ic32_latch.set(new_ic32);
+
+        if ic32 & !new_ic32 & IC32_SOUND_WRITE != 0 {
+            self.sound.borrow_mut().write(self.port_a, cycles);
+        }
+    }
+
+    // with the keyboard disabled its counter scans every column at 1MHz, so any key
+    // outside row 0 raises CA2 within 10us, otherwise only keys in the selected column
+    fn ca2_input(&mut self) -> Option<bool> {
+        let keyboard = self.keyboard.borrow();
+
+        Some(if self.is_keyboard_enabled() {
+            keyboard.is_column_pressed(self.port_a & 0x0f)
+        } else {
+            keyboard.is_any_pressed()
+        })
+    }
+}
+
+// IC32 outputs: sound write enable, speech read and write, keyboard enable,
+// screen start C0 and C1 (read by video memory access), then the LEDs
+// all but the screen start bits are active low
+pub const IC32_SOUND_WRITE: u8 = 0x01;
+pub const IC32_KEYBOARD_WRITE: u8 = 0x08;
+pub const IC32_CAPS_LOCK_LED: u8 = 0x40;
+pub const IC32_SHIFT_LOCK_LED: u8 = 0x80;
@@ -0,0 +1,566 @@
+use super::via_6522::*;
+use super::*;
+use crate::keyboard::{BbcKey, KeyboardMatrix};
+use crate::shared::Latch;
+
+// expected timings follow the timer and shift register timing diagrams and register
+// descriptions in the MOS MCS6522 and Rockwell R6522 data sheets, converted to the Model
+// B's 2MHz cycles with the VIA clocked by the 1MHz bus
+//
+// the 6522 has no timer 2 free running mode, so timer 2 is covered as a one-shot and a
+// pulse counter
+
+// port and control line outputs as (level, cycles)
+#[derive(Default)]
+struct Outputs {
+    port_b: Vec<(u8, u64)>,
+    ca2: Vec<(bool, u64)>,
+    cb2: Vec<(bool, u64)>,
+}
+
+struct PeripheralMock {
+    outputs: Shared<Outputs>,
+    port_a: u8,
+}
+
+impl ViaPeripheral for PeripheralMock {
+    fn port_a_input(&mut self, _output: u8, _ddr: u8) -> u8 {
+        self.port_a
+    }
+
+    fn port_b_output(&mut self, output: u8, _ddr: u8, cycles: u64) {
+        self.outputs.borrow_mut().port_b.push((output, cycles));
+    }
+
+    fn ca2_output(&mut self, level: bool, cycles: u64) {
+        self.outputs.borrow_mut().ca2.push((level, cycles));
+    }
+
+    fn cb2_output(&mut self, level: bool, cycles: u64) {
+        self.outputs.borrow_mut().cb2.push((level, cycles));
+    }
+}
+
+fn via_with_outputs(port_a: u8) -> (Via6522, Shared<Outputs>) {
+    let outputs = Shared::new(Outputs::default());
+    let peripheral = PeripheralMock {
+        outputs: outputs.clone(),
+        port_a,
+    };
+
+    (Via6522::new(Box::new(peripheral), 0), outputs)
+}
+
+fn via() -> Via6522 {
+    via_with_outputs(0xff).0
+}
+
+// reads land on odd cycles (1MHz phi2), writes on the even cycle that ends it
+fn start_t1(via: &mut Via6522, latch: u16, write_cycles: u64) {
+    via.write(REG_T1C_L, latch as u8, write_cycles - 2);
+    via.write(REG_T1C_H, (latch >> 8) as u8, write_cycles);
+}
+
+fn is_t1_flagged(via: &mut Via6522, cycles: u64) -> bool {
+    via.catch_up(cycles);
+
+    via.peek(REG_IFR) & IFR_T1 != 0
+}
+
+#[test]
+fn it_counts_timer_1_down_through_ffff_before_reloading() {
+    let mut via = via();
+
+    start_t1(&mut via, 3, 4);
+
+    let counts: Vec<u8> = (0..8)
+        .map(|index| via.read(REG_T1C_L, 5 + index * 2))
+        .collect();
+
+    // N, N - 1 .. 0, FFFF, N
+    assert_eq!(counts, [3, 2, 1, 0, 0xff, 3, 2, 1]);
+}
+
+// data sheet: timer 1 one-shot mode timing
+#[test]
+fn it_flags_timer_1_n_plus_one_and_a_half_cycles_after_the_write() {
+    let mut via = via();
+
+    start_t1(&mut via, 0x10, 100);
+
+    // 0x11.5us after the write, in 2MHz cycles
+    assert!(!is_t1_flagged(&mut via, 100 + 2 * 0x10 + 2));
+    assert!(is_t1_flagged(&mut via, 100 + 2 * 0x10 + 3));
+}
+
+#[test]
+fn it_only_flags_timer_1_once_in_one_shot_mode() {
+    let mut via = via();
+
+    start_t1(&mut via, 0x10, 100);
+
+    assert!(is_t1_flagged(&mut via, 200));
+
+    via.read(REG_T1C_L, 201);
+
+    assert!(!is_t1_flagged(&mut via, 1000));
+}
+
+// data sheet: timer 1 free-run mode timing
+#[test]
+fn it_runs_timer_1_free_with_a_period_of_n_plus_2_toggling_pb7() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_ACR, 0xc0, 2);
+    start_t1(&mut via, 0x10, 100);
+
+    via.catch_up(100 + 3 * 2 * 0x12 + 10);
+
+    let pb7: Vec<(u8, u64)> = outputs
+        .borrow()
+        .port_b
+        .iter()
+        .map(|(output, cycles)| (output & 0x80, *cycles))
+        .collect();
+
+    let first_irq = 100 + 2 * 0x10 + 3;
+
+    assert_eq!(
+        pb7,
+        [
+            (0x80, 2),
+            (0x00, 100),
+            (0x80, first_irq),
+            (0x00, first_irq + 2 * 0x12),
+            (0x80, first_irq + 4 * 0x12),
+        ]
+    );
+    assert!(is_t1_flagged(&mut via, first_irq + 4 * 0x12));
+}
+
+// data sheet: in free-run mode a new latch value takes effect at the next time-out,
+// without restarting the count
+#[test]
+fn it_reloads_timer_1_from_latches_written_while_running_free() {
+    let mut via = via();
+
+    via.write(REG_ACR, 0x40, 2);
+    start_t1(&mut via, 0x10, 100);
+
+    let first_irq = 100 + 2 * 0x10 + 3;
+
+    assert!(is_t1_flagged(&mut via, first_irq));
+    via.read(REG_T1C_L, first_irq + 2);
+
+    via.write(REG_T1L_L, 0x20, first_irq + 3);
+    via.write(REG_T1L_H, 0x00, first_irq + 5);
+
+    let second_irq = first_irq + 2 * 0x12;
+
+    assert!(!is_t1_flagged(&mut via, second_irq - 1));
+    assert!(is_t1_flagged(&mut via, second_irq));
+    via.read(REG_T1C_L, second_irq + 2);
+
+    assert!(!is_t1_flagged(&mut via, second_irq + 2 * 0x22 - 1));
+    assert!(is_t1_flagged(&mut via, second_irq + 2 * 0x22));
+}
+
+// data sheet: writing T1L-H clears the timer 1 flag but, unlike T1C-H, doesn't load
+// the counter
+#[test]
+fn it_clears_the_timer_1_flag_on_a_high_latch_write_without_restarting() {
+    let mut via = via();
+
+    start_t1(&mut via, 0x10, 100);
+
+    assert!(is_t1_flagged(&mut via, 200));
+
+    via.write(REG_T1L_H, 0x00, 202);
+
+    assert!(!is_t1_flagged(&mut via, 203));
+    assert_ne!(via.read(REG_T1C_L, 203), 0x10);
+    assert!(!is_t1_flagged(&mut via, 1000));
+}
+
+// data sheet: timer 1 one-shot mode, PB7 goes low on the T1C-H write and high at time-out
+#[test]
+fn it_drives_pb7_from_timer_1_in_one_shot_mode() {
+    let mut via = via();
+
+    via.write(REG_ACR, 0x80, 2);
+    start_t1(&mut via, 0x10, 100);
+
+    assert_eq!(via.read(REG_ORB, 101) & 0x80, 0x00);
+    assert_eq!(via.read(REG_ORB, 201) & 0x80, 0x80);
+}
+
+// data sheet: timer 2 one-shot mode timing, the counter carries on decrementing after
+// time-out without setting the flag again until T2C-H is rewritten
+#[test]
+fn it_flags_timer_2_once_in_one_shot_mode_and_keeps_counting() {
+    let mut via = via();
+
+    via.write(REG_T2C_L, 0x10, 98);
+    via.write(REG_T2C_H, 0x00, 100);
+
+    assert_eq!(via.peek(REG_IFR) & IFR_T2, 0);
+
+    via.catch_up(100 + 2 * 0x10 + 3);
+    assert_eq!(via.peek(REG_IFR) & IFR_T2, IFR_T2);
+
+    assert_eq!(via.read(REG_T2C_L, 100 + 2 * 0x10 + 5), 0xfe);
+    assert_eq!(via.peek(REG_IFR) & IFR_T2, 0);
+
+    assert_eq!(via.read(REG_T2C_H, 100 + 2 * 0x10 + 5), 0xff);
+
+    via.catch_up(100 + 2 * 0x10000 + 2 * 0x10 + 10);
+    assert_eq!(via.peek(REG_IFR) & IFR_T2, 0);
+}
+
+// data sheet: timer 2 pulse counting mode
+#[test]
+fn it_counts_pb6_falling_edges_on_timer_2_in_pulse_counting_mode() {
+    let mut via = via();
+
+    via.write(REG_ACR, 0x20, 2);
+    via.write(REG_T2C_L, 3, 4);
+    via.write(REG_T2C_H, 0, 6);
+
+    for pulse in 0..3 {
+        let cycles = 100 + pulse * 20;
+
+        assert_eq!(via.peek(REG_IFR) & IFR_T2, 0);
+
+        via.set_pb6(false, cycles);
+        via.set_pb6(true, cycles + 10);
+    }
+
+    assert_eq!(via.peek(REG_IFR) & IFR_T2, IFR_T2);
+    assert_eq!(via.read(REG_T2C_L, 1001), 0);
+}
+
+#[test]
+fn it_sets_and_clears_interrupt_enables_and_raises_irq_through_them() {
+    let mut via = via();
+
+    via.write(REG_IER, 0x80 | IFR_T1 | IFR_CA1, 2);
+    via.write(REG_IER, IFR_CA1, 4);
+
+    assert_eq!(via.read(REG_IER, 5), 0x80 | IFR_T1);
+
+    via.set_ca1(false, 10);
+
+    assert_eq!(via.read(REG_IFR, 11), IFR_CA1);
+    assert!(!via.irq());
+
+    start_t1(&mut via, 0, 20);
+    via.catch_up(30);
+
+    assert_eq!(via.read(REG_IFR, 31), 0x80 | IFR_T1 | IFR_CA1);
+    assert!(via.irq());
+
+    via.write(REG_IFR, IFR_T1, 32);
+
+    assert_eq!(via.read(REG_IFR, 33), IFR_CA1);
+    assert!(!via.irq());
+}
+
+#[test]
+fn it_latches_port_a_on_the_active_ca1_edge() {
+    let mut via = via_with_outputs(0x5a).0;
+
+    via.write(REG_ACR, 0x01, 2);
+    via.write(REG_PCR, 0x01, 4);
+
+    via.set_ca1(false, 10);
+    assert_eq!(via.peek(REG_IFR) & IFR_CA1, 0);
+
+    via.set_ca1(true, 20);
+    assert_eq!(via.peek(REG_IFR) & IFR_CA1, IFR_CA1);
+
+    assert_eq!(via.read(REG_ORA, 31), 0x5a);
+    assert_eq!(via.peek(REG_IFR) & IFR_CA1, 0);
+}
+
+#[test]
+fn it_only_clears_independent_ca2_interrupts_through_the_ifr() {
+    let mut via = via();
+
+    via.write(REG_PCR, 0x02, 2);
+    via.set_ca2(false, 10);
+
+    via.read(REG_ORA, 11);
+    assert_eq!(via.peek(REG_IFR) & IFR_CA2, IFR_CA2);
+
+    via.write(REG_PCR, 0x00, 12);
+    via.read(REG_ORA, 13);
+    assert_eq!(via.peek(REG_IFR) & IFR_CA2, 0);
+}
+
+#[test]
+fn it_handshakes_ca2_on_port_a_reads_until_ca1_is_active() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_PCR, 0x08, 2);
+    via.read(REG_ORA, 11);
+
+    // the no handshake register leaves CA2 alone
+    via.set_ca1(false, 20);
+    via.read(REG_ORA_NO_HANDSHAKE, 31);
+
+    assert_eq!(outputs.borrow().ca2, [(false, 11), (true, 20)]);
+}
+
+#[test]
+fn it_pulses_cb2_for_a_cycle_on_port_b_writes() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_PCR, 0xa0, 2);
+    via.write(REG_ORB, 0x12, 10);
+    via.catch_up(20);
+
+    assert_eq!(outputs.borrow().cb2, [(false, 10), (true, 11)]);
+}
+
+#[test]
+fn it_drives_ca2_and_cb2_manually() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_PCR, 0xcc, 2);
+    via.write(REG_PCR, 0xee, 4);
+
+    assert_eq!(outputs.borrow().ca2, [(false, 2), (true, 4)]);
+    assert_eq!(outputs.borrow().cb2, [(false, 2), (true, 4)]);
+}
+
+// data sheet: shift out under control of phi2 (ACR 110)
+#[test]
+fn it_shifts_out_under_phi2_and_flags_after_8_bits() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_ACR, 0x18, 2);
+    via.write(REG_SR, 0b0101_0011, 4);
+    via.catch_up(100);
+
+    let levels: Vec<bool> = outputs
+        .borrow()
+        .cb2
+        .iter()
+        .map(|(level, _)| *level)
+        .collect();
+
+    // only changes of level reach CB2
+    assert_eq!(levels, [false, true, false, true, false, true]);
+    assert_eq!(via.peek(REG_IFR) & IFR_SR, IFR_SR);
+    assert_eq!(via.read(REG_SR, 101), 0b0101_0011);
+}
+
+// data sheet: shift in under control of external CB1 clock (ACR 011)
+#[test]
+fn it_shifts_in_on_external_cb1_rising_edges() {
+    let mut via = via();
+
+    via.write(REG_ACR, 0x0c, 2);
+    via.read(REG_SR, 3);
+
+    for (index, bit) in [true, false, true, true, false, false, true, false]
+        .into_iter()
+        .enumerate()
+    {
+        let cycles = 10 + index as u64 * 10;
+
+        via.set_cb2(bit, cycles);
+        via.set_cb1(false, cycles + 2);
+        via.set_cb1(true, cycles + 4);
+    }
+
+    assert_eq!(via.peek(REG_IFR) & IFR_SR, IFR_SR);
+    assert_eq!(via.read(REG_SR, 201), 0b1011_0010);
+}
+
+// data sheet: shift out under control of T2 (ACR 101)
+#[test]
+fn it_shifts_at_the_timer_2_rate() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_T2C_L, 2, 2);
+    via.write(REG_ACR, 0x14, 4);
+    via.write(REG_SR, 0x00, 6);
+
+    // each half of the shift clock lasts N + 2 cycles, the first bit shifts out as
+    // it first falls and the flag rises at the end of the 16th half
+    via.catch_up(6 + 2 * 4 * 16 - 2);
+    assert_eq!(via.peek(REG_IFR) & IFR_SR, 0);
+
+    via.catch_up(6 + 2 * 4 * 16 - 1);
+    assert_eq!(via.peek(REG_IFR) & IFR_SR, IFR_SR);
+    assert_eq!(outputs.borrow().cb2, [(false, 6 + 2 * 4 - 1)]);
+}
+
+// data sheet: shift out free-running at T2 rate (ACR 100), the bits recirculate
+// without the shift counter stopping or setting the flag
+#[test]
+fn it_shifts_out_free_running_without_flagging() {
+    let (mut via, outputs) = via_with_outputs(0xff);
+
+    via.write(REG_T2C_L, 2, 2);
+    via.write(REG_ACR, 0x10, 4);
+    via.write(REG_SR, 0b0000_1111, 6);
+
+    // 3 full passes of 8 bits, each bit lasting 2 halves of N + 2 cycles
+    via.catch_up(6 + 3 * 8 * 2 * 4 * 2);
+
+    let levels: Vec<bool> = outputs
+        .borrow()
+        .cb2
+        .iter()
+        .map(|(level, _)| *level)
+        .collect();
+
+    assert_eq!(levels, [false, true, false, true, false, true]);
+    assert_eq!(via.peek(REG_IFR) & IFR_SR, 0);
+    assert_eq!(via.peek(REG_SR), 0b0000_1111);
+}
+
+#[test]
+fn it_resets_registers_but_not_timers() {
+    let mut via = via();
+
+    via.write(REG_DDRA, 0xff, 2);
+    via.write(REG_IER, 0xff, 4);
+    start_t1(&mut via, 0x1234, 8);
+
+    via.reset();
+
+    assert_eq!(via.peek(REG_DDRA), 0);
+    assert_eq!(via.peek(REG_IER), 0x80);
+    assert_eq!(via.peek(REG_T1L_H), 0x12);
+}
+
+#[test]
+fn it_saves_and_restores_its_state() {
+    let mut via = via();
+
+    via.write(REG_ACR, 0x40, 2);
+    via.write(REG_IER, 0x80 | IFR_T1, 4);
+    start_t1(&mut via, 0x100, 8);
+    via.catch_up(101);
+
+    let state = via.save_state();
+    let mut restored = via_with_outputs(0xff).0;
+
+    restored.load_state(&state).unwrap();
+
+    assert_eq!(restored.save_state(), state);
+    assert_eq!(restored.read(REG_T1C_L, 1001), via.read(REG_T1C_L, 1001));
+    assert_eq!(restored.peek(REG_IFR), via.peek(REG_IFR));
+    assert!(restored.load_state(&state[1..]).is_err());
+}
+
+#[test]
+fn it_wakes_at_the_next_timer_interrupt() {
+    let mut via = via();
+
+    assert_eq!(via.next_wakeup(), None);
+
+    start_t1(&mut via, 0x10, 100);
+    via.write(REG_T2C_L, 0x08, 102);
+    via.write(REG_T2C_H, 0, 104);
+
+    assert_eq!(via.next_wakeup(), Some(104 + 2 * 0x08 + 3));
+
+    via.catch_up(200);
+
+    assert_eq!(via.next_wakeup(), None);
+}
+
+#[test]
+fn it_sets_ic32_outputs_from_port_b() {
+    let ic32_latch = Latch::default();
+    let mut via = Via6522::new(
+        Box::new(SystemViaPeripheral::new(
+            ic32_latch.clone(),
+            Shared::default(),
+            Shared::default(),
+        )),
+        0,
+    );
+
+    // with PB3 an input it's pulled high, so outputs can only be set
+    via.write(REG_DDRB, 0x07, 2);
+    via.write(REG_ORB, 0x04, 4);
+
+    assert_eq!(ic32_latch.get(), 0x11);
+
+    via.write(REG_DDRB, 0x0f, 6);
+    via.write(REG_ORB, 0x0e, 8);
+
+    assert_eq!(ic32_latch.get(), 0x41);
+}
+
+#[test]
+fn it_reads_the_selected_key_on_pa7_with_the_keyboard_enabled() {
+    let ic32_latch = Latch::default();
+    let keyboard = Shared::new(KeyboardMatrix::default());
+    let mut via = Via6522::new(
+        Box::new(SystemViaPeripheral::new(
+            ic32_latch.clone(),
+            keyboard.clone(),
+            Shared::default(),
+        )),
+        0,
+    );
+
+    keyboard
+        .borrow_mut()
+        .set_key(BbcKey::from_internal(0x41), true);
+
+    via.write(REG_DDRA, 0x7f, 2);
+    via.write(REG_ORA_NO_HANDSHAKE, 0x41, 4);
+    assert_eq!(via.read(REG_ORA_NO_HANDSHAKE, 5), 0xc1);
+
+    via.write(REG_ORA_NO_HANDSHAKE, 0x42, 6);
+    assert_eq!(via.read(REG_ORA_NO_HANDSHAKE, 7), 0x42);
+
+    // disabled, the keyboard leaves PA7 pulled up
+    ic32_latch.set(0x08);
+    assert_eq!(via.read(REG_ORA_NO_HANDSHAKE, 9), 0xc2);
+}
+
+#[test]
+fn it_raises_ca2_for_keys_pressed_while_scanning_or_in_the_selected_column() {
+    let ic32_latch = Latch::default();
+    let keyboard = Shared::new(KeyboardMatrix::default());
+    let mut via = Via6522::new(
+        Box::new(SystemViaPeripheral::new(
+            ic32_latch.clone(),
+            keyboard.clone(),
+            Shared::default(),
+        )),
+        0,
+    );
+
+    // CA2 positive edge, PB writes setting IC32 keyboard enable high (scanning)
+    via.write(REG_PCR, 0x04, 2);
+    via.write(REG_DDRB, 0x0f, 4);
+    via.write(REG_ORB, 0x0b, 6);
+
+    keyboard
+        .borrow_mut()
+        .set_key(BbcKey::from_internal(0x41), true);
+    via.resample_peripheral(8);
+
+    assert_eq!(via.peek(REG_IFR) & IFR_CA2, IFR_CA2);
+
+    // selecting another column with the keyboard enabled drops CA2
+    via.write(REG_IFR, IFR_CA2, 10);
+    via.write(REG_DDRA, 0x7f, 12);
+    via.write(REG_ORA_NO_HANDSHAKE, 0x02, 14);
+    via.write(REG_ORB, 0x03, 16);
+
+    assert_eq!(via.peek(REG_IFR) & IFR_CA2, 0);
+
+    via.write(REG_ORA_NO_HANDSHAKE, 0x01, 18);
+
+    assert_eq!(via.peek(REG_IFR) & IFR_CA2, IFR_CA2);
+}
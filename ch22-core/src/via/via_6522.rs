@@ -0,0 +1,888 @@
+use super::via_peripheral::ViaPeripheral;
+use crate::devices::DeviceDescription;
+
+// 6522 VIA, stepped in 1MHz ticks and caught up lazily whenever it is accessed
+// tick k spans 2MHz cycles 2k and 2k + 1, its counters change and interrupts are
+// raised as 1MHz phi2 starts (2k + 1), writes are latched at its end (2k + 2)
+pub struct Via6522 {
+    peripheral: Box<dyn ViaPeripheral>,
+    // next tick to run
+    tick: u64,
+
+    ora: u8,
+    orb: u8,
+    ddra: u8,
+    ddrb: u8,
+    ira_latch: u8,
+    irb_latch: u8,
+
+    t1: Countdown,
+    t1_latch: u16,
+    t1_armed: bool,
+    pb7: bool,
+
+    t2: Countdown,
+    t2_latch: u16,
+    t2_armed: bool,
+    pb6: bool,
+
+    sr: u8,
+    sr_bits_left: u8,
+    sr_timer: Countdown,
+    sr_clock: bool,
+
+    acr: u8,
+    pcr: u8,
+    ifr: u8,
+    ier: u8,
+
+    ca1: bool,
+    ca2: bool,
+    cb1: bool,
+    cb2: bool,
+    ca2_out: bool,
+    cb2_out: bool,
+    ca2_pulse: bool,
+    cb2_pulse: bool,
+}
+
+impl Via6522 {
+    pub fn new(peripheral: Box<dyn ViaPeripheral>, cycles: u64) -> Self {
+        Via6522 {
+            peripheral,
+            tick: ticks_before(cycles),
+            ora: 0,
+            orb: 0,
+            ddra: 0,
+            ddrb: 0,
+            ira_latch: 0,
+            irb_latch: 0,
+            t1: Countdown::default(),
+            t1_latch: 0,
+            t1_armed: false,
+            pb7: true,
+            t2: Countdown::default(),
+            t2_latch: 0,
+            t2_armed: false,
+            pb6: true,
+            sr: 0,
+            sr_bits_left: 0,
+            sr_timer: Countdown::default(),
+            sr_clock: true,
+            acr: 0,
+            pcr: 0,
+            ifr: 0,
+            ier: 0,
+            ca1: true,
+            ca2: true,
+            cb1: true,
+            cb2: true,
+            ca2_out: true,
+            cb2_out: true,
+            ca2_pulse: false,
+            cb2_pulse: false,
+        }
+    }
+
+    pub fn peripheral_mut(&mut self) -> &mut dyn ViaPeripheral {
+        self.peripheral.as_mut()
+    }
+
+    // runs every tick whose phi2 has started by cycles
+    pub fn catch_up(&mut self, cycles: u64) {
+        let target = ticks_before(cycles);
+
+        while self.tick < target {
+            let quiet_ticks = self.quiet_ticks().min(target - self.tick);
+
+            if quiet_ticks > 0 {
+                self.skip(quiet_ticks);
+            } else {
+                self.step();
+            }
+        }
+    }
+
+    pub fn irq(&self) -> bool {
+        self.ifr & self.ier & 0x7f != 0
+    }
+
+    // the next cycle an armed timer raises its interrupt, for scheduler wakeups
+    // wakeups are only an optimisation as every access and interrupt poll catches up
+    pub fn next_wakeup(&self) -> Option<u64> {
+        let t1 = self
+            .t1_armed
+            .then(|| self.t1.ticks_to_underflow(self.t1_latch));
+        let t2 = (self.t2_armed && self.acr & ACR_T2_PULSE_COUNTING == 0)
+            .then(|| self.t2.ticks_to_underflow(self.t2_latch));
+
+        let ticks = t1.into_iter().chain(t2).min()?;
+
+        Some(phi2_cycles(self.tick + ticks))
+    }
+
+    pub fn read(&mut self, register: u8, cycles: u64) -> u8 {
+        self.catch_up(cycles);
+
+        match register & 0x0f {
+            REG_ORB => {
+                self.clear_port_b_flags();
+
+                self.read_port_b()
+            }
+            REG_ORA => {
+                self.clear_port_a_flags();
+                self.port_a_handshake(cycles);
+
+                self.read_port_a()
+            }
+            REG_T1C_L => {
+                self.ifr &= !IFR_T1;
+
+                self.t1.counter as u8
+            }
+            REG_T2C_L => {
+                self.ifr &= !IFR_T2;
+
+                self.t2.counter as u8
+            }
+            REG_SR => {
+                self.ifr &= !IFR_SR;
+                self.start_shift();
+
+                self.sr
+            }
+            REG_ORA_NO_HANDSHAKE => self.read_port_a(),
+            register => self.peek(register),
+        }
+    }
+
+    pub fn write(&mut self, register: u8, value: u8, cycles: u64) {
+        self.catch_up(cycles);
+
+        match register & 0x0f {
+            REG_ORB => {
+                self.orb = value;
+                self.clear_port_b_flags();
+                self.port_b_output(cycles);
+                self.port_b_handshake(cycles);
+            }
+            REG_ORA => {
+                self.ora = value;
+                self.clear_port_a_flags();
+                self.port_a_output(cycles);
+                self.port_a_handshake(cycles);
+            }
+            REG_DDRB => {
+                self.ddrb = value;
+                self.port_b_output(cycles);
+            }
+            REG_DDRA => {
+                self.ddra = value;
+                self.port_a_output(cycles);
+            }
+            REG_T1C_L | REG_T1L_L => self.t1_latch = self.t1_latch & 0xff00 | value as u16,
+            REG_T1C_H => {
+                self.t1_latch = self.t1_latch & 0x00ff | (value as u16) << 8;
+                self.t1.load = true;
+                self.t1_armed = true;
+                self.ifr &= !IFR_T1;
+
+                if self.acr & ACR_T1_PB7 != 0 {
+                    self.pb7 = false;
+                    self.port_b_output(cycles);
+                }
+            }
+            REG_T1L_H => {
+                self.t1_latch = self.t1_latch & 0x00ff | (value as u16) << 8;
+                self.ifr &= !IFR_T1;
+            }
+            REG_T2C_L => self.t2_latch = self.t2_latch & 0xff00 | value as u16,
+            REG_T2C_H => {
+                self.t2_latch = self.t2_latch & 0x00ff | (value as u16) << 8;
+                self.t2.load = true;
+                self.t2_armed = true;
+                self.ifr &= !IFR_T2;
+            }
+            REG_SR => {
+                self.sr = value;
+                self.ifr &= !IFR_SR;
+                self.start_shift();
+            }
+            REG_ACR => {
+                self.acr = value;
+                self.port_b_output(cycles);
+            }
+            REG_PCR => {
+                self.pcr = value;
+
+                if let Some(level) = manual_output(value >> 1) {
+                    self.set_ca2_out(level, cycles);
+                }
+
+                if let Some(level) = manual_output(value >> 5) {
+                    self.set_cb2_out(level, cycles);
+                }
+            }
+            REG_IFR => self.ifr &= !(value & 0x7f),
+            REG_IER => {
+                if value & 0x80 != 0 {
+                    self.ier |= value & 0x7f;
+                } else {
+                    self.ier &= !value;
+                }
+            }
+            REG_ORA_NO_HANDSHAKE => {
+                self.ora = value;
+                self.port_a_output(cycles);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    // registers without read side effects, ports read from the output registers
+    pub fn peek(&self, register: u8) -> u8 {
+        match register & 0x0f {
+            REG_ORB => self.orb,
+            REG_ORA | REG_ORA_NO_HANDSHAKE => self.ora,
+            REG_DDRB => self.ddrb,
+            REG_DDRA => self.ddra,
+            REG_T1C_L => self.t1.counter as u8,
+            REG_T1C_H => (self.t1.counter >> 8) as u8,
+            REG_T1L_L => self.t1_latch as u8,
+            REG_T1L_H => (self.t1_latch >> 8) as u8,
+            REG_T2C_L => self.t2.counter as u8,
+            REG_T2C_H => (self.t2.counter >> 8) as u8,
+            REG_SR => self.sr,
+            REG_ACR => self.acr,
+            REG_PCR => self.pcr,
+            REG_IFR => self.ifr | if self.irq() { 0x80 } else { 0 },
+            REG_IER => self.ier | 0x80,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn set_ca1(&mut self, level: bool, cycles: u64) {
+        self.catch_up(cycles);
+
+        if level == self.ca1 {
+            return;
+        }
+
+        self.ca1 = level;
+
+        if level == (self.pcr & PCR_CA1_POSITIVE != 0) {
+            self.ifr |= IFR_CA1;
+
+            if self.acr & ACR_PA_LATCH != 0 {
+                self.ira_latch = self.port_a_pins();
+            }
+
+            if self.pcr & 0x0e == PCR_HANDSHAKE << 1 {
+                self.set_ca2_out(true, cycles);
+            }
+        }
+    }
+
+    pub fn set_ca2(&mut self, level: bool, cycles: u64) {
+        self.catch_up(cycles);
+
+        self.ca2_input(level);
+    }
+
+    // for when the peripheral's inputs change outside the VIA's own accesses
+    pub fn resample_peripheral(&mut self, cycles: u64) {
+        self.catch_up(cycles);
+
+        self.sample_peripheral();
+    }
+
+    pub fn set_cb1(&mut self, level: bool, cycles: u64) {
+        self.catch_up(cycles);
+
+        if level == self.cb1 {
+            return;
+        }
+
+        self.cb1 = level;
+
+        if matches!(self.acr & ACR_SR_MODE, SR_EXTERNAL_IN | SR_EXTERNAL_OUT) {
+            self.shift_clock_edge(level, cycles);
+        }
+
+        if level == (self.pcr & PCR_CB1_POSITIVE != 0) {
+            self.ifr |= IFR_CB1;
+
+            if self.acr & ACR_PB_LATCH != 0 {
+                self.irb_latch = self.port_b_pins();
+            }
+
+            if self.pcr & 0xe0 == PCR_HANDSHAKE << 5 {
+                self.set_cb2_out(true, cycles);
+            }
+        }
+    }
+
+    pub fn set_cb2(&mut self, level: bool, cycles: u64) {
+        self.catch_up(cycles);
+
+        if level == self.cb2 {
+            return;
+        }
+
+        self.cb2 = level;
+
+        if self.pcr & 0x80 == 0 && level == (self.pcr & 0x40 != 0) {
+            self.ifr |= IFR_CB2;
+        }
+    }
+
+    // timer 2 counts falling edges on PB6 in pulse counting mode
+    pub fn set_pb6(&mut self, level: bool, cycles: u64) {
+        self.catch_up(cycles);
+
+        if level == self.pb6 {
+            return;
+        }
+
+        self.pb6 = level;
+
+        if !level && self.acr & ACR_T2_PULSE_COUNTING != 0 && !self.t2.load {
+            self.t2.counter = self.t2.counter.wrapping_sub(1);
+
+            if self.t2.counter == 0 && self.t2_armed {
+                self.ifr |= IFR_T2;
+                self.t2_armed = false;
+            }
+        }
+    }
+
+    // RES clears everything but the timers and shift register
+    pub fn reset(&mut self) {
+        let cycles = phi2_cycles(self.tick);
+
+        self.ora = 0;
+        self.orb = 0;
+        self.ddra = 0;
+        self.ddrb = 0;
+        self.acr = 0;
+        self.pcr = 0;
+        self.ifr = 0;
+        self.ier = 0;
+        self.sr_bits_left = 0;
+        self.ca2_pulse = false;
+        self.cb2_pulse = false;
+
+        self.port_a_output(cycles);
+        self.port_b_output(cycles);
+        self.set_ca2_out(true, cycles);
+        self.set_cb2_out(true, cycles);
+    }
+
+    pub fn describe(&self) -> DeviceDescription {
+        let registers = [
+            ("ORB", self.orb as u16),
+            ("ORA", self.ora as u16),
+            ("DDRB", self.ddrb as u16),
+            ("DDRA", self.ddra as u16),
+            ("T1C", self.t1.counter),
+            ("T1L", self.t1_latch),
+            ("T2C", self.t2.counter),
+            ("T2L", self.t2_latch),
+            ("SR", self.sr as u16),
+            ("ACR", self.acr as u16),
+            ("PCR", self.pcr as u16),
+            ("IFR", self.peek(REG_IFR) as u16),
+            ("IER", self.ier as u16),
+        ];
+
+        DeviceDescription {
+            name: "6522 VIA".to_string(),
+            registers: registers
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+        }
+    }
+
+    pub fn save_state(&self) -> Vec<u8> {
+        let flags = [
+            self.t1.load,
+            self.t1_armed,
+            self.pb7,
+            self.t2.load,
+            self.t2_armed,
+            self.pb6,
+            self.sr_timer.load,
+            self.sr_clock,
+            self.ca1,
+            self.ca2,
+            self.cb1,
+            self.cb2,
+            self.ca2_out,
+            self.cb2_out,
+            self.ca2_pulse,
+            self.cb2_pulse,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0u16, |flags, (bit, set)| flags | (*set as u16) << bit);
+
+        let mut state = Vec::with_capacity(VIA_STATE_LEN);
+
+        state.extend(self.tick.to_le_bytes());
+        state.extend([
+            self.ora,
+            self.orb,
+            self.ddra,
+            self.ddrb,
+            self.ira_latch,
+            self.irb_latch,
+            self.sr,
+            self.sr_bits_left,
+            self.acr,
+            self.pcr,
+            self.ifr,
+            self.ier,
+        ]);
+
+        for value in [
+            self.t1.counter,
+            self.t1_latch,
+            self.t2.counter,
+            self.t2_latch,
+            self.sr_timer.counter,
+            flags,
+        ] {
+            state.extend(value.to_le_bytes());
+        }
+
+        state
+    }
+
+    pub fn load_state(&mut self, state: &[u8]) -> Result<(), String> {
+        if state.len() != VIA_STATE_LEN {
+            return Err(format!("Invalid VIA state length: {}", state.len()));
+        }
+
+        let (tick, state) = state.split_at(8);
+        let (registers, words) = state.split_at(12);
+        let words: Vec<u16> = words
+            .chunks(2)
+            .map(|word| u16::from_le_bytes([word[0], word[1]]))
+            .collect();
+        let flag = |bit: usize| words[5] & (1 << bit) != 0;
+
+        self.tick = u64::from_le_bytes(tick.try_into().unwrap());
+
+        [
+            self.ora,
+            self.orb,
+            self.ddra,
+            self.ddrb,
+            self.ira_latch,
+            self.irb_latch,
+            self.sr,
+            self.sr_bits_left,
+            self.acr,
+            self.pcr,
+            self.ifr,
+            self.ier,
+        ] = registers.try_into().unwrap();
+
+        self.t1.counter = words[0];
+        self.t1_latch = words[1];
+        self.t2.counter = words[2];
+        self.t2_latch = words[3];
+        self.sr_timer.counter = words[4];
+
+        self.t1.load = flag(0);
+        self.t1_armed = flag(1);
+        self.pb7 = flag(2);
+        self.t2.load = flag(3);
+        self.t2_armed = flag(4);
+        self.pb6 = flag(5);
+        self.sr_timer.load = flag(6);
+        self.sr_clock = flag(7);
+        self.ca1 = flag(8);
+        self.ca2 = flag(9);
+        self.cb1 = flag(10);
+        self.cb2 = flag(11);
+        self.ca2_out = flag(12);
+        self.cb2_out = flag(13);
+        self.ca2_pulse = flag(14);
+        self.cb2_pulse = flag(15);
+
+        Ok(())
+    }
+
+    // ticks which would only count the timers down
+    fn quiet_ticks(&self) -> u64 {
+        if self.t1.load
+            || self.t2.load
+            || self.ca2_pulse
+            || self.cb2_pulse
+            || self.is_shift_clocked()
+        {
+            return 0;
+        }
+
+        let t2_ticks = if self.acr & ACR_T2_PULSE_COUNTING == 0 {
+            self.t2.counter
+        } else {
+            u16::MAX
+        };
+
+        self.t1.counter.min(t2_ticks) as u64
+    }
+
+    fn skip(&mut self, ticks: u64) {
+        self.t1.counter -= ticks as u16;
+
+        if self.acr & ACR_T2_PULSE_COUNTING == 0 {
+            self.t2.counter -= ticks as u16;
+        }
+
+        self.tick += ticks;
+    }
+
+    fn step(&mut self) {
+        let cycles = phi2_cycles(self.tick);
+
+        if self.t1.step(self.t1_latch) && self.t1_armed {
+            self.ifr |= IFR_T1;
+
+            if self.acr & ACR_T1_FREE_RUN != 0 {
+                self.pb7 = !self.pb7;
+            } else {
+                self.pb7 = true;
+                self.t1_armed = false;
+            }
+
+            if self.acr & ACR_T1_PB7 != 0 {
+                self.port_b_output(cycles);
+            }
+        }
+
+        if self.t2.load {
+            self.t2.counter = self.t2_latch;
+            self.t2.load = false;
+        } else if self.acr & ACR_T2_PULSE_COUNTING == 0 {
+            self.t2.counter = self.t2.counter.wrapping_sub(1);
+
+            if self.t2.counter == 0xffff && self.t2_armed {
+                self.ifr |= IFR_T2;
+                self.t2_armed = false;
+            }
+        }
+
+        if self.is_shift_clocked() {
+            self.step_shift_clock(cycles);
+        }
+
+        if self.ca2_pulse {
+            self.ca2_pulse = false;
+            self.set_ca2_out(true, cycles);
+        }
+
+        if self.cb2_pulse {
+            self.cb2_pulse = false;
+            self.set_cb2_out(true, cycles);
+        }
+
+        self.tick += 1;
+    }
+
+    // the shift register clocks itself from timer 2 or phi2 until 8 bits have shifted,
+    // forever when shifting out free running
+    fn is_shift_clocked(&self) -> bool {
+        match self.acr & ACR_SR_MODE {
+            SR_T2_IN | SR_PHI2_IN | SR_T2_OUT | SR_PHI2_OUT => self.sr_bits_left > 0,
+            SR_FREE_RUN_OUT => true,
+            _ => false,
+        }
+    }
+
+    // phi2 clocks a bit every tick, timer 2 toggles the shift clock each time its
+    // low latch counts through
+    fn step_shift_clock(&mut self, cycles: u64) {
+        let mode = self.acr & ACR_SR_MODE;
+
+        if mode == SR_PHI2_IN || mode == SR_PHI2_OUT {
+            self.shift_clock_edge(false, cycles);
+            self.shift_clock_edge(true, cycles);
+        } else if self.sr_timer.step(self.t2_latch & 0x00ff) {
+            self.sr_clock = !self.sr_clock;
+            self.shift_clock_edge(self.sr_clock, cycles);
+        }
+    }
+
+    fn start_shift(&mut self) {
+        if self.acr & ACR_SR_MODE != SR_DISABLED {
+            self.sr_bits_left = 8;
+            self.sr_timer.load = true;
+            self.sr_clock = true;
+        }
+    }
+
+    // bits shift out onto CB2 as the clock falls and in from CB2 as it rises,
+    // with each bit complete as the clock rises
+    fn shift_clock_edge(&mut self, rising: bool, cycles: u64) {
+        let mode = self.acr & ACR_SR_MODE;
+
+        if mode != SR_FREE_RUN_OUT && self.sr_bits_left == 0 {
+            return;
+        }
+
+        if mode & SR_OUT != 0 {
+            if !rising {
+                let bit = self.sr >> 7;
+
+                self.sr = self.sr << 1 | bit;
+                self.set_cb2_out(bit != 0, cycles);
+            }
+        } else if rising {
+            self.sr = self.sr << 1 | self.cb2 as u8;
+        }
+
+        if rising && mode != SR_FREE_RUN_OUT {
+            self.sr_bits_left -= 1;
+
+            if self.sr_bits_left == 0 {
+                self.ifr |= IFR_SR;
+            }
+        }
+    }
+
+    fn clear_port_a_flags(&mut self) {
+        self.ifr &= !IFR_CA1;
+
+        if self.pcr & 0x0a != 0x02 {
+            self.ifr &= !IFR_CA2;
+        }
+    }
+
+    fn clear_port_b_flags(&mut self) {
+        self.ifr &= !IFR_CB1;
+
+        if self.pcr & 0xa0 != 0x20 {
+            self.ifr &= !IFR_CB2;
+        }
+    }
+
+    // CA2 goes low on a port A read or write in the handshake and pulse output modes
+    fn port_a_handshake(&mut self, cycles: u64) {
+        match (self.pcr >> 1) & 0x07 {
+            PCR_HANDSHAKE => self.set_ca2_out(false, cycles),
+            PCR_PULSE => {
+                self.set_ca2_out(false, cycles);
+                self.ca2_pulse = true;
+            }
+            _ => (),
+        }
+    }
+
+    // CB2 only handshakes on port B writes
+    fn port_b_handshake(&mut self, cycles: u64) {
+        match (self.pcr >> 5) & 0x07 {
+            PCR_HANDSHAKE => self.set_cb2_out(false, cycles),
+            PCR_PULSE => {
+                self.set_cb2_out(false, cycles);
+                self.cb2_pulse = true;
+            }
+            _ => (),
+        }
+    }
+
+    fn port_a_pins(&mut self) -> u8 {
+        let input = self.peripheral.port_a_input(self.ora, self.ddra);
+
+        self.ora & self.ddra | input & !self.ddra
+    }
+
+    fn port_b_pins(&mut self) -> u8 {
+        let (output, ddr) = self.port_b_drive();
+        let input = self.peripheral.port_b_input(output, ddr);
+
+        output & ddr | input & !ddr
+    }
+
+    fn read_port_a(&mut self) -> u8 {
+        if self.acr & ACR_PA_LATCH != 0 {
+            self.ira_latch
+        } else {
+            self.port_a_pins()
+        }
+    }
+
+    // output bits read back from the output register rather than the pins
+    fn read_port_b(&mut self) -> u8 {
+        let (output, ddr) = self.port_b_drive();
+
+        let input = if self.acr & ACR_PB_LATCH != 0 {
+            self.irb_latch
+        } else {
+            self.port_b_pins()
+        };
+
+        output & ddr | input & !ddr
+    }
+
+    // timer 1 takes over PB7 when its output is enabled
+    fn port_b_drive(&self) -> (u8, u8) {
+        if self.acr & ACR_T1_PB7 != 0 {
+            (self.orb & 0x7f | (self.pb7 as u8) << 7, self.ddrb | 0x80)
+        } else {
+            (self.orb, self.ddrb)
+        }
+    }
+
+    fn port_a_output(&mut self, cycles: u64) {
+        self.peripheral.port_a_output(self.ora, self.ddra, cycles);
+        self.sample_peripheral();
+    }
+
+    fn port_b_output(&mut self, cycles: u64) {
+        let (output, ddr) = self.port_b_drive();
+
+        self.peripheral.port_b_output(output, ddr, cycles);
+        self.sample_peripheral();
+    }
+
+    fn sample_peripheral(&mut self) {
+        if let Some(level) = self.peripheral.ca2_input() {
+            self.ca2_input(level);
+        }
+    }
+
+    fn ca2_input(&mut self, level: bool) {
+        if level == self.ca2 {
+            return;
+        }
+
+        self.ca2 = level;
+
+        if self.pcr & 0x08 == 0 && level == (self.pcr & 0x04 != 0) {
+            self.ifr |= IFR_CA2;
+        }
+    }
+
+    fn set_ca2_out(&mut self, level: bool, cycles: u64) {
+        if self.ca2_out != level {
+            self.ca2_out = level;
+            self.peripheral.ca2_output(level, cycles);
+        }
+    }
+
+    fn set_cb2_out(&mut self, level: bool, cycles: u64) {
+        if self.cb2_out != level {
+            self.cb2_out = level;
+            self.peripheral.cb2_output(level, cycles);
+        }
+    }
+}
+
+// a counter which loads from its latch the tick after a write or an underflow, so counts
+// N, N - 1 .. 0, 0xffff and raises its interrupt N + 1.5 cycles after the write
+#[derive(Default)]
+struct Countdown {
+    counter: u16,
+    load: bool,
+}
+
+impl Countdown {
+    // true on underflow, when the counter reloads from latch on the next tick
+    fn step(&mut self, latch: u16) -> bool {
+        if self.load {
+            self.counter = latch;
+            self.load = false;
+
+            return false;
+        }
+
+        self.counter = self.counter.wrapping_sub(1);
+        self.load = self.counter == 0xffff;
+
+        self.load
+    }
+
+    fn ticks_to_underflow(&self, latch: u16) -> u64 {
+        if self.load {
+            latch as u64 + 1
+        } else {
+            self.counter as u64
+        }
+    }
+}
+
+// number of ticks whose phi2 has started by cycles
+fn ticks_before(cycles: u64) -> u64 {
+    cycles.div_ceil(2)
+}
+
+fn phi2_cycles(tick: u64) -> u64 {
+    tick * 2 + 1
+}
+
+// CA2/CB2 control bits after shifting down
+fn manual_output(control: u8) -> Option<bool> {
+    match control & 0x07 {
+        PCR_MANUAL_LOW => Some(false),
+        PCR_MANUAL_HIGH => Some(true),
+        PCR_HANDSHAKE | PCR_PULSE => Some(true),
+        _ => None,
+    }
+}
+
+const VIA_STATE_LEN: usize = 32;
+
+pub const REG_ORB: u8 = 0x0;
+pub const REG_ORA: u8 = 0x1;
+pub const REG_DDRB: u8 = 0x2;
+pub const REG_DDRA: u8 = 0x3;
+pub const REG_T1C_L: u8 = 0x4;
+pub const REG_T1C_H: u8 = 0x5;
+pub const REG_T1L_L: u8 = 0x6;
+pub const REG_T1L_H: u8 = 0x7;
+pub const REG_T2C_L: u8 = 0x8;
+pub const REG_T2C_H: u8 = 0x9;
+pub const REG_SR: u8 = 0xa;
+pub const REG_ACR: u8 = 0xb;
+pub const REG_PCR: u8 = 0xc;
+pub const REG_IFR: u8 = 0xd;
+pub const REG_IER: u8 = 0xe;
+pub const REG_ORA_NO_HANDSHAKE: u8 = 0xf;
+
+pub const IFR_CA2: u8 = 0x01;
+pub const IFR_CA1: u8 = 0x02;
+pub const IFR_SR: u8 = 0x04;
+pub const IFR_CB2: u8 = 0x08;
+pub const IFR_CB1: u8 = 0x10;
+pub const IFR_T2: u8 = 0x20;
+pub const IFR_T1: u8 = 0x40;
+
+const ACR_PA_LATCH: u8 = 0x01;
+const ACR_PB_LATCH: u8 = 0x02;
+const ACR_SR_MODE: u8 = 0x1c;
+const ACR_T2_PULSE_COUNTING: u8 = 0x20;
+const ACR_T1_FREE_RUN: u8 = 0x40;
+const ACR_T1_PB7: u8 = 0x80;
+
+const SR_DISABLED: u8 = 0x00;
+const SR_T2_IN: u8 = 0x04;
+const SR_PHI2_IN: u8 = 0x08;
+const SR_EXTERNAL_IN: u8 = 0x0c;
+const SR_FREE_RUN_OUT: u8 = 0x10;
+const SR_T2_OUT: u8 = 0x14;
+const SR_PHI2_OUT: u8 = 0x18;
+const SR_EXTERNAL_OUT: u8 = 0x1c;
+const SR_OUT: u8 = 0x10;
+
+const PCR_CA1_POSITIVE: u8 = 0x01;
+const PCR_CB1_POSITIVE: u8 = 0x10;
+
+// CA2/CB2 output modes
+const PCR_HANDSHAKE: u8 = 0x04;
+const PCR_PULSE: u8 = 0x05;
+const PCR_MANUAL_LOW: u8 = 0x06;
+const PCR_MANUAL_HIGH: u8 = 0x07;
@@ -0,0 +1,71 @@
+use super::via_6522::Via6522;
+use crate::devices::{DeviceDescription, IODevice, ResetType};
+use crate::shared::Shared;
+use crate::word::Word;
+
+pub struct ViaIODevice {
+    via: Shared<Via6522>,
+    vsync_on_ca1: bool,
+}
+
+impl ViaIODevice {
+    pub fn new(via: Shared<Via6522>) -> Self {
+        ViaIODevice {
+            via,
+            vsync_on_ca1: false,
+        }
+    }
+
+    // the system VIA sees the CRTC's vsync, inverted, on CA1
+    pub fn with_vsync_on_ca1(mut self) -> Self {
+        self.vsync_on_ca1 = true;
+
+        self
+    }
+}
+
+impl IODevice for ViaIODevice {
+    fn read(&mut self, _address: Word, register: u16, cycles: u64) -> u8 {
+        self.via.borrow_mut().read(register as u8, cycles)
+    }
+
+    fn write(&mut self, _address: Word, register: u16, value: u8, cycles: u64) -> bool {
+        self.via.borrow_mut().write(register as u8, value, cycles);
+
+        false
+    }
+
+    fn get_interrupt(&mut self, cycles: u64) -> bool {
+        let mut via = self.via.borrow_mut();
+
+        via.catch_up(cycles);
+
+        via.irq()
+    }
+
+    fn on_vsync_change(&mut self, vsync: bool, cycles: u64) {
+        if self.vsync_on_ca1 {
+            self.via.borrow_mut().set_ca1(!vsync, cycles);
+        }
+    }
+
+    fn peek(&self, _address: Word, register: u16) -> Option<u8> {
+        Some(self.via.borrow().peek(register as u8))
+    }
+
+    fn reset(&mut self, _reset_type: ResetType) {
+        self.via.borrow_mut().reset();
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        Some(self.via.borrow().save_state())
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), String> {
+        self.via.borrow_mut().load_state(state)
+    }
+
+    fn describe(&self) -> Option<DeviceDescription> {
+        Some(self.via.borrow().describe())
+    }
+}
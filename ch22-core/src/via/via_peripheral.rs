@@ -0,0 +1,26 @@
+use crate::shared::MaybeSend;
+
+// whatever is wired to a VIA's ports and control lines
+// inputs are the levels of the pins not driven by the VIA, outputs are called as they change
+pub trait ViaPeripheral: MaybeSend {
+    fn port_a_input(&mut self, _output: u8, _ddr: u8) -> u8 {
+        0xff
+    }
+    fn port_b_input(&mut self, _output: u8, _ddr: u8) -> u8 {
+        0xff
+    }
+    fn port_a_output(&mut self, _output: u8, _ddr: u8, _cycles: u64) {}
+    fn port_b_output(&mut self, _output: u8, _ddr: u8, _cycles: u64) {}
+    fn ca2_output(&mut self, _level: bool, _cycles: u64) {}
+    fn cb2_output(&mut self, _level: bool, _cycles: u64) {}
+    // a CA2 level following the port outputs, resampled as they change
+    // None leaves CA2 to set_ca2
+    fn ca2_input(&mut self) -> Option<bool> {
+        None
+    }
+}
+
+// pulled up ports with nothing attached
+pub struct NoPeripheral;
+
+impl ViaPeripheral for NoPeripheral {}
@@ -0,0 +1,33 @@
+use super::via_6522::Via6522;
+use crate::devices::TimerDevice;
+use crate::shared::Shared;
+
+pub struct ViaTimerDevice {
+    via: Shared<Via6522>,
+}
+
+impl ViaTimerDevice {
+    pub fn new(via: Shared<Via6522>) -> Self {
+        ViaTimerDevice { via }
+    }
+}
+
+impl TimerDevice for ViaTimerDevice {
+    // wakes at the next timer interrupt, or often enough that catching up stays cheap
+    // when a write has started a timer since the last wakeup
+    fn sync(&mut self, cycles: u64) -> Option<u64> {
+        let mut via = self.via.borrow_mut();
+
+        via.catch_up(cycles);
+
+        let next_sync = cycles + VIA_SYNC_INTERVAL;
+
+        Some(
+            via.next_wakeup()
+                .map_or(next_sync, |wakeup| wakeup.min(next_sync)),
+        )
+    }
+}
+
+// maximum number of cycles between wakeups
+pub const VIA_SYNC_INTERVAL: u64 = 2 * 0x10000;
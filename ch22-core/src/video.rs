@@ -8,8 +8,7 @@ mod video_ula_registers_device;
 
 pub const MAX_LINES: usize = 320;
 
-use std::{cell::RefCell, rc::Rc};
-
+use crate::shared::Shared;
 use crtc::Crtc;
 pub use field_data::Field;
 use field_line::FieldLine;
@@ -21,14 +20,20 @@ use video_ula_registers_device::VideoULARegistersDevice;
 #[cfg(test)]
 pub use field_line::flags as field_line_flags;
 
+#[cfg(test)]
+mod tests;
+
 #[derive(Default)]
 pub struct Video {
     field_data: Field,
     crtc: Crtc,
-    registers: Rc<RefCell<VideoRegisters>>,
+    registers: Shared<VideoRegisters>,
     field_counter: u8,
     next_scanline_trigger: u64,
     vsync: bool,
+    warp_snapshot_interval: Option<u32>,
+    warp_field_count: u32,
+    skip_field_snapshot: bool,
 }
 
 impl Video {
@@ -61,10 +66,15 @@ impl Video {
         if snapshot_params.beam_scanline == 0 {
             self.field_counter = self.field_counter.wrapping_add(1);
 
-            self.field_data.clear();
+            self.skip_field_snapshot =
+                is_warp_skipped_field(self.warp_snapshot_interval, &mut self.warp_field_count);
+
+            if !self.skip_field_snapshot {
+                self.field_data.clear();
+            }
         }
 
-        if snapshot_params.is_displayed {
+        if snapshot_params.is_displayed && !self.skip_field_snapshot {
             self.field_data.snapshot_scanline(
                 snapshot_params.beam_scanline as usize,
                 snapshot_params.address,
@@ -91,6 +101,18 @@ impl Video {
         self.crtc.is_beam_reset()
     }
 
+    // in warp mode, crtc timing and vsync are unchanged
+    // but only every nth field is snapshotted
+    pub fn set_warp(&mut self, snapshot_interval: Option<u32>) {
+        self.warp_snapshot_interval = snapshot_interval.map(|interval| interval.max(1));
+        self.warp_field_count = 0;
+
+        // leaving warp part way through a skipped field snapshots the rest of it
+        if snapshot_interval.is_none() {
+            self.skip_field_snapshot = false;
+        }
+    }
+
     pub fn get_next_scanline_trigger(&self) -> u64 {
         self.next_scanline_trigger
     }
@@ -98,4 +120,19 @@ impl Video {
     pub fn get_field_start(&self) -> *const Field {
         &self.field_data as *const Field
     }
+
+    #[cfg(test)]
+    pub fn field(&self) -> &Field {
+        &self.field_data
+    }
+}
+
+fn is_warp_skipped_field(snapshot_interval: Option<u32>, field_count: &mut u32) -> bool {
+    let Some(snapshot_interval) = snapshot_interval else {
+        return false;
+    };
+
+    *field_count = (*field_count + 1) % snapshot_interval;
+
+    *field_count != 0
 }
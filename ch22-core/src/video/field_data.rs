@@ -28,6 +28,11 @@ impl Field {
         }
     }
 
+    #[cfg(test)]
+    pub fn get_line(&self, line_index: usize) -> &FieldLine {
+        &self.lines[line_index]
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn snapshot_scanline<'a>(
         &mut self,
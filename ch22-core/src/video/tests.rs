@@ -0,0 +1,119 @@
+use super::*;
+use crate::video::field_line_flags::DISPLAYED;
+
+fn run_field(video: &mut Video, ram: &[u8]) {
+    while !video.process_scanline(
+        0,
+        |range| &ram[range.start as usize..range.end as usize],
+        |_| {},
+    ) {}
+}
+
+fn first_displayed_char(video: &Video) -> u8 {
+    let line = (0..MAX_LINES)
+        .map(|line_index| video.field_data.get_line(line_index).get_raw_data())
+        .find(|raw_data| raw_data[0] & DISPLAYED != 0)
+        .expect("no displayed lines");
+
+    line[16]
+}
+
+#[test]
+fn it_snapshots_every_field_by_default() {
+    let mut video = Video::default();
+    video.init();
+
+    run_field(&mut video, &[0x00; 0x8000]);
+    assert_eq!(first_displayed_char(&video), 0x00);
+
+    run_field(&mut video, &[0xff; 0x8000]);
+    assert_eq!(first_displayed_char(&video), 0xff);
+}
+
+#[test]
+fn it_only_snapshots_every_nth_field_in_warp_mode() {
+    let mut video = Video::default();
+    video.init();
+
+    run_field(&mut video, &[0x00; 0x8000]);
+
+    video.set_warp(Some(3));
+
+    run_field(&mut video, &[0x11; 0x8000]);
+    assert_eq!(first_displayed_char(&video), 0x00);
+
+    run_field(&mut video, &[0x22; 0x8000]);
+    assert_eq!(first_displayed_char(&video), 0x00);
+
+    run_field(&mut video, &[0x33; 0x8000]);
+    assert_eq!(first_displayed_char(&video), 0x33);
+}
+
+#[test]
+fn it_snapshots_the_rest_of_a_skipped_field_after_leaving_warp_mode() {
+    let mut video = Video::default();
+    video.init();
+
+    video.set_warp(Some(3));
+
+    run_field(&mut video, &[0x00; 0x8000]);
+
+    let ram = [0x11; 0x8000];
+    let process_scanline = |video: &mut Video| {
+        video.process_scanline(
+            0,
+            |range| &ram[range.start as usize..range.end as usize],
+            |_| {},
+        )
+    };
+
+    // the second field starts skipped
+    process_scanline(&mut video);
+
+    video.set_warp(None);
+
+    while !process_scanline(&mut video) {}
+
+    let last_line = (0..MAX_LINES)
+        .rev()
+        .map(|line_index| video.field_data.get_line(line_index).get_raw_data())
+        .find(|raw_data| raw_data[0] & DISPLAYED != 0)
+        .expect("no displayed lines");
+
+    assert_eq!(last_line[16], 0x11);
+}
+
+#[test]
+fn it_keeps_vsync_timing_in_warp_mode() {
+    let mut normal_video = Video::default();
+    normal_video.init();
+
+    let mut warp_video = Video::default();
+    warp_video.init();
+    warp_video.set_warp(Some(4));
+
+    let ram = [0x00; 0x8000];
+
+    for _ in 0..4 {
+        let mut normal_vsyncs = Vec::new();
+        let mut warp_vsyncs = Vec::new();
+
+        while !normal_video.process_scanline(
+            0,
+            |range| &ram[range.start as usize..range.end as usize],
+            |vsync| normal_vsyncs.push(vsync),
+        ) {}
+
+        while !warp_video.process_scanline(
+            0,
+            |range| &ram[range.start as usize..range.end as usize],
+            |vsync| warp_vsyncs.push(vsync),
+        ) {}
+
+        assert_eq!(normal_vsyncs, warp_vsyncs);
+        assert_eq!(
+            normal_video.get_next_scanline_trigger(),
+            warp_video.get_next_scanline_trigger()
+        );
+    }
+}
@@ -1,7 +1,5 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use crate::devices::IODevice;
+use crate::devices::{DeviceDescription, IODevice, ResetType};
+use crate::shared::Shared;
 use crate::video::VideoRegisters;
 use crate::word::Word;
 
@@ -9,12 +7,12 @@ use crate::word::Word;
 mod tests;
 
 pub struct VideoCRTCRegistersDevice {
-    video_crtc_registers: Rc<RefCell<VideoRegisters>>,
+    video_crtc_registers: Shared<VideoRegisters>,
     control_reg: u8,
 }
 
 impl VideoCRTCRegistersDevice {
-    pub fn new(video_crtc_registers: Rc<RefCell<VideoRegisters>>) -> Self {
+    pub fn new(video_crtc_registers: Shared<VideoRegisters>) -> Self {
         VideoCRTCRegistersDevice {
             video_crtc_registers,
             control_reg: 0,
@@ -23,8 +21,8 @@ impl VideoCRTCRegistersDevice {
 }
 
 impl IODevice for VideoCRTCRegistersDevice {
-    fn read(&mut self, address: Word, _cycles: u64) -> u8 {
-        if address.0 & 0x07 == 0x01 {
+    fn read(&mut self, _address: Word, register: u16, _cycles: u64) -> u8 {
+        if register == 1 {
             let registers = self.video_crtc_registers.borrow_mut();
 
             match self.control_reg {
@@ -43,8 +41,8 @@ impl IODevice for VideoCRTCRegistersDevice {
         }
     }
 
-    fn write(&mut self, address: Word, value: u8, _cycles: u64) -> bool {
-        if address.0 & 0x07 == 0x01 {
+    fn write(&mut self, _address: Word, register: u16, value: u8, _cycles: u64) -> bool {
+        if register == 1 {
             let mut registers = self.video_crtc_registers.borrow_mut();
 
             match self.control_reg {
@@ -88,4 +86,71 @@ impl IODevice for VideoCRTCRegistersDevice {
 
         false
     }
+
+    fn peek(&self, _address: Word, register: u16) -> Option<u8> {
+        if register == 1 {
+            let registers = self.video_crtc_registers.borrow();
+
+            match self.control_reg {
+                14 => Some(registers.crtc_r14_cursor_h),
+
+                15 => Some(registers.crtc_r15_cursor_l),
+
+                12 | 13 | 16 | 17 => None,
+
+                _ => Some(0),
+            }
+        } else {
+            Some(0)
+        }
+    }
+
+    // the crtc has no reset input, its registers are reset with the video at power on
+    fn reset(&mut self, _reset_type: ResetType) {
+        self.control_reg = 0;
+    }
+
+    // the address register then R0-R15
+    fn save_state(&self) -> Option<Vec<u8>> {
+        let mut state = vec![self.control_reg];
+
+        state.extend(self.video_crtc_registers.borrow().crtc_registers());
+
+        Some(state)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), String> {
+        let Some((control_reg, registers)) = state.split_first() else {
+            return Err("Empty CRTC state".to_string());
+        };
+
+        let Ok(registers) = registers.try_into() else {
+            return Err(format!("Invalid CRTC state length: {}", state.len()));
+        };
+
+        self.control_reg = *control_reg;
+        self.video_crtc_registers
+            .borrow_mut()
+            .set_crtc_registers(registers);
+
+        Ok(())
+    }
+
+    fn describe(&self) -> Option<DeviceDescription> {
+        let mut registers = vec![("AR".to_string(), self.control_reg as u16)];
+
+        registers.extend(
+            self.video_crtc_registers
+                .borrow()
+                .crtc_registers()
+                .iter()
+                .enumerate()
+                .map(|(index, value)| (format!("R{index}"), *value as u16)),
+        );
+
+        Some(DeviceDescription {
+            name: "CRTC".to_string(),
+            registers,
+        })
+    }
 }
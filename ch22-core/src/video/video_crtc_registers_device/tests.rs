@@ -1,9 +1,7 @@
 #[cfg(test)]
 mod test_video_crtc_registers_device {
-    use std::cell::RefCell;
-    use std::rc::Rc;
-
     use crate::devices::IODevice;
+    use crate::shared::Shared;
     use crate::video::VideoCRTCRegistersDevice;
     use crate::video::VideoRegisters;
     use crate::word::Word;
@@ -31,13 +29,13 @@ mod test_video_crtc_registers_device {
         ];
 
         for (control_reg, write_value, expected_masked) in test_cases {
-            let video_registers = Rc::new(RefCell::new(VideoRegisters::default()));
+            let video_registers = Shared::new(VideoRegisters::default());
             let mut device = VideoCRTCRegistersDevice::new(video_registers.clone());
 
             // Select control register (address low bits != 0x01)
-            device.write(Word::from(0xfe20), control_reg, 0); // sets control_reg = value & 0x1f
+            device.write(Word::from(0xfe20), 0, control_reg, 0); // sets control_reg = value & 0x1f
             // Perform write to selected register (address low bits == 0x01)
-            device.write(Word::from(0xfe21), write_value, 0);
+            device.write(Word::from(0xfe21), 1, write_value, 0);
 
             let regs = video_registers.borrow();
             let actual = regs.get_crtc_register(control_reg);
@@ -58,21 +56,21 @@ mod test_video_crtc_registers_device {
             ..VideoRegisters::default()
         };
 
-        let mut device = VideoCRTCRegistersDevice::new(Rc::new(RefCell::new(video_registers)));
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
 
         // Read r14
-        device.write(Word::from(0xfe20), 14, 0); // select r14
-        let r14_read = device.read(Word::from(0xfe21), 0);
+        device.write(Word::from(0xfe20), 0, 14, 0); // select r14
+        let r14_read = device.read(Word::from(0xfe21), 1, 0);
         assert_eq!(r14_read, 0x3f, "Read of r14 should return value 0x3f");
 
         // Read r15
-        device.write(Word::from(0xfe20), 15, 0); // select r15
-        let r15_read = device.read(Word::from(0xfe21), 0);
+        device.write(Word::from(0xfe20), 0, 15, 0); // select r15
+        let r15_read = device.read(Word::from(0xfe21), 1, 0);
         assert_eq!(r15_read, 0x5a, "Read of r15 should return value 0x5a");
 
         // Reads of other control registers (e.g. r0) should return 0
-        device.write(Word::from(0xfe20), 0, 0); // select r0
-        let r0_read = device.read(Word::from(0xfe21), 0);
+        device.write(Word::from(0xfe20), 0, 0, 0); // select r0
+        let r0_read = device.read(Word::from(0xfe21), 1, 0);
         assert_eq!(r0_read, 0x00, "Non-readable register should return 0");
     }
 
@@ -80,39 +78,60 @@ mod test_video_crtc_registers_device {
     #[should_panic(expected = "not implemented")]
     fn test_read_unimplemented_r12_should_panic() {
         let video_registers = VideoRegisters::default();
-        let mut device = VideoCRTCRegistersDevice::new(Rc::new(RefCell::new(video_registers)));
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
 
-        device.write(Word::from(0xfe20), 12, 0); // select register
-        device.read(Word::from(0xfe21), 0); // should panic
+        device.write(Word::from(0xfe20), 0, 12, 0); // select register
+        device.read(Word::from(0xfe21), 1, 0); // should panic
     }
 
     #[test]
     #[should_panic(expected = "not implemented")]
     fn test_read_unimplemented_r13_should_panic() {
         let video_registers = VideoRegisters::default();
-        let mut device = VideoCRTCRegistersDevice::new(Rc::new(RefCell::new(video_registers)));
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
 
-        device.write(Word::from(0xfe20), 13, 0); // select register
-        device.read(Word::from(0xfe21), 0); // should panic
+        device.write(Word::from(0xfe20), 0, 13, 0); // select register
+        device.read(Word::from(0xfe21), 1, 0); // should panic
     }
 
     #[test]
     #[should_panic(expected = "not implemented")]
     fn test_read_unimplemented_r16_should_panic() {
         let video_registers = VideoRegisters::default();
-        let mut device = VideoCRTCRegistersDevice::new(Rc::new(RefCell::new(video_registers)));
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
 
-        device.write(Word::from(0xfe20), 16, 0); // select register
-        device.read(Word::from(0xfe21), 0); // should panic
+        device.write(Word::from(0xfe20), 0, 16, 0); // select register
+        device.read(Word::from(0xfe21), 1, 0); // should panic
     }
 
     #[test]
     #[should_panic(expected = "not implemented")]
     fn test_read_unimplemented_r17_should_panic() {
         let video_registers = VideoRegisters::default();
-        let mut device = VideoCRTCRegistersDevice::new(Rc::new(RefCell::new(video_registers)));
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
+
+        device.write(Word::from(0xfe20), 0, 17, 0); // select register
+        device.read(Word::from(0xfe21), 1, 0); // should panic
+    }
+
+    #[test]
+    fn test_save_and_load_state_round_trip() {
+        let mut video_registers = VideoRegisters::default();
+        video_registers.reset();
+        let mut device = VideoCRTCRegistersDevice::new(Shared::new(video_registers));
+
+        device.write(Word::from(0xfe20), 0, 12, 0); // select register
+        device.write(Word::from(0xfe21), 1, 0x0b, 0);
+
+        let state = device.save_state().unwrap();
+
+        let mut restored = VideoCRTCRegistersDevice::new(Shared::new(VideoRegisters::default()));
+        restored.load_state(&state).unwrap();
 
-        device.write(Word::from(0xfe20), 17, 0); // select register
-        device.read(Word::from(0xfe21), 0); // should panic
+        assert_eq!(restored.save_state(), Some(state.clone()));
+        let description = restored.describe().unwrap();
+        assert_eq!(description.registers[0], ("AR".to_string(), 12));
+        assert_eq!(description.registers[13], ("R12".to_string(), 0x0b));
+        assert!(restored.load_state(&state[..4]).is_err());
     }
 }
@@ -46,6 +46,48 @@ impl VideoRegisters {
         self.crtc_r15_cursor_l = 0x00;
     }
 
+    pub fn crtc_registers(&self) -> [u8; 16] {
+        [
+            self.crtc_r0_horizontal_total,
+            self.crtc_r1_horizontal_displayed,
+            self.crtc_r2_horizontal_sync_position,
+            self.crtc_r3_sync_width,
+            self.crtc_r4_vertical_total,
+            self.crtc_r5_vertical_total_adjust,
+            self.crtc_r6_vertical_displayed,
+            self.crtc_r7_vertical_sync_position,
+            self.crtc_r8_interlace_and_skew,
+            self.crtc_r9_maximum_raster_address,
+            self.crtc_r10_cursor_start_raster,
+            self.crtc_r11_cursor_end_raster,
+            self.crtc_r12_start_address_h,
+            self.crtc_r13_start_address_l,
+            self.crtc_r14_cursor_h,
+            self.crtc_r15_cursor_l,
+        ]
+    }
+
+    pub fn set_crtc_registers(&mut self, registers: [u8; 16]) {
+        [
+            self.crtc_r0_horizontal_total,
+            self.crtc_r1_horizontal_displayed,
+            self.crtc_r2_horizontal_sync_position,
+            self.crtc_r3_sync_width,
+            self.crtc_r4_vertical_total,
+            self.crtc_r5_vertical_total_adjust,
+            self.crtc_r6_vertical_displayed,
+            self.crtc_r7_vertical_sync_position,
+            self.crtc_r8_interlace_and_skew,
+            self.crtc_r9_maximum_raster_address,
+            self.crtc_r10_cursor_start_raster,
+            self.crtc_r11_cursor_end_raster,
+            self.crtc_r12_start_address_h,
+            self.crtc_r13_start_address_l,
+            self.crtc_r14_cursor_h,
+            self.crtc_r15_cursor_l,
+        ] = registers;
+    }
+
     pub fn set_ula_palette(&mut self, entry: u8, value: u8) {
         let shift = entry * 4;
 
@@ -1,7 +1,5 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use crate::devices::IODevice;
+use crate::devices::{DeviceDescription, IODevice};
+use crate::shared::Shared;
 use crate::video::VideoRegisters;
 use crate::word::Word;
 
@@ -9,22 +7,22 @@ use crate::word::Word;
 mod tests;
 
 pub struct VideoULARegistersDevice {
-    video_registers: Rc<RefCell<VideoRegisters>>,
+    video_registers: Shared<VideoRegisters>,
 }
 
 impl VideoULARegistersDevice {
-    pub fn new(video_registers: Rc<RefCell<VideoRegisters>>) -> Self {
+    pub fn new(video_registers: Shared<VideoRegisters>) -> Self {
         VideoULARegistersDevice { video_registers }
     }
 }
 
 impl IODevice for VideoULARegistersDevice {
-    fn read(&mut self, _address: Word, _cycles: u64) -> u8 {
+    fn read(&mut self, _address: Word, _register: u16, _cycles: u64) -> u8 {
         0xfe
     }
 
-    fn write(&mut self, address: Word, value: u8, _cycles: u64) -> bool {
-        if address.0 & 0x03 == 0x01 {
+    fn write(&mut self, _address: Word, register: u16, value: u8, _cycles: u64) -> bool {
+        if register == 1 {
             let entry = (value & 0xf0) >> 4;
             let value = (value & 0x0f) ^ 7;
 
@@ -37,4 +35,52 @@ impl IODevice for VideoULARegistersDevice {
 
         false
     }
+
+    fn peek(&self, _address: Word, _register: u16) -> Option<u8> {
+        Some(0xfe)
+    }
+
+    // the control register then the palette, little endian
+    fn save_state(&self) -> Option<Vec<u8>> {
+        let registers = self.video_registers.borrow();
+        let palette = registers.ula_palette;
+
+        let mut state = vec![registers.ula_control];
+
+        state.extend(palette.to_le_bytes());
+
+        Some(state)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), String> {
+        let Ok([control, palette @ ..]) = <[u8; 9]>::try_from(state) else {
+            return Err(format!("Invalid video ULA state length: {}", state.len()));
+        };
+
+        let mut registers = self.video_registers.borrow_mut();
+
+        registers.ula_control = control;
+        registers.ula_palette = u64::from_le_bytes(palette);
+
+        Ok(())
+    }
+
+    fn describe(&self) -> Option<DeviceDescription> {
+        let registers = self.video_registers.borrow();
+        let palette = registers.ula_palette;
+
+        let mut description = vec![("control".to_string(), registers.ula_control as u16)];
+
+        description.extend((0..16).map(|entry| {
+            (
+                format!("palette{entry}"),
+                ((palette >> (entry * 4)) & 0x0f) as u16,
+            )
+        }));
+
+        Some(DeviceDescription {
+            name: "Video ULA".to_string(),
+            registers: description,
+        })
+    }
 }
@@ -1,9 +1,8 @@
 use crate::devices::IODevice;
+use crate::shared::Shared;
 use crate::video::VideoRegisters;
 use crate::video::VideoULARegistersDevice;
 use crate::word::Word;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 #[cfg(test)]
 mod test_video_ula_registers_device {
@@ -24,12 +23,12 @@ mod test_video_ula_registers_device {
         ];
 
         for (address, value, initial_palette, expected_palette) in test_cases {
-            let video_registers = Rc::new(RefCell::new(VideoRegisters::default()));
+            let video_registers = Shared::new(VideoRegisters::default());
             video_registers.borrow_mut().ula_palette = initial_palette;
 
             let mut device = VideoULARegistersDevice::new(video_registers.clone());
 
-            device.write(Word::from(address), value, 0);
+            device.write(Word::from(address), address & 0x01, value, 0);
 
             let actual_palette = video_registers.borrow().ula_palette;
 
@@ -43,24 +42,26 @@ mod test_video_ula_registers_device {
 
     #[test]
     fn test_write_control_register() {
+        let unchanged = VideoRegisters::default().ula_control;
+
         let test_cases = [
-            // (address, value)
-            (0xfe20, 0x9c),
-            (0xfe22, 0x1e),
-            (0xfe23, 0xab),
-            (0xfe20, 0x00),
-            (0xfe22, 0xff),
+            // (address, value, expected_control)
+            (0xfe20, 0x9c, 0x9c),
+            (0xfe22, 0x1e, 0x1e),
+            (0xfe23, 0xab, unchanged), // odd addresses mirror the palette register
+            (0xfe20, 0x00, 0x00),
+            (0xfe22, 0xff, 0xff),
         ];
 
-        for (address, value) in test_cases {
-            let video_registers = Rc::new(RefCell::new(VideoRegisters::default()));
+        for (address, value, expected_control) in test_cases {
+            let video_registers = Shared::new(VideoRegisters::default());
             let mut device = VideoULARegistersDevice::new(video_registers.clone());
 
-            device.write(Word::from(address), value, 0);
+            device.write(Word::from(address), address & 0x01, value, 0);
 
             assert_eq!(
                 video_registers.borrow().ula_control,
-                value,
+                expected_control,
                 "Failed for address=0x{:04x}, value=0x{:02x}",
                 address,
                 value
@@ -68,17 +69,31 @@ mod test_video_ula_registers_device {
         }
     }
 
+    #[test]
+    fn test_write_palette_through_mirror() {
+        let video_registers = Shared::new(VideoRegisters::default());
+        video_registers.borrow_mut().ula_palette = 0x1234567890abcdef;
+
+        let mut device = VideoULARegistersDevice::new(video_registers.clone());
+
+        device.write(Word::from(0xfe23), 0x01, 0x54, 0);
+
+        let actual_palette = video_registers.borrow().ula_palette;
+
+        assert_eq!(actual_palette, 0x12345678903bcdef);
+    }
+
     #[test]
     fn test_reads() {
         let test_cases = [
             0xfe20, 0xfe21, 0xfe22, 0xfe23, 0x0000, 0xffff, 0x1234, 0xabcd,
         ];
 
-        let video_registers = Rc::new(RefCell::new(VideoRegisters::default()));
+        let video_registers = Shared::new(VideoRegisters::default());
         let mut device = VideoULARegistersDevice::new(video_registers);
 
         for address in test_cases {
-            let result = device.read(Word::from(address), 0);
+            let result = device.read(Word::from(address), address & 0x01, 0);
             assert_eq!(result, 0xfe, "Failed for address=0x{:04x}", address);
         }
     }
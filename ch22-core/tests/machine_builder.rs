@@ -0,0 +1,110 @@
+use std::sync::{Arc, Mutex};
+
+use ch22_core::{DeviceSpeed, FnAddressMap, IOAddressRange, IODevice, MachineBuilder, Word};
+
+// address, register, value, cycles
+type Writes = Arc<Mutex<Vec<(u16, u16, u8, u64)>>>;
+
+#[derive(Default)]
+struct RecordingDevice {
+    writes: Writes,
+}
+
+impl IODevice for RecordingDevice {
+    fn read(&mut self, _address: Word, _register: u16, _cycles: u64) -> u8 {
+        0x5a
+    }
+
+    fn write(&mut self, address: Word, register: u16, value: u8, cycles: u64) -> bool {
+        self.writes
+            .lock()
+            .unwrap()
+            .push((address.into(), register, value, cycles));
+
+        false
+    }
+}
+
+#[test]
+fn it_runs_a_custom_machine_with_flat_ram_and_an_io_device() {
+    let device = RecordingDevice::default();
+    let writes = device.writes.clone();
+
+    let mut machine = MachineBuilder::new(FnAddressMap {
+        read: |address, clock, ram, _roms, io_space| match address.1 {
+            0xfe => io_space.read(address, clock),
+            _ => ram.read(address),
+        },
+        write: |address, value, clock, ram, io_space| match address.1 {
+            0xfe => io_space.write(address, value, clock),
+            _ => ram.write(address, value),
+        },
+    })
+    .ram_size(0x10000)
+    .io_device(
+        IOAddressRange::mirrored(0xfe00, 0x10, 0x03),
+        Box::new(device),
+        None,
+        DeviceSpeed::TwoMhz,
+    )
+    .build();
+
+    let program = [
+        0xad, 0x01, 0xfe, // LDA &FE01
+        0x8d, 0x00, 0xfe, // STA &FE00
+        0x8d, 0x0d, 0xfe, // STA &FE0D (mirror of register 1)
+        0x4c, 0x09, 0x02, // JMP &0209
+    ];
+
+    for (offset, value) in program.iter().enumerate() {
+        machine
+            .ram_mut()
+            .write(Word::from(0x0200 + offset as u16), *value);
+    }
+
+    machine.ram_mut().write(Word::from(0xfffc), 0x00);
+    machine.ram_mut().write(Word::from(0xfffd), 0x02);
+
+    // 7 reset cycles, then 4 for the LDA
+    machine.reset();
+    machine.run(100);
+
+    assert_eq!(
+        *writes.lock().unwrap(),
+        [(0xfe00, 0, 0x5a, 15), (0xfe0d, 1, 0x5a, 19)]
+    );
+}
+
+#[test]
+fn it_reads_from_roms_of_a_custom_size() {
+    let mut machine = MachineBuilder::new(FnAddressMap {
+        read: |address, _clock, ram, roms, _io_space| match address.1 {
+            0xf0.. => roms[0].read(address.rebased_to(0xf0)),
+            _ => ram.read(address),
+        },
+        write: |address, value, _clock, ram, _io_space| {
+            if address.1 < 0xf0 {
+                ram.write(address, value)
+            }
+        },
+    })
+    .ram_size(0xf000)
+    .roms(1, 0x1000)
+    .build();
+
+    let mut rom = [0xea; 0x1000]; // NOP
+    rom[0x000..0x005].copy_from_slice(&[
+        0xa9, 0x99, // LDA #&99
+        0x85, 0x70, // STA &70
+        0x00, // BRK
+    ]);
+    rom[0xffc] = 0x00; // reset vector &F000
+    rom[0xffd] = 0xf0;
+
+    machine.load_rom(0, &rom);
+
+    machine.reset();
+    machine.run(20);
+
+    assert_eq!(machine.ram().slice(0x70..0x71), [0x99]);
+}
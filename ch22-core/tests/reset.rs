@@ -0,0 +1,44 @@
+use ch22_core::cpu::{Cpu, CpuIOMock};
+
+#[test]
+fn it_runs_the_reset_sequence_with_dummy_stack_reads() {
+    let mut io = CpuIOMock::new(
+        &vec![
+            (0x0000, 0xea),
+            (0x0100, 0x00),
+            (0x01ff, 0x00),
+            (0x01fe, 0x00),
+            (0x01fd, 0x00),
+            (0x01fc, 0x00),
+            (0x01fb, 0x00),
+            (0x1234, 0xea),
+            (0xfffc, 0x34),
+            (0xfffd, 0x12),
+        ],
+        None,
+        None,
+    );
+
+    let mut cpu = Cpu::default();
+
+    cpu.reset(&mut io);
+
+    let addresses: Vec<u16> = io.cycles.iter().map(|(address, _, _)| *address).collect();
+
+    assert_eq!(
+        addresses,
+        [0x0000, 0x0000, 0x0100, 0x01ff, 0x01fe, 0xfffc, 0xfffd]
+    );
+    assert_eq!(u16::from(cpu.get_program_counter()), 0x1234);
+
+    io.cycles.clear();
+    cpu.reset(&mut io);
+
+    let addresses: Vec<u16> = io.cycles.iter().map(|(address, _, _)| *address).collect();
+
+    // the stack pointer carries on down from the first reset
+    assert_eq!(
+        addresses,
+        [0x1234, 0x1234, 0x01fd, 0x01fc, 0x01fb, 0xfffc, 0xfffd]
+    );
+}